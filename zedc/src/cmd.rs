@@ -2,8 +2,12 @@
 
 use std::process::Command;
 
+use crate::code::{Channel, Commands as CodeCommands, Distribution};
+use crate::mock::Commands as MockCommands;
 use crate::output::OutputFormat;
+use crate::sandbox::{Commands as SandboxCommands, DEFAULT_SANDBOX};
 use crate::test::Commands as TestCommands;
+use crate::vsix::Commands as VsixCommands;
 use anyhow::Result;
 use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::{generate, Shell};
@@ -11,30 +15,206 @@ use clap_complete::{generate, Shell};
 /// Configuration options for test commands
 #[derive(Parser)]
 pub struct TestConfig {
+    #[command(flatten)]
+    pub vscode: VsCodeConfig,
+
     #[arg(
-        default_value = None,
-        default_missing_value = Some("latest"),
-        help = "Installs the given version of Zowe CLI",
+        help = "Test against the `engines.vscode` floor of the extensions and the latest VS Code",
+        long,
         global = true,
+        conflicts_with = "vsc_version"
+    )]
+    pub matrix: bool,
+
+    #[arg(
+        help = "Check each sandbox headlessly instead of launching VS Code",
         long,
-        value_name = "VERSION",
-        num_args = 0
+        global = true
     )]
-    pub install_cli: Option<String>,
+    pub smoke: bool,
+
+    #[command(flatten)]
+    pub sandbox: SandboxConfig,
+}
 
+/// Options that select the copy of VS Code used by `test` and `pr` commands
+#[derive(clap::Args, Clone, Default)]
+pub struct VsCodeConfig {
     #[arg(
         default_value = None,
-        help = "The VS Code version to use for testing",
+        help = "The VS Code version to use for testing (default: latest), or `compatible` to satisfy `engines.vscode` of the extensions; `test` accepts a comma-separated list",
         long,
         value_name = "VERSION",
         global = true
     )]
     pub vsc_version: Option<String>,
+
+    #[arg(
+        help = "Use a local VS Code archive (.zip or .tar.gz) instead of downloading one",
+        long,
+        value_name = "PATH",
+        global = true,
+        conflicts_with = "vsc_version"
+    )]
+    pub vscode_archive: Option<std::path::PathBuf>,
+
+    #[arg(
+        help = "The VS Code release channel to use",
+        long,
+        value_enum,
+        default_value_t,
+        global = true
+    )]
+    pub channel: Channel,
+
+    #[arg(
+        help = "The VS Code distribution to use",
+        long,
+        value_enum,
+        default_value_t,
+        global = true
+    )]
+    pub distribution: Distribution,
+
+    #[arg(
+        help = "Serve the sandbox as VS Code for the Web using the VS Code server build",
+        long,
+        global = true
+    )]
+    pub web: bool,
+}
+
+/// Configuration options for the VS Code sandbox launched by `test` and `pr` commands
+#[derive(clap::Args, Default)]
+pub struct SandboxConfig {
+    #[arg(
+        default_value = DEFAULT_SANDBOX,
+        help = "The sandbox to install extensions into and launch",
+        long = "sandbox",
+        value_name = "NAME",
+        global = true
+    )]
+    pub name: String,
+
+    #[arg(
+        help = "A setting to merge into the sandbox settings.json (value is parsed as JSON if possible)",
+        long = "setting",
+        value_name = "KEY=VALUE",
+        global = true
+    )]
+    pub settings: Vec<String>,
+
+    #[arg(
+        help = "A JSON file of settings to merge into the sandbox settings.json",
+        long,
+        value_name = "PATH",
+        global = true
+    )]
+    pub settings_file: Option<std::path::PathBuf>,
+
+    #[arg(
+        help = "An environment variable to pass to VS Code",
+        long,
+        value_name = "KEY=VALUE",
+        global = true
+    )]
+    pub env: Vec<String>,
+
+    #[arg(
+        help = "The display language to launch VS Code with (e.g. `de`, requires its language pack)",
+        long,
+        value_name = "LOCALE",
+        global = true
+    )]
+    pub locale: Option<String>,
+
+    #[arg(
+        help = "Seed the sandbox .zowe folder from a built-in template (zosmf, ftp, ssh, base, mock) or a zowe.config.json file",
+        long,
+        value_name = "TEMPLATE|FILE",
+        global = true
+    )]
+    pub zowe_config: Option<String>,
+
+    #[arg(
+        help = "The host to fill into the Zowe config template (default: $ZOWE_OPT_HOST)",
+        long,
+        value_name = "HOST",
+        global = true
+    )]
+    pub zowe_host: Option<String>,
+
+    #[arg(
+        help = "The port to fill into the Zowe config template (default: $ZOWE_OPT_PORT)",
+        long,
+        value_name = "PORT",
+        global = true
+    )]
+    pub zowe_port: Option<u16>,
+
+    #[arg(
+        help = "The user to fill into the Zowe config template (default: $ZOWE_OPT_USER)",
+        long,
+        value_name = "USER",
+        global = true
+    )]
+    pub zowe_user: Option<String>,
+
+    #[arg(
+        help = "Install the given version of Zowe CLI (default: latest) into the sandbox and put it on the PATH of VS Code",
+        long,
+        value_name = "VERSION",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "latest",
+        global = true
+    )]
+    pub install_cli: Option<String>,
+
+    #[arg(
+        help = "A Zowe CLI plugin to install into the sandbox (e.g. `@zowe/zos-ftp-for-zowe-cli@3.0.0`)",
+        long = "cli-plugin",
+        value_name = "NAME[@VERSION]",
+        requires = "install_cli",
+        global = true
+    )]
+    pub cli_plugins: Vec<String>,
+
+    #[arg(
+        help = "Stay attached until the VS Code window is closed, reporting Zowe Explorer activation errors",
+        long,
+        global = true
+    )]
+    pub wait: bool,
+
+    #[arg(
+        help = "Launch VS Code with the extension host debugger listening on PORT (default: 9229) and print an attach configuration",
+        long,
+        value_name = "PORT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "9229",
+        global = true
+    )]
+    pub inspect: Option<u16>,
+
+    #[arg(
+        help = "Also write the attach configuration to .vscode/launch.json in the repository",
+        long,
+        requires = "inspect",
+        global = true
+    )]
+    pub launch_json: bool,
 }
 
 /// Root commands available in the Zowe Explorer development CLI
 #[derive(Subcommand)]
 pub enum RootCommands {
+    /// List, fetch and prune the cached copies of VS Code used by sandboxes
+    Code {
+        #[command(subcommand)]
+        subcommand: CodeCommands,
+    },
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for
@@ -43,6 +223,11 @@ pub enum RootCommands {
     },
     /// Check that installed tool versions satisfy the project's requirements
     Doctor,
+    /// Serve mock mainframe services (z/OSMF) backed by local fixtures
+    Mock {
+        #[command(subcommand)]
+        subcommand: MockCommands,
+    },
     /// Run package manager commands
     PkgMgr {
         /// Arguments to pass to the package manager
@@ -53,15 +238,21 @@ pub enum RootCommands {
     Pr {
         /// GitHub pull request number
         pr_number: u64,
-        /// VS Code version to use for the sandbox (default: latest)
-        #[arg(long, value_name = "VERSION")]
-        vsc_version: Option<String>,
+        #[command(flatten)]
+        vscode: VsCodeConfig,
         /// Skip dependency installation (reuse existing node_modules)
         #[arg(long)]
         skip_setup: bool,
         /// Always build from source, ignoring any VSIX artifact posted on the PR
         #[arg(long)]
         build: bool,
+        #[command(flatten)]
+        sandbox: SandboxConfig,
+    },
+    /// Inspect, reset or delete the VS Code sandboxes created by `test` and `pr`
+    Sandbox {
+        #[command(subcommand)]
+        subcommand: SandboxCommands,
     },
     /// Set up the development environment
     Setup {
@@ -84,6 +275,11 @@ pub enum RootCommands {
     },
     /// Print version information
     Version,
+    /// Inspect .vsix extension packages
+    Vsix {
+        #[command(subcommand)]
+        subcommand: VsixCommands,
+    },
 }
 
 /// Command-line arguments for the zedc tool
@@ -0,0 +1,313 @@
+//! Functions for inspecting and cleaning up the VS Code copies cached in `zedc_data`.
+
+use super::flavor::Flavor;
+use crate::sandbox::Sandbox;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Name of the marker file that records when a cached copy of VS Code was last used.
+const CACHE_MARKER: &str = ".zedc-cache.json";
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheMarker {
+    last_used: Option<u64>,
+}
+
+/// A copy of VS Code extracted into `zedc_data/<flavor>-<version>` (e.g. `vscode-1.90.0`).
+#[derive(Serialize)]
+pub struct CachedVsCode {
+    /// The name of the cache folder (e.g. `vscode-insider-1.102.0-abc1234`).
+    pub key: String,
+    /// The distribution and channel of the cached copy.
+    pub flavor: Flavor,
+    /// The product version read from the extracted copy, if it could be determined.
+    pub version: Option<String>,
+    pub path: String,
+    pub size_bytes: u64,
+    /// Unix timestamp of the last time a sandbox was prepared with this copy.
+    pub last_used: Option<u64>,
+}
+
+/// Records that the VS Code copy in the given directory was just used.
+pub fn mark_used(vsc_path: &Path) -> anyhow::Result<()> {
+    let marker = CacheMarker {
+        last_used: Some(crate::util::unix_now()),
+    };
+    std::fs::write(vsc_path.join(CACHE_MARKER), serde_json::to_string(&marker)?)?;
+    Ok(())
+}
+
+/// Returns the last-used time for a cached copy, falling back to the folder's modification time.
+fn last_used(vsc_path: &Path) -> Option<u64> {
+    std::fs::read_to_string(vsc_path.join(CACHE_MARKER))
+        .ok()
+        .and_then(|s| serde_json::from_str::<CacheMarker>(&s).ok())
+        .and_then(|m| m.last_used)
+        .or_else(|| {
+            std::fs::metadata(vsc_path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+        })
+}
+
+/// Returns the cache directory for the given version of a VS Code flavor.
+pub fn cache_dir(flavor: Flavor, version: &str) -> anyhow::Result<PathBuf> {
+    Ok(crate::util::data_dir()?.join(flavor.cache_key(version)))
+}
+
+/// Returns the cache directory for a version given on the command line.
+/// Accepts either a bare stable VS Code version (`1.90.0`) or a folder name (`vscodium-1.101.24242`).
+pub fn resolve_cache_dir(name: &str) -> anyhow::Result<PathBuf> {
+    match Flavor::parse_cache_key(name) {
+        Some((flavor, version)) => cache_dir(flavor, version),
+        None => cache_dir(Flavor::default(), name),
+    }
+}
+
+/// Lists all cached copies of VS Code, most recently used first.
+pub fn list_cached() -> anyhow::Result<Vec<CachedVsCode>> {
+    list_cached_in(&crate::util::data_dir()?)
+}
+
+/// Lists the cached copies of VS Code in a data directory, most recently used first.
+fn list_cached_in(data_dir: &Path) -> anyhow::Result<Vec<CachedVsCode>> {
+    if !data_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut cached = Vec::new();
+    for entry in std::fs::read_dir(data_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some((flavor, _)) = Flavor::parse_cache_key(&name) else {
+            continue;
+        };
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let path = entry.path();
+        cached.push(CachedVsCode {
+            version: super::cli_product_version(&flavor.cli_binary(&path)),
+            key: name,
+            flavor,
+            path: path.to_string_lossy().into_owned(),
+            size_bytes: crate::util::dir_size(&path),
+            last_used: last_used(&path),
+        });
+    }
+    cached.sort_by_key(|c| std::cmp::Reverse(c.last_used));
+    Ok(cached)
+}
+
+/// A cached copy of VS Code selected by `zedc code prune`, with the sandboxes that still use it.
+pub struct PruneCandidate<'a> {
+    pub cached: &'a CachedVsCode,
+    /// The names of the sandboxes whose recorded Code CLI binary is in this copy.
+    pub sandboxes: Vec<String>,
+}
+
+/// The cached copies of VS Code that `zedc code prune` removes and keeps.
+pub struct PruneSelection<'a> {
+    pub remove: Vec<PruneCandidate<'a>>,
+    /// Copies that would be removed, but are kept because sandboxes still use them.
+    pub in_use: Vec<PruneCandidate<'a>>,
+}
+
+/// Selects the cached copies to remove: all but the `keep` most recently used ones. Copies that
+/// sandboxes still use are kept, unless `all` is set.
+///
+/// # Arguments
+/// * `cached` - The cached copies, most recently used first (see [`list_cached`])
+/// * `keep` - The number of recently used copies to keep
+/// * `all` - Whether to also remove copies that sandboxes still use
+/// * `sandboxes` - The sandboxes to check for copies in use
+pub fn prune_selection<'a>(
+    cached: &'a [CachedVsCode],
+    keep: usize,
+    all: bool,
+    sandboxes: &[Sandbox],
+) -> PruneSelection<'a> {
+    let mut selection = PruneSelection {
+        remove: Vec::new(),
+        in_use: Vec::new(),
+    };
+    for cached in cached.iter().skip(keep) {
+        let candidate = PruneCandidate {
+            cached,
+            sandboxes: sandboxes
+                .iter()
+                .filter(|sandbox| {
+                    sandbox
+                        .metadata()
+                        .vscode_bin
+                        .is_some_and(|bin| Path::new(&bin).starts_with(&cached.path))
+                })
+                .map(|sandbox| sandbox.name.clone())
+                .collect(),
+        };
+        if candidate.sandboxes.is_empty() || all {
+            selection.remove.push(candidate);
+        } else {
+            selection.in_use.push(candidate);
+        }
+    }
+    selection
+}
+
+/// Returns archives left behind in `zedc_data` by completed downloads. With `include_partial`,
+/// the `.part` files of interrupted downloads are included as well; otherwise they are kept so
+/// that the download can resume.
+pub fn stray_archives(include_partial: bool) -> anyhow::Result<Vec<PathBuf>> {
+    stray_archives_in(&crate::util::data_dir()?, include_partial)
+}
+
+/// Returns the archives left behind in a data directory. See [`stray_archives`].
+fn stray_archives_in(data_dir: &Path, include_partial: bool) -> anyhow::Result<Vec<PathBuf>> {
+    if !data_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut archives = Vec::new();
+    for entry in std::fs::read_dir(data_dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if name.ends_with(".part") && !include_partial {
+            continue;
+        }
+        if path.is_file()
+            && [".zip", ".tar.gz", ".tgz", ".bin", ".part"]
+                .iter()
+                .any(|ext| name.ends_with(ext))
+        {
+            archives.push(path);
+        }
+    }
+    archives.sort();
+    Ok(archives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a cached copy of VS Code that was last used at the given time.
+    fn cache(data_dir: &Path, key: &str, last_used: u64) -> PathBuf {
+        let dir = data_dir.join(key);
+        std::fs::create_dir_all(&dir).unwrap();
+        let marker = CacheMarker {
+            last_used: Some(last_used),
+        };
+        std::fs::write(
+            dir.join(CACHE_MARKER),
+            serde_json::to_string(&marker).unwrap(),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn lists_cached_copies_by_last_use() {
+        let dir = tempfile::tempdir().unwrap();
+        cache(dir.path(), "vscode-1.90.0", 100);
+        cache(dir.path(), "vscode-insider-1.102.0-0d0ab1b", 300);
+        cache(dir.path(), "vscodium-1.101.24242", 200);
+        // Copies cached before `latest` was resolved to a version
+        cache(dir.path(), "vscode-latest", 50);
+        std::fs::create_dir_all(dir.path().join("sandboxes")).unwrap();
+        std::fs::write(dir.path().join("vscode-1.95.0"), "").unwrap();
+
+        let keys: Vec<_> = list_cached_in(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|c| c.key)
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "vscode-insider-1.102.0-0d0ab1b",
+                "vscodium-1.101.24242",
+                "vscode-1.90.0",
+                "vscode-latest"
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_partial_downloads_unless_requested() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "vscode-1.90.0-linux-x64.tar.gz",
+            "vscode-1.91.0-win32-x64.zip",
+            "vscode-1.92.0-linux-x64.tar.gz.part",
+            "notes.txt",
+        ] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let names = |include_partial| -> Vec<String> {
+            stray_archives_in(dir.path(), include_partial)
+                .unwrap()
+                .iter()
+                .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+        assert_eq!(
+            names(false),
+            [
+                "vscode-1.90.0-linux-x64.tar.gz",
+                "vscode-1.91.0-win32-x64.zip"
+            ]
+        );
+        assert_eq!(
+            names(true),
+            [
+                "vscode-1.90.0-linux-x64.tar.gz",
+                "vscode-1.91.0-win32-x64.zip",
+                "vscode-1.92.0-linux-x64.tar.gz.part"
+            ]
+        );
+    }
+
+    #[test]
+    fn selects_copies_to_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        cache(dir.path(), "vscode-1.95.0", 400);
+        cache(dir.path(), "vscode-1.94.0", 300);
+        let used = cache(dir.path(), "vscode-1.93.0", 200);
+        cache(dir.path(), "vscode-latest", 100);
+
+        let sandboxes_dir = dir.path().join("sandboxes");
+        for name in ["default", "pr-1234"] {
+            std::fs::create_dir_all(sandboxes_dir.join(name)).unwrap();
+        }
+        let sandboxes = Sandbox::list_in(&sandboxes_dir).unwrap();
+        let mut metadata = sandboxes[1].metadata();
+        metadata.vscode_bin = Some(
+            used.join("VSCode-linux-x64/bin/code")
+                .to_string_lossy()
+                .into_owned(),
+        );
+        sandboxes[1].save_metadata(&metadata).unwrap();
+
+        let cached = list_cached_in(dir.path()).unwrap();
+        let keys = |candidates: &[PruneCandidate]| -> Vec<String> {
+            candidates.iter().map(|c| c.cached.key.clone()).collect()
+        };
+        let selection = prune_selection(&cached, 1, false, &sandboxes);
+        assert_eq!(keys(&selection.remove), ["vscode-1.94.0", "vscode-latest"]);
+        assert_eq!(keys(&selection.in_use), ["vscode-1.93.0"]);
+        assert_eq!(selection.in_use[0].sandboxes, ["pr-1234"]);
+
+        let selection = prune_selection(&cached, 1, true, &sandboxes);
+        assert_eq!(
+            keys(&selection.remove),
+            ["vscode-1.94.0", "vscode-1.93.0", "vscode-latest"]
+        );
+        assert_eq!(selection.remove[1].sandboxes, ["pr-1234"]);
+        assert!(selection.in_use.is_empty());
+
+        assert!(prune_selection(&cached, 4, false, &sandboxes)
+            .remove
+            .is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+//! Command module for handling `code` commands.
+
+use super::cache::{list_cached, prune_selection, resolve_cache_dir, stray_archives, CachedVsCode};
+use super::flavor::{Channel, Distribution};
+use crate::cmd::VsCodeConfig;
+use crate::output::{self, exit};
+use crate::sandbox::Sandbox;
+use crate::util::{format_age, format_bytes};
+use anyhow::{bail, Context};
+use owo_colors::OwoColorize;
+
+#[derive(clap::Subcommand)]
+pub enum Commands {
+    /// List the cached copies of VS Code with their version, size and last-used time
+    #[command(alias = "ls")]
+    List,
+    /// Download a VS Code version into the cache without launching a sandbox
+    Fetch {
+        /// The VS Code version to download
+        #[arg(default_value = "latest")]
+        version: String,
+        /// Import a local VS Code archive (.zip or .tar.gz) into the cache instead of downloading
+        #[arg(long, value_name = "PATH", conflicts_with = "version")]
+        archive: Option<std::path::PathBuf>,
+        /// The release channel to download from
+        #[arg(long, value_enum, default_value_t)]
+        channel: Channel,
+        /// The VS Code distribution to download
+        #[arg(long, value_enum, default_value_t)]
+        distribution: Distribution,
+        /// Download the VS Code server build used by `--web` instead of the desktop build
+        #[arg(long, conflicts_with = "archive")]
+        web: bool,
+    },
+    /// Remove all but the most recently used copies of VS Code
+    Prune {
+        /// Number of recently used copies to keep
+        #[arg(long, value_name = "N", default_value_t = 2)]
+        keep: usize,
+        /// Also remove partial downloads and copies that sandboxes still use
+        #[arg(long)]
+        all: bool,
+    },
+    /// Remove a cached VS Code version
+    #[command(alias = "rm")]
+    Remove {
+        /// The VS Code version or cache key (e.g. `vscodium-1.101.24242`) to remove
+        version: String,
+    },
+}
+
+/// Removes a cached copy of VS Code from disk.
+fn remove_cached(cached: &CachedVsCode) -> anyhow::Result<()> {
+    std::fs::remove_dir_all(&cached.path)
+        .with_context(|| format!("Failed to remove {}", cached.path))
+}
+
+/// Handles the logic for the `zedc code` command.
+///
+/// # Arguments
+/// * `cmd` - The `code` subcommand to run
+pub async fn handle_cmd(cmd: Commands) -> anyhow::Result<i32> {
+    let json = output::json_enabled();
+
+    match cmd {
+        Commands::List => {
+            let cached = list_cached()?;
+            if json {
+                output::emit_json(&serde_json::json!({ "cached": cached }));
+            } else if cached.is_empty() {
+                println!("No cached VS Code versions found.");
+            } else {
+                println!(
+                    "{}",
+                    format!(
+                        "{:<32} {:<16} {:>10}  {}",
+                        "CACHE KEY", "VERSION", "SIZE", "LAST USED"
+                    )
+                    .bold()
+                );
+                for c in &cached {
+                    println!(
+                        "{:<32} {:<16} {:>10}  {}",
+                        c.key,
+                        c.version.as_deref().unwrap_or("unknown"),
+                        format_bytes(c.size_bytes),
+                        c.last_used
+                            .map(format_age)
+                            .unwrap_or_else(|| "never".to_owned())
+                    );
+                }
+            }
+        }
+        Commands::Fetch {
+            version,
+            archive,
+            channel,
+            distribution,
+            web,
+        } => {
+            let vsc = super::download_vscode(&VsCodeConfig {
+                vsc_version: Some(version),
+                vscode_archive: archive,
+                channel,
+                distribution,
+                web,
+            })
+            .await?;
+            if json {
+                output::emit_json(&serde_json::json!({
+                    "ok": true,
+                    "command": "code fetch",
+                    "vscode": vsc,
+                }));
+            } else {
+                println!("✔️  VS Code {} is ready", vsc.version.bold());
+            }
+        }
+        Commands::Prune { keep, all } => {
+            let cached = list_cached()?;
+            let selection = prune_selection(&cached, keep, all, &Sandbox::list()?);
+            let mut removed = Vec::new();
+            let mut freed = 0;
+            for candidate in &selection.in_use {
+                if !json {
+                    println!(
+                        "⚠️  Keeping VS Code {}, which is used by sandbox {}",
+                        candidate.cached.key.bold(),
+                        candidate.sandboxes.join(", ")
+                    );
+                }
+            }
+            for candidate in &selection.remove {
+                let c = candidate.cached;
+                if !json && !candidate.sandboxes.is_empty() {
+                    println!(
+                        "⚠️  Removing VS Code {}, which is used by sandbox {}",
+                        c.key.bold(),
+                        candidate.sandboxes.join(", ")
+                    );
+                }
+                remove_cached(c)?;
+                freed += c.size_bytes;
+                removed.push(c.key.clone());
+            }
+            let in_use: Vec<_> = selection
+                .in_use
+                .iter()
+                .map(|c| serde_json::json!({ "key": c.cached.key, "sandboxes": c.sandboxes }))
+                .collect();
+            for archive in stray_archives(all)? {
+                freed += std::fs::metadata(&archive).map(|m| m.len()).unwrap_or(0);
+                std::fs::remove_file(&archive)
+                    .with_context(|| format!("Failed to remove {}", archive.display()))?;
+            }
+
+            if json {
+                output::emit_json(&serde_json::json!({
+                    "ok": true,
+                    "command": "code prune",
+                    "removed": removed,
+                    "in_use": in_use,
+                    "freed_bytes": freed,
+                }));
+            } else {
+                for key in &removed {
+                    println!("🗑️  Removed VS Code {}", key.bold());
+                }
+                println!("🧹 Freed {}", format_bytes(freed));
+            }
+        }
+        Commands::Remove { version } => {
+            let dir = resolve_cache_dir(&version)?;
+            let cached = list_cached()?;
+            let Some(c) = cached.iter().find(|c| std::path::Path::new(&c.path) == dir) else {
+                bail!("VS Code {} is not in the cache.", version);
+            };
+            remove_cached(c)?;
+            if json {
+                output::emit_action_result("code remove", true);
+            } else {
+                println!(
+                    "🗑️  Removed VS Code {} ({})",
+                    c.key.bold(),
+                    format_bytes(c.size_bytes)
+                );
+            }
+        }
+    }
+
+    Ok(exit::SUCCESS)
+}
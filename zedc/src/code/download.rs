@@ -0,0 +1,390 @@
+//! Resumable, checksum-verified downloads for VS Code archives.
+
+use indicatif::ProgressBar;
+use reqwest::{header, Client, StatusCode};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::io::AsyncWriteExt;
+
+/// Errors that can occur while downloading an archive.
+#[derive(Debug)]
+pub enum DownloadError {
+    /// The server responded with a status code that cannot be recovered from by retrying.
+    Status { url: String, status: StatusCode },
+    /// The request failed, or the connection dropped while streaming the response body.
+    Network { url: String, source: reqwest::Error },
+    /// Reading or writing the downloaded file failed.
+    Io(std::io::Error),
+    /// The downloaded file does not match the SHA-256 hash published for it.
+    ChecksumMismatch { expected: String, actual: String },
+    /// Every attempt failed; contains the error from the last attempt.
+    RetriesExhausted {
+        attempts: u32,
+        last: Box<DownloadError>,
+    },
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { url, status } => write!(f, "{} returned HTTP {}", url, status),
+            Self::Network { url, source } => write!(f, "request to {} failed: {}", url, source),
+            Self::Io(e) => write!(f, "failed to write download: {}", e),
+            Self::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch: expected SHA-256 {}, got {}",
+                expected, actual
+            ),
+            Self::RetriesExhausted { attempts, last } => {
+                write!(f, "download failed after {} attempts: {}", attempts, last)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DownloadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Network { source, .. } => Some(source),
+            Self::Io(e) => Some(e),
+            Self::RetriesExhausted { last, .. } => Some(last.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for DownloadError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl DownloadError {
+    /// Whether the failed attempt is worth retrying (dropped connections and server-side errors).
+    fn is_retryable(&self) -> bool {
+        match self {
+            Self::Network { .. } => true,
+            Self::Status { status, .. } => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Downloads files into a `.part` file, resuming with HTTP `Range` requests and retrying with
+/// exponential backoff when the connection drops.
+pub struct Downloader {
+    client: Client,
+    max_attempts: u32,
+    initial_backoff: Duration,
+}
+
+impl Default for Downloader {
+    fn default() -> Self {
+        Self::new(4, Duration::from_secs(1))
+    }
+}
+
+impl Downloader {
+    /// # Arguments
+    /// * `max_attempts` - The number of attempts before giving up
+    /// * `initial_backoff` - The delay before the first retry; doubled for every further retry
+    pub fn new(max_attempts: u32, initial_backoff: Duration) -> Self {
+        Self {
+            client: Client::new(),
+            max_attempts: max_attempts.max(1),
+            initial_backoff,
+        }
+    }
+
+    /// Downloads `url` to `dest`, verifying the result against `sha256` when provided.
+    ///
+    /// Data is written to `<dest>.part` first. If that file already exists (e.g. from a previous
+    /// run that was interrupted), the download resumes from its current length. The `.part` file
+    /// is only renamed to `dest` once the checksum has been verified.
+    pub async fn fetch(
+        &self,
+        url: &str,
+        dest: &Path,
+        sha256: Option<&str>,
+        progress: &ProgressBar,
+    ) -> Result<(), DownloadError> {
+        let part = part_path(dest);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match self.fetch_attempt(url, &part, progress).await {
+                Ok(()) => break,
+                Err(e) if e.is_retryable() && attempt < self.max_attempts => {
+                    let backoff = self.initial_backoff * 2u32.pow(attempt - 1);
+                    progress.println(format!(
+                        "  ⚠️  {} - retrying in {:.1}s",
+                        e,
+                        backoff.as_secs_f32()
+                    ));
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(e) if e.is_retryable() => {
+                    return Err(DownloadError::RetriesExhausted {
+                        attempts: attempt,
+                        last: Box::new(e),
+                    })
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if let Some(expected) = sha256 {
+            let actual = sha256_file(&part)?;
+            if !actual.eq_ignore_ascii_case(expected) {
+                // Start from scratch next time rather than resuming a corrupt file.
+                let _ = std::fs::remove_file(&part);
+                return Err(DownloadError::ChecksumMismatch {
+                    expected: expected.to_owned(),
+                    actual,
+                });
+            }
+        }
+
+        std::fs::rename(&part, dest)?;
+        Ok(())
+    }
+
+    /// Performs a single (possibly resumed) request, appending the response body to `part`.
+    async fn fetch_attempt(
+        &self,
+        url: &str,
+        part: &Path,
+        progress: &ProgressBar,
+    ) -> Result<(), DownloadError> {
+        let offset = std::fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+        let network_err = |source| DownloadError::Network {
+            url: url.to_owned(),
+            source,
+        };
+
+        let mut request = self.client.get(url);
+        if offset > 0 {
+            request = request.header(header::RANGE, format!("bytes={}-", offset));
+        }
+        let mut resp = request.send().await.map_err(network_err)?;
+
+        let append = match resp.status() {
+            StatusCode::PARTIAL_CONTENT => true,
+            StatusCode::OK => false,
+            // The partial file already holds the whole body.
+            StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => return Ok(()),
+            status => {
+                return Err(DownloadError::Status {
+                    url: url.to_owned(),
+                    status,
+                })
+            }
+        };
+
+        let start = if append { offset } else { 0 };
+        if let Some(len) = resp.content_length() {
+            progress.set_length(start + len);
+        }
+        progress.set_position(start);
+
+        let mut outfile = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(part)
+            .await?;
+        while let Some(chunk) = resp.chunk().await.map_err(network_err)? {
+            outfile.write_all(&chunk).await?;
+            progress.inc(chunk.len() as u64);
+        }
+        outfile.flush().await?;
+        Ok(())
+    }
+}
+
+/// Returns the path of the temporary file used while downloading to `dest`.
+fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+/// Computes the hex-encoded SHA-256 hash of a file.
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    const BODY: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    /// How the stand-in server answers each incoming connection.
+    #[derive(Clone, Copy)]
+    enum Reply {
+        /// Serve the body, honoring any `Range` header.
+        Full,
+        /// Announce the full body but close the connection halfway through.
+        Drop,
+        NotFound,
+    }
+
+    /// Starts a local HTTP server that answers one connection per entry in `replies`.
+    /// The returned handle yields the `Range` header (if any) of each request.
+    fn serve(replies: Vec<Reply>) -> (String, std::thread::JoinHandle<Vec<Option<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/archive.tar.gz", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut ranges = Vec::new();
+            for reply in replies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut range = None;
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("range: bytes=") {
+                        range = Some(value.trim().to_owned());
+                    }
+                    line.clear();
+                }
+                let start = range
+                    .as_deref()
+                    .and_then(|r| r.trim_end_matches('-').parse::<usize>().ok())
+                    .unwrap_or(0);
+                ranges.push(range);
+
+                let response = match reply {
+                    Reply::NotFound => {
+                        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_owned()
+                    }
+                    Reply::Full | Reply::Drop if start > 0 => format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\n\r\n",
+                        BODY.len() - start,
+                        start,
+                        BODY.len() - 1,
+                        BODY.len()
+                    ),
+                    Reply::Full | Reply::Drop => {
+                        format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", BODY.len())
+                    }
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+                match reply {
+                    Reply::Full => stream.write_all(&BODY[start..]).unwrap(),
+                    Reply::Drop => stream.write_all(&BODY[start..BODY.len() / 2]).unwrap(),
+                    Reply::NotFound => {}
+                }
+                stream.flush().unwrap();
+            }
+            ranges
+        });
+        (url, handle)
+    }
+
+    fn body_sha256() -> String {
+        format!("{:x}", Sha256::digest(BODY))
+    }
+
+    fn downloader() -> Downloader {
+        Downloader::new(3, Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn downloads_and_verifies_checksum() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("archive.tar.gz");
+        let (url, server) = serve(vec![Reply::Full]);
+
+        downloader()
+            .fetch(&url, &dest, Some(&body_sha256()), &ProgressBar::hidden())
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&dest).unwrap(), BODY);
+        assert!(!part_path(&dest).exists());
+        assert_eq!(server.join().unwrap(), vec![None]);
+    }
+
+    #[tokio::test]
+    async fn rejects_checksum_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("archive.tar.gz");
+        let (url, _server) = serve(vec![Reply::Full]);
+
+        let err = downloader()
+            .fetch(&url, &dest, Some("deadbeef"), &ProgressBar::hidden())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, DownloadError::ChecksumMismatch { .. }));
+        assert!(!dest.exists());
+        assert!(!part_path(&dest).exists());
+    }
+
+    #[tokio::test]
+    async fn resumes_partial_download_with_range_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("archive.tar.gz");
+        std::fs::write(part_path(&dest), &BODY[..10]).unwrap();
+        let (url, server) = serve(vec![Reply::Full]);
+
+        downloader()
+            .fetch(&url, &dest, Some(&body_sha256()), &ProgressBar::hidden())
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&dest).unwrap(), BODY);
+        assert_eq!(server.join().unwrap(), vec![Some("10-".to_owned())]);
+    }
+
+    #[tokio::test]
+    async fn retries_and_resumes_after_dropped_connection() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("archive.tar.gz");
+        let (url, server) = serve(vec![Reply::Drop, Reply::Full]);
+
+        downloader()
+            .fetch(&url, &dest, Some(&body_sha256()), &ProgressBar::hidden())
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&dest).unwrap(), BODY);
+        let ranges = server.join().unwrap();
+        assert_eq!(ranges[0], None);
+        assert_eq!(ranges[1], Some(format!("{}-", BODY.len() / 2)));
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_client_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("archive.tar.gz");
+        let (url, server) = serve(vec![Reply::NotFound]);
+
+        let err = downloader()
+            .fetch(&url, &dest, None, &ProgressBar::hidden())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            DownloadError::Status {
+                status: StatusCode::NOT_FOUND,
+                ..
+            }
+        ));
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+}
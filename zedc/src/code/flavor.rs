@@ -0,0 +1,233 @@
+//! Release channels and distributions of VS Code, and the archive layout of each.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::bail;
+use serde::Serialize;
+
+/// The release channel (update service "quality") of a VS Code build.
+#[derive(clap::ValueEnum, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    #[default]
+    Stable,
+    Insider,
+}
+
+/// The vendor of a VS Code build.
+#[derive(clap::ValueEnum, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Distribution {
+    /// Microsoft builds from the VS Code update service
+    #[default]
+    Vscode,
+    /// Open-source builds published on the VSCodium GitHub releases
+    Vscodium,
+}
+
+/// A combination of distribution and channel, which determines where builds are downloaded from,
+/// how their archives are laid out and where they are cached.
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Flavor {
+    pub distribution: Distribution,
+    pub channel: Channel,
+}
+
+/// All flavors, ordered so that longer cache prefixes are matched first.
+const ALL: [Flavor; 4] = [
+    Flavor::new(Distribution::Vscodium, Channel::Insider),
+    Flavor::new(Distribution::Vscodium, Channel::Stable),
+    Flavor::new(Distribution::Vscode, Channel::Insider),
+    Flavor::new(Distribution::Vscode, Channel::Stable),
+];
+
+impl Flavor {
+    pub const fn new(distribution: Distribution, channel: Channel) -> Self {
+        Self {
+            distribution,
+            channel,
+        }
+    }
+
+    /// Returns all distribution and channel combinations.
+    pub fn all() -> impl Iterator<Item = Flavor> {
+        ALL.into_iter()
+    }
+
+    /// Returns the product name shown in command output (e.g. `VS Code Insiders`).
+    pub fn display_name(&self) -> &'static str {
+        match (self.distribution, self.channel) {
+            (Distribution::Vscode, Channel::Stable) => "VS Code",
+            (Distribution::Vscode, Channel::Insider) => "VS Code Insiders",
+            (Distribution::Vscodium, Channel::Stable) => "VSCodium",
+            (Distribution::Vscodium, Channel::Insider) => "VSCodium Insiders",
+        }
+    }
+
+    /// Returns the update service quality for this flavor (`stable` or `insider`).
+    pub fn quality(&self) -> &'static str {
+        match self.channel {
+            Channel::Stable => "stable",
+            Channel::Insider => "insider",
+        }
+    }
+
+    /// Returns the name used in cache folders and command output (e.g. `vscode-insider`).
+    pub fn name(&self) -> &'static str {
+        match (self.distribution, self.channel) {
+            (Distribution::Vscode, Channel::Stable) => "vscode",
+            (Distribution::Vscode, Channel::Insider) => "vscode-insider",
+            (Distribution::Vscodium, Channel::Stable) => "vscodium",
+            (Distribution::Vscodium, Channel::Insider) => "vscodium-insider",
+        }
+    }
+
+    /// Returns the name of the cache folder for the given version (e.g. `vscode-insider-1.102.0`).
+    pub fn cache_key(&self, version: &str) -> String {
+        format!("{}-{}", self.name(), version)
+    }
+
+    /// Splits a cache folder name into its flavor and version. Folders named `<flavor>-latest`,
+    /// which older versions of zedc created before `latest` was resolved, are recognized as well.
+    pub fn parse_cache_key(key: &str) -> Option<(Flavor, &str)> {
+        ALL.iter().find_map(|flavor| {
+            key.strip_prefix(flavor.name())
+                .and_then(|rest| rest.strip_prefix('-'))
+                .filter(|version| {
+                    *version == "latest" || version.starts_with(|c: char| c.is_ascii_digit())
+                })
+                .map(|version| (*flavor, version))
+        })
+    }
+
+    /// Returns the name of the Code CLI script and Linux executable (e.g. `code-insiders`).
+    pub fn application_name(&self) -> &'static str {
+        match (self.distribution, self.channel) {
+            (Distribution::Vscode, Channel::Stable) => "code",
+            (Distribution::Vscode, Channel::Insider) => "code-insiders",
+            (Distribution::Vscodium, Channel::Stable) => "codium",
+            (Distribution::Vscodium, Channel::Insider) => "codium-insiders",
+        }
+    }
+
+    /// Returns the flavor with the given application name, as found in the Code CLI file name.
+    pub fn from_application_name(name: &str) -> Option<Flavor> {
+        ALL.into_iter().find(|f| f.application_name() == name)
+    }
+
+    /// Returns the file name of the application bundle (macOS) or executable (Windows, Linux).
+    pub fn app_name(&self) -> &'static str {
+        match std::env::consts::OS {
+            "macos" => match (self.distribution, self.channel) {
+                (Distribution::Vscode, Channel::Stable) => "Visual Studio Code.app",
+                (Distribution::Vscode, Channel::Insider) => "Visual Studio Code - Insiders.app",
+                (Distribution::Vscodium, Channel::Stable) => "VSCodium.app",
+                (Distribution::Vscodium, Channel::Insider) => "VSCodium - Insiders.app",
+            },
+            "windows" => match (self.distribution, self.channel) {
+                (Distribution::Vscode, Channel::Stable) => "Code.exe",
+                (Distribution::Vscode, Channel::Insider) => "Code - Insiders.exe",
+                (Distribution::Vscodium, Channel::Stable) => "VSCodium.exe",
+                (Distribution::Vscodium, Channel::Insider) => "VSCodium - Insiders.exe",
+            },
+            _ => self.application_name(),
+        }
+    }
+
+    /// Returns the platform identifier used in download URLs and archive names for this flavor.
+    pub fn platform(&self) -> anyhow::Result<&'static str> {
+        let arm = std::env::consts::ARCH == "aarch64";
+        Ok(match (std::env::consts::OS, self.distribution) {
+            ("linux", _) if arm => "linux-arm64",
+            ("linux", _) => "linux-x64",
+            ("macos", Distribution::Vscode) => "darwin-universal",
+            ("macos", Distribution::Vscodium) if arm => "darwin-arm64",
+            ("macos", Distribution::Vscodium) => "darwin-x64",
+            ("windows", Distribution::Vscode) if arm => "win32-arm64-archive",
+            ("windows", Distribution::Vscode) => "win32-x64-archive",
+            ("windows", Distribution::Vscodium) if arm => "win32-arm64",
+            ("windows", Distribution::Vscodium) => "win32-x64",
+            _ => bail!("OS is not supported."),
+        })
+    }
+
+    /// Returns the file extension of the archive for the current operating system.
+    pub fn archive_extension(&self) -> &'static str {
+        match std::env::consts::OS {
+            "linux" => "tar.gz",
+            _ => "zip",
+        }
+    }
+
+    /// Returns the path to the Code CLI binary inside the directory an archive was extracted into.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory path for the extracted copy of VS Code
+    pub fn cli_binary(&self, dir: &Path) -> PathBuf {
+        let app = self.application_name();
+        match std::env::consts::OS {
+            "windows" => dir.join("bin").join(format!("{}.cmd", app)),
+            "macos" => dir
+                .join(self.app_name())
+                .join("Contents/Resources/app/bin")
+                .join(app),
+            // Microsoft archives contain a `VSCode-linux-<arch>` folder, VSCodium archives are flat.
+            _ => match self.distribution {
+                Distribution::Vscode => dir
+                    .join(format!(
+                        "VSCode-linux-{}",
+                        if std::env::consts::ARCH == "aarch64" {
+                            "arm64"
+                        } else {
+                            "x64"
+                        }
+                    ))
+                    .join("bin")
+                    .join(app),
+                Distribution::Vscodium => dir.join("bin").join(app),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cache_keys() {
+        assert_eq!(
+            Flavor::parse_cache_key("vscode-1.90.0"),
+            Some((Flavor::default(), "1.90.0"))
+        );
+        assert_eq!(
+            Flavor::parse_cache_key("vscode-insider-1.102.0-abc1234"),
+            Some((
+                Flavor::new(Distribution::Vscode, Channel::Insider),
+                "1.102.0-abc1234"
+            ))
+        );
+        assert_eq!(
+            Flavor::parse_cache_key("vscodium-1.101.24242"),
+            Some((
+                Flavor::new(Distribution::Vscodium, Channel::Stable),
+                "1.101.24242"
+            ))
+        );
+        assert_eq!(Flavor::parse_cache_key("vscode-linux-x64.tar.gz"), None);
+    }
+
+    #[test]
+    fn cache_keys_round_trip() {
+        for flavor in ALL {
+            let key = flavor.cache_key("1.2.3");
+            assert_eq!(Flavor::parse_cache_key(&key), Some((flavor, "1.2.3")));
+            let legacy = flavor.cache_key("latest");
+            assert_eq!(Flavor::parse_cache_key(&legacy), Some((flavor, "latest")));
+            assert_eq!(
+                Flavor::from_application_name(flavor.application_name()),
+                Some(flavor)
+            );
+        }
+    }
+}
@@ -1,14 +1,58 @@
 //! "Root" module for VS Code-related functions.
 
+use std::path::{Path, PathBuf};
+
+mod cache;
+mod cmd;
+mod download;
+mod flavor;
 mod prepare;
+mod server;
+mod vscodium;
+pub use cache::mark_used;
+pub use cmd::{handle_cmd, Commands};
+pub use flavor::{Channel, Distribution, Flavor};
 pub use prepare::*;
+pub use server::is_server_binary;
 
-/// Returns an OS-specific filename that corresponds to the VS Code binary.
-pub fn code_binary() -> String {
-    match std::env::consts::OS {
-        "macos" => "Visual Studio Code.app",
-        "windows" => "Code.exe",
-        _ => "code",
+/// Returns the VS Code application bundle (macOS) or executable that belongs to a Code CLI binary.
+///
+/// # Arguments
+/// * `vsc_bin` - A path to the Code CLI binary (e.g. `bin/code` or `bin/codium.cmd`)
+pub fn code_binary(vsc_bin: &Path) -> Option<PathBuf> {
+    if std::env::consts::OS == "macos" {
+        // <app>/Contents/Resources/app/bin/code
+        return vsc_bin.ancestors().nth(5).map(Path::to_path_buf);
     }
-    .to_owned()
+    let flavor = Flavor::from_application_name(vsc_bin.file_stem()?.to_str()?)?;
+    Some(vsc_bin.parent()?.parent()?.join(flavor.app_name()))
+}
+
+/// Reads the product version of VS Code from the `package.json` bundled next to its Code CLI binary.
+///
+/// # Arguments
+/// * `vsc_bin` - A path to the Code CLI binary (`bin/code`)
+pub fn cli_product_version(vsc_bin: &Path) -> Option<String> {
+    read_app_json(vsc_bin, "package.json", "version")
+}
+
+/// Reads the commit that VS Code was built from out of the `product.json` next to its Code CLI binary.
+///
+/// # Arguments
+/// * `vsc_bin` - A path to the Code CLI binary (`bin/code`)
+pub fn cli_product_commit(vsc_bin: &Path) -> Option<String> {
+    read_app_json(vsc_bin, "product.json", "commit")
+}
+
+/// Reads a string field from a JSON file in the `resources/app` folder of a VS Code copy.
+fn read_app_json(vsc_bin: &Path, file: &str, field: &str) -> Option<String> {
+    let app_dir = match std::env::consts::OS {
+        // <app>/Contents/Resources/app/bin/code
+        "macos" => vsc_bin.parent()?.parent()?.to_path_buf(),
+        // <root>/bin/code -> <root>/resources/app
+        _ => vsc_bin.parent()?.parent()?.join("resources").join("app"),
+    };
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(app_dir.join(file)).ok()?).ok()?;
+    json.get(field)?.as_str().map(str::to_owned)
 }
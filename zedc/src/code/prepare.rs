@@ -6,7 +6,8 @@ use std::{
     process::{Command, Stdio},
 };
 
-use anyhow::bail;
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
 cfg_if::cfg_if! {
     if #[cfg(not(windows))] {
         use flate2::read::GzDecoder;
@@ -16,57 +17,236 @@ cfg_if::cfg_if! {
 
 use indicatif::{ProgressBar, ProgressStyle};
 use owo_colors::OwoColorize;
-use reqwest::{header, Client};
-use tokio::io::AsyncWriteExt;
+use reqwest::Client;
 
-/// Returns a URL for the VS Code release for the current operating system with the given version.
-///
-/// # Arguments
-/// * `version` - The version of VS Code to download
+use super::download::Downloader;
+use super::flavor::{Channel, Distribution, Flavor};
+use crate::cmd::VsCodeConfig;
+
+/// The public VS Code update service, used to resolve versions and download archives.
+pub const UPDATE_SERVICE_URL: &str = "https://update.code.visualstudio.com";
+
+/// Build metadata published by the VS Code update service (or VSCodium on GitHub) for a release.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ReleaseInfo {
+    /// The product version of the build (e.g. `1.101.2`).
+    #[serde(rename = "productVersion")]
+    pub version: String,
+    /// The commit the build was produced from (empty for VSCodium releases).
+    #[serde(rename = "version")]
+    pub commit: String,
+    /// Download URL for the archive.
+    pub url: String,
+    /// The SHA-256 hash of the archive.
+    #[serde(rename = "sha256hash", default)]
+    pub sha256: Option<String>,
+}
+
+/// Environment variable with the base URL of a mirror of the VS Code update service.
+pub const MIRROR_ENV: &str = "ZEDC_VSCODE_MIRROR";
+
+/// Environment variable with a directory of pre-downloaded VS Code archives.
+pub const ARCHIVE_DIR_ENV: &str = "ZEDC_VSCODE_ARCHIVE_DIR";
+
+/// Client for the VS Code update service, or the VSCodium GitHub releases.
 ///
-/// ### Note:  
-/// The returned URL is not validated and might not exist; any errors should be handled at the time
-/// of the request.
-fn build_url(version: &String) -> anyhow::Result<String> {
-    Ok(format!(
-        "https://update.code.visualstudio.com/{}/{}/stable",
-        version,
-        match std::env::consts::OS {
-            "linux" => match std::env::consts::ARCH {
-                "aarch64" => "linux-arm64",
-                _ => "linux-x64",
-            },
-            "macos" => "darwin-universal",
-            "windows" => match std::env::consts::ARCH {
-                "aarch64" => "win32-arm64-archive",
-                _ => "win32-x64-archive",
+/// The base URL is configurable so that tests can point it at a local stand-in server.
+pub struct UpdateService {
+    base_url: String,
+    client: Client,
+    flavor: Flavor,
+    platform: Option<&'static str>,
+}
+
+impl Default for UpdateService {
+    fn default() -> Self {
+        Self::new(UPDATE_SERVICE_URL)
+    }
+}
+
+impl UpdateService {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            client: Client::new(),
+            flavor: Flavor::default(),
+            platform: None,
+        }
+    }
+
+    /// Sets the distribution and channel to look up releases for.
+    pub fn with_flavor(mut self, flavor: Flavor) -> Self {
+        self.flavor = flavor;
+        self
+    }
+
+    /// Returns the release service for the given flavor. Microsoft builds are looked up through
+    /// `ZEDC_VSCODE_MIRROR` when it is set, or the public update service otherwise.
+    ///
+    /// A mirror must expose the same paths as the update service (`/api/...`,
+    /// `/<version>/<platform>/<quality>` and `/commit:<commit>/<platform>/<quality>`), as an HTTP
+    /// proxy or artifact repository would.
+    pub fn from_env(flavor: Flavor) -> Self {
+        let base_url = match flavor.distribution {
+            Distribution::Vscodium => super::vscodium::GITHUB_API_URL.to_owned(),
+            Distribution::Vscode => match std::env::var(MIRROR_ENV) {
+                Ok(url) if !url.trim().is_empty() => url.trim().to_owned(),
+                _ => UPDATE_SERVICE_URL.to_owned(),
             },
-            _ => bail!("OS is not supported."),
+        };
+        Self::new(&base_url).with_flavor(flavor)
+    }
+
+    /// Looks up builds for another platform identifier than the desktop archive of the flavor,
+    /// e.g. `server-linux-x64-web` for the VS Code server.
+    pub fn with_platform(mut self, platform: &'static str) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    /// The distribution and channel this client looks up releases for.
+    pub fn flavor(&self) -> Flavor {
+        self.flavor
+    }
+
+    /// The platform identifier used in update service URLs.
+    fn platform(&self) -> anyhow::Result<&'static str> {
+        match self.platform {
+            Some(platform) => Ok(platform),
+            None => self.flavor.platform(),
+        }
+    }
+
+    /// Whether this client talks to a mirror rather than the public update service.
+    pub(super) fn is_mirror(&self) -> bool {
+        self.flavor.distribution == Distribution::Vscode && self.base_url != UPDATE_SERVICE_URL
+    }
+
+    /// Returns a URL for the VS Code release for the current operating system with the given version.
+    ///
+    /// # Arguments
+    /// * `version` - The version of VS Code to download (a release tag for VSCodium)
+    ///
+    /// ### Note:
+    /// The returned URL is not validated and might not exist; any errors should be handled at the time
+    /// of the request.
+    pub fn download_url(&self, version: &str) -> anyhow::Result<String> {
+        match self.flavor.distribution {
+            Distribution::Vscode => Ok(format!(
+                "{}/{}/{}/{}",
+                self.base_url,
+                version,
+                self.platform()?,
+                self.flavor.quality()
+            )),
+            Distribution::Vscodium => super::vscodium::download_url(self.flavor, version),
+        }
+    }
+
+    /// Returns the URL to download a release from. Metadata served by a mirror still links to the
+    /// public CDN, so mirrored releases are downloaded through the mirror instead: Insiders builds by
+    /// commit, since they share a product version across daily builds, and others by version.
+    pub(super) fn release_url(
+        &self,
+        version: &str,
+        release: &ReleaseInfo,
+    ) -> anyhow::Result<String> {
+        if !self.is_mirror() {
+            return Ok(release.url.clone());
+        }
+        match self.flavor.channel {
+            Channel::Insider if !release.commit.is_empty() => {
+                self.download_url(&format!("commit:{}", release.commit))
+            }
+            Channel::Insider => self.download_url("latest"),
+            Channel::Stable => self.download_url(version),
+        }
+    }
+
+    /// Queries the release service for the latest release in this client's channel.
+    pub async fn latest(&self) -> anyhow::Result<ReleaseInfo> {
+        match self.flavor.distribution {
+            Distribution::Vscode => {
+                self.query(&format!(
+                    "{}/api/update/{}/{}/latest",
+                    self.base_url,
+                    self.platform()?,
+                    self.flavor.quality()
+                ))
+                .await
+            }
+            Distribution::Vscodium => {
+                super::vscodium::release(&self.client, &self.base_url, self.flavor, None).await
+            }
+        }
+    }
+
+    /// Queries the release service for the metadata of a specific release.
+    pub async fn release(&self, version: &str) -> anyhow::Result<ReleaseInfo> {
+        match self.flavor.distribution {
+            Distribution::Vscode => {
+                self.query(&format!(
+                    "{}/api/versions/{}/{}/{}",
+                    self.base_url,
+                    version,
+                    self.platform()?,
+                    self.flavor.quality()
+                ))
+                .await
+            }
+            Distribution::Vscodium => {
+                super::vscodium::release(&self.client, &self.base_url, self.flavor, Some(version))
+                    .await
+            }
+        }
+    }
+
+    /// Fetches and parses release metadata from the given update service API URL.
+    async fn query(&self, url: &str) -> anyhow::Result<ReleaseInfo> {
+        let url = url.to_owned();
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to query {}", url))?;
+        if !resp.status().is_success() {
+            bail!("{} returned HTTP {}", url, resp.status());
         }
-    ))
+        let body = resp.text().await?;
+        serde_json::from_str::<ReleaseInfo>(&body)
+            .with_context(|| format!("Invalid release metadata from {}", url))
+    }
 }
 
-/// Returns a path to the Code CLI binary, relative to the directory where the desired VS Code version was extracted.
-///
-/// # Arguments
-/// * `dir` - The directory path for the extracted copy of VS Code
-fn code_cli_binary(dir: &Path) -> PathBuf {
-    match std::env::consts::OS {
-        "windows" => dir.join("bin").join("code.cmd"),
-        "macos" => dir
-            .join("Visual Studio Code.app")
-            .join("Contents/Resources/app/bin/code"),
-        _ => dir
-            .join(format!(
-                "VSCode-linux-{}",
-                if std::env::consts::ARCH == "x86_64" {
-                    "x64"
-                } else {
-                    "aarch64"
-                }
-            ))
-            .join("bin")
-            .join("code"),
+/// A copy of VS Code that is ready to use, as returned by [`download_vscode`].
+#[derive(Serialize, Clone, Debug)]
+pub struct VsCodeInstall {
+    /// The concrete VS Code version (never `latest`).
+    pub version: String,
+    /// The commit of the build, when known.
+    pub commit: Option<String>,
+    /// The distribution and channel of the build.
+    #[serde(flatten)]
+    pub flavor: Flavor,
+    /// Absolute path to the Code CLI binary, or the `code-server` binary of a server build.
+    pub bin: String,
+    /// Whether this is a VS Code server build for VS Code for the Web (`--web`).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub web: bool,
+}
+
+impl VsCodeInstall {
+    /// Describes the copy of VS Code extracted into the given cache directory.
+    fn from_cache(vsc_path: &Path, version: &str, flavor: Flavor) -> Self {
+        let bin = flavor.cli_binary(vsc_path);
+        Self {
+            flavor,
+            version: super::cli_product_version(&bin).unwrap_or_else(|| version.to_owned()),
+            commit: super::cli_product_commit(&bin),
+            bin: bin.to_string_lossy().into_owned(),
+            web: false,
+        }
     }
 }
 
@@ -97,25 +277,23 @@ async fn extract_code_zip(
             }
             tokio::fs::create_dir(vsc_path.join("code-portable-data")).await?;
         } else {
-            let mut archive = zip::ZipArchive::new(file).unwrap();
+            let mut archive = zip::ZipArchive::new(file)?;
 
             for i in 0..archive.len() {
-                let mut entry = archive.by_index(i).unwrap();
+                let mut entry = archive.by_index(i)?;
                 let out_path = match entry.enclosed_name() {
                     Some(p) => p.to_owned(),
                     None => continue,
                 };
 
                 if entry.is_dir() {
-                    std::fs::create_dir_all(vsc_path.join(&out_path)).unwrap();
+                    std::fs::create_dir_all(vsc_path.join(&out_path))?;
                 } else {
                     if let Some(p) = out_path.parent() {
-                        if !p.exists() {
-                            std::fs::create_dir_all(p).unwrap();
-                        }
+                        std::fs::create_dir_all(vsc_path.join(p))?;
                     }
-                    let mut outfile = std::fs::File::create(vsc_path.join(&out_path)).unwrap();
-                    std::io::copy(&mut entry, &mut outfile).unwrap();
+                    let mut outfile = std::fs::File::create(vsc_path.join(&out_path))?;
+                    std::io::copy(&mut entry, &mut outfile)?;
                 }
 
                 // Apply permissions to file for UNIX-based systems
@@ -124,7 +302,7 @@ async fn extract_code_zip(
                 {
                     use std::os::unix::fs::PermissionsExt;
                     if let Some(mode) = entry.unix_mode() {
-                        std::fs::set_permissions(vsc_path.join(&out_path), std::fs::Permissions::from_mode(mode)).unwrap();
+                        std::fs::set_permissions(vsc_path.join(&out_path), std::fs::Permissions::from_mode(mode))?;
                     }
                 }
             }
@@ -136,82 +314,177 @@ async fn extract_code_zip(
     Ok(())
 }
 
-/// Downloads a portable copy of VS Code with the given version, if provided (default: `latest`).  
-/// Returns an absolute path to the Code CLI binary.
+/// Prepares a portable copy of VS Code as selected by the given options (default: `latest`).
+/// Returns the resolved version and an absolute path to the Code CLI binary.
 ///
-/// # Arguments  
-/// * `version`: (optional) The version of VS Code to download
+/// # Arguments
+/// * `config`: The VS Code version or archive to use
 ///
 /// # Summary
 /// This function performs the following operations:
 /// * Creates a data directory for `zedc` to manage VS Code versions (`zedc_data`)
-/// * Builds a URL and performs a `GET` request to fetch the VS Code archive
-/// * Extracts the VS Code archive into its corresponding directory in into `zedc_data`
-pub async fn download_vscode(version: Option<String>) -> anyhow::Result<String> {
+/// * Picks the archive source: `--vscode-archive`, the `ZEDC_VSCODE_ARCHIVE_DIR` directory,
+///   or the release service for the `--distribution` and `--channel` (`ZEDC_VSCODE_MIRROR` when set)
+/// * Resolves `latest` to a concrete version and fetches the archive, unless that version is cached
+/// * Extracts the VS Code archive into its corresponding directory in `zedc_data`
+///
+/// With `--web`, the VS Code server build is prepared instead (see [`super::server`]).
+pub async fn download_vscode(config: &VsCodeConfig) -> anyhow::Result<VsCodeInstall> {
+    let flavor = Flavor::new(config.distribution, config.channel);
+    if config.web {
+        return super::server::download_server(config).await;
+    }
+    if let Some(archive) = &config.vscode_archive {
+        return install_archive(archive, None, flavor).await;
+    }
+    if let Ok(dir) = std::env::var(ARCHIVE_DIR_ENV) {
+        if !dir.trim().is_empty() {
+            return install_from_archive_dir(
+                Path::new(dir.trim()),
+                config.vsc_version.as_deref(),
+                flavor,
+            )
+            .await;
+        }
+    }
+    download_vscode_from(&UpdateService::from_env(flavor), config.vsc_version.clone()).await
+}
+
+/// Downloads VS Code using the given update service. See [`download_vscode`].
+pub async fn download_vscode_from(
+    service: &UpdateService,
+    version: Option<String>,
+) -> anyhow::Result<VsCodeInstall> {
     let json = crate::output::json_enabled();
+    let flavor = service.flavor();
     if !json {
-        println!("💿 Downloading VS Code...");
+        println!("💿 Downloading {}...", flavor.display_name());
     }
-    let ver = match version {
-        Some(v) => v,
-        None => "latest".to_owned(),
-    };
+    let zedc_path = crate::util::data_dir()?;
 
-    let zedc_path = Path::new(&std::env::current_exe()?)
-        .parent()
-        .unwrap()
-        .join("zedc_data");
-
-    let vsc_path = zedc_path.join(format!("vscode-{}", ver));
-    if let Ok(vsc_exists) = tokio::fs::try_exists(&vsc_path).await {
-        if vsc_exists {
-            match ver.as_str() {
-                "latest" => {
-                    // redownload latest to ensure newest version
-                    tokio::fs::remove_dir_all(&vsc_path).await?;
+    let (ver, release) = match version.as_deref() {
+        None | Some("latest") => match service.latest().await {
+            Ok(release) => {
+                if !json {
+                    let commit: String = release.commit.chars().take(7).collect();
+                    println!(
+                        "  🔎 Latest {} is {}{}",
+                        flavor.display_name(),
+                        release.version.bold(),
+                        if commit.is_empty() {
+                            String::new()
+                        } else {
+                            format!(" ({})", commit)
+                        }
+                    );
                 }
-                _ => {
-                    if !json {
-                        println!(
-                            "  ⏭️  {}",
-                            format!("Found VS Code {} in cache, skipping download...", ver)
+                (
+                    cache_version(flavor, &release.version, &release.commit),
+                    Some(release),
+                )
+            }
+            Err(e) => {
+                // Fall back to the newest cached copy so that `zedc test` still works offline.
+                let newest = newest_cached(flavor, None)?;
+                match newest {
+                    Some(vsc) => {
+                        if !json {
+                            println!(
+                                "  ⚠️  {}",
+                                format!(
+                                    "Could not resolve the latest {} version ({}), using cached {}",
+                                    flavor.display_name(),
+                                    e,
+                                    vsc.version
+                                )
                                 .italic()
-                        );
+                            );
+                        }
+                        return Ok(vsc);
+                    }
+                    None => {
+                        return Err(e.context(format!(
+                            "Failed to resolve the latest {} version",
+                            flavor.display_name()
+                        )))
                     }
-                    return Ok(code_cli_binary(&vsc_path).to_str().unwrap().to_owned());
                 }
             }
+        },
+        // Insiders builds are replaced daily, so only cached builds can be pinned.
+        Some(v) if flavor.channel == Channel::Insider => {
+            return newest_cached(flavor, Some(v))?.with_context(|| {
+                format!(
+                    "{} {} is not cached; Insiders builds can only be downloaded as `latest` or installed with --vscode-archive",
+                    flavor.display_name(),
+                    v
+                )
+            });
         }
-    }
+        Some(v) => (v.to_owned(), None),
+    };
 
-    let _ = tokio::fs::create_dir(&zedc_path).await;
-    let _ = tokio::fs::create_dir(&vsc_path).await;
-    if !zedc_path.exists() {
-        bail!("Failed to create the data dir for zedc.".red());
+    if let Some(cached) = find_cached(flavor, &ver)? {
+        return Ok(cached);
     }
 
-    let url = build_url(&ver)?;
-    let client = Client::new();
+    tokio::fs::create_dir_all(&zedc_path)
+        .await
+        .map_err(|_| anyhow::anyhow!("Failed to create the data dir for zedc.".red()))?;
 
-    let download_size = {
-        let resp = client.head(url.as_str()).send().await?;
-        if resp.status().is_success() {
-            resp.headers()
-                .get(header::CONTENT_LENGTH)
-                .and_then(|len| len.to_str().ok())
-                .and_then(|len| len.parse().ok())
-                .unwrap_or(0)
-        } else {
-            0
-        }
+    // Look up the download URL and published checksum for pinned versions.
+    let release = match release {
+        Some(r) => Some(r),
+        None => match service.release(&ver).await {
+            Ok(r) => Some(r),
+            Err(e) => {
+                if !json {
+                    println!(
+                        "  ⚠️  {}",
+                        format!(
+                            "Could not fetch release metadata ({}), the download will not be verified",
+                            e
+                        )
+                        .italic()
+                    );
+                }
+                None
+            }
+        },
+    };
+    let (url, sha256) = match release {
+        Some(r) => (service.release_url(&ver, &r)?, r.sha256),
+        None => (service.download_url(&ver)?, None),
     };
 
-    let mut resp = client.get(url).send().await.expect("request failed");
+    let path = zedc_path.join(format!(
+        "{}-{}.{}",
+        flavor.cache_key(&ver),
+        flavor.platform()?,
+        flavor.archive_extension()
+    ));
+    fetch_archive(&url, &path, sha256.as_deref())
+        .await
+        .with_context(|| format!("Failed to download {} {}", flavor.display_name(), ver))?;
 
-    let progress_bar = if json {
+    let vsc = install_archive(&path, Some(&ver), flavor).await?;
+
+    // The archive is no longer needed once it has been extracted.
+    let _ = tokio::fs::remove_file(&path).await;
+    Ok(vsc)
+}
+
+/// Downloads an archive to `path` with a progress bar (hidden in JSON mode), verifying it against
+/// the published SHA-256 hash when there is one.
+pub(super) async fn fetch_archive(
+    url: &str,
+    path: &Path,
+    sha256: Option<&str>,
+) -> anyhow::Result<()> {
+    let progress_bar = if crate::output::json_enabled() {
         ProgressBar::hidden()
     } else {
-        ProgressBar::new(download_size)
+        ProgressBar::new(0)
     };
     progress_bar.set_style(
         ProgressStyle::with_template(
@@ -220,55 +493,186 @@ pub async fn download_vscode(version: Option<String>) -> anyhow::Result<String>
         .unwrap()
         .progress_chars("#>-"),
     );
+    Downloader::default()
+        .fetch(url, path, sha256, &progress_bar)
+        .await?;
+    progress_bar.finish();
+    Ok(())
+}
 
-    let fname = resp
-        .url()
-        .path_segments()
-        .and_then(|segments| segments.last())
-        .and_then(|name| if name.is_empty() { None } else { Some(name) })
-        .unwrap_or("tmp-vscode.bin");
+/// Returns the version used in the cache key of a release.
+///
+/// Insiders builds share a product version (e.g. `1.102.0-insider`) across daily builds, so they
+/// are cached by version and short commit instead (e.g. `1.102.0-abc1234`).
+pub(super) fn cache_version(flavor: Flavor, version: &str, commit: &str) -> String {
+    match flavor.channel {
+        Channel::Insider if !commit.is_empty() => format!(
+            "{}-{}",
+            version.trim_end_matches("-insider"),
+            commit.chars().take(7).collect::<String>()
+        ),
+        _ => version.to_owned(),
+    }
+}
 
-    let path = zedc_path.join(fname);
-    {
-        let mut outfile = tokio::fs::File::create(&path).await?;
+/// Returns the cached copy of the given VS Code version, if it has already been extracted.
+fn find_cached(flavor: Flavor, version: &str) -> anyhow::Result<Option<VsCodeInstall>> {
+    let vsc_path = super::cache::cache_dir(flavor, version)?;
+    if !flavor.cli_binary(&vsc_path).exists() {
+        return Ok(None);
+    }
+    if crate::output::text_enabled() {
+        println!(
+            "  ⏭️  {}",
+            format!(
+                "Found {} {} in cache, skipping download...",
+                flavor.display_name(),
+                version
+            )
+            .italic()
+        );
+    }
+    super::mark_used(&vsc_path)?;
+    Ok(Some(VsCodeInstall::from_cache(&vsc_path, version, flavor)))
+}
 
-        while let Some(chunk) = resp.chunk().await? {
-            progress_bar.inc(chunk.len() as u64);
-            outfile.write_all(&chunk).await?;
+/// Returns the newest cached copy of a flavor, optionally limited to versions starting with `prefix`.
+fn newest_cached(flavor: Flavor, prefix: Option<&str>) -> anyhow::Result<Option<VsCodeInstall>> {
+    let newest = super::cache::list_cached()?
+        .into_iter()
+        .filter(|c| c.flavor == flavor)
+        .filter_map(|c| {
+            let (_, key) = Flavor::parse_cache_key(&c.key)?;
+            let key = key.to_owned();
+            Some((key, c.path))
+        })
+        .filter(|(key, _)| prefix.is_none_or(|p| key.starts_with(p)))
+        .max_by(|a, b| compare_versions(&a.0, &b.0));
+    let Some((key, path)) = newest else {
+        return Ok(None);
+    };
+    let vsc_path = PathBuf::from(path);
+    super::mark_used(&vsc_path)?;
+    Ok(Some(VsCodeInstall::from_cache(&vsc_path, &key, flavor)))
+}
+
+/// Installs VS Code from a directory of pre-downloaded archives (`ZEDC_VSCODE_ARCHIVE_DIR`).
+///
+/// Archive file names must contain the VS Code version (e.g. `VSCode-linux-x64-1.101.2.tar.gz`);
+/// `latest` picks the highest version found in the directory. VSCodium and Insiders archives are
+/// recognized by `vscodium` and `insider` in their file names.
+async fn install_from_archive_dir(
+    dir: &Path,
+    version: Option<&str>,
+    flavor: Flavor,
+) -> anyhow::Result<VsCodeInstall> {
+    if crate::output::text_enabled() {
+        println!("💿 Looking for VS Code archives in {}...", dir.display());
+    }
+    let mut archives = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read archive directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let lower = name.to_lowercase();
+        if name.ends_with(&format!(".{}", flavor.archive_extension()))
+            && lower.contains("vscodium") == (flavor.distribution == Distribution::Vscodium)
+            && lower.contains("insider") == (flavor.channel == Channel::Insider)
+        {
+            if let Some(v) = version_in_file_name(&name) {
+                archives.push((v, path));
+            }
         }
-        outfile.flush().await?;
     }
 
-    progress_bar.finish();
-    if !json {
-        println!("📤 Unpacking VS Code archive...");
+    let (ver, path) = match version {
+        None | Some("latest") => archives
+            .into_iter()
+            .max_by(|a, b| compare_versions(&a.0, &b.0)),
+        Some(v) => archives.into_iter().find(|(found, _)| found == v),
     }
+    .with_context(|| {
+        format!(
+            "No {} {} archive found in {}",
+            flavor.display_name(),
+            version.unwrap_or("latest"),
+            dir.display()
+        )
+    })?;
 
-    // If the VS Code folder exists for the latest version, remove its contents before downloading
-    if vsc_path.exists() {
-        tokio::fs::remove_dir_all(&vsc_path).await?;
-        tokio::fs::create_dir(&vsc_path).await?;
+    // Insiders archives are cached by commit, which is only known after extracting.
+    if flavor.channel == Channel::Insider {
+        return install_archive(&path, None, flavor).await;
+    }
+    if let Some(cached) = find_cached(flavor, &ver)? {
+        return Ok(cached);
     }
+    install_archive(&path, Some(&ver), flavor).await
+}
 
-    match path
-        .extension()
-        .unwrap_or_default()
-        .to_str()
-        .unwrap_or_default()
-    {
-        "zip" => {
-            let file = std::fs::File::open(&path)?;
-            extract_code_zip(&file, &path, &vsc_path).await?;
-        }
-        #[cfg(not(windows))]
-        "tgz" | "gz" => {
-            let tar_gz = std::fs::File::open(&path)?;
-            let tar = GzDecoder::new(tar_gz);
-            let mut archive = Archive::new(tar);
-            archive.unpack(&vsc_path)?;
+/// Returns the first dotted version (e.g. `1.101.2`) contained in an archive file name.
+fn version_in_file_name(name: &str) -> Option<String> {
+    name.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map(|token| token.trim_matches('.'))
+        .find(|token| token.split('.').count() >= 3 && token.split('.').all(|p| !p.is_empty()))
+        .map(str::to_owned)
+}
+
+/// Extracts a VS Code archive into the cache and returns the resulting install.
+///
+/// The archive is extracted into a temporary folder first. When `version` is `None` (e.g. for
+/// `--vscode-archive`), the version is read from the extracted copy. The flavor is detected from the
+/// archive layout, falling back to `flavor` when it matches. An existing cached copy of the same
+/// version is replaced. If a `<archive>.sha256` file exists next to the archive, the archive
+/// is verified against it before extracting.
+async fn install_archive(
+    archive: &Path,
+    version: Option<&str>,
+    flavor: Flavor,
+) -> anyhow::Result<VsCodeInstall> {
+    let text = crate::output::text_enabled();
+    if !archive.is_file() {
+        bail!("VS Code archive {} does not exist", archive.display());
+    }
+    verify_sidecar_checksum(archive)?;
+
+    if text {
+        println!("📤 Unpacking VS Code archive...");
+    }
+    let zedc_path = crate::util::data_dir()?;
+    let staging = zedc_path.join(".vscode-extract");
+    if staging.exists() {
+        tokio::fs::remove_dir_all(&staging).await?;
+    }
+    tokio::fs::create_dir_all(&staging).await?;
+    extract_archive(archive, &staging).await?;
+
+    let not_vscode = || {
+        format!(
+            "{} does not look like a VS Code archive for this platform",
+            archive.display()
+        )
+    };
+    let flavor = std::iter::once(flavor)
+        .chain(Flavor::all())
+        .find(|f| f.cli_binary(&staging).exists())
+        .with_context(not_vscode)?;
+    let ver = match version {
+        Some(v) => v.to_owned(),
+        None => {
+            let bin = flavor.cli_binary(&staging);
+            let product_version = super::cli_product_version(&bin).with_context(not_vscode)?;
+            let commit = super::cli_product_commit(&bin).unwrap_or_default();
+            cache_version(flavor, &product_version, &commit)
         }
-        _ => bail!("Unable to extract VS Code; invalid extension for archive.".red()),
+    };
+
+    let vsc_path = super::cache::cache_dir(flavor, &ver)?;
+    if vsc_path.exists() {
+        tokio::fs::remove_dir_all(&vsc_path).await?;
     }
+    tokio::fs::rename(&staging, &vsc_path).await?;
 
     if std::env::consts::OS == "macos" {
         // Remove "quarantined" attribute from downloaded VS Code binary on macOS
@@ -276,12 +680,255 @@ pub async fn download_vscode(version: Option<String>) -> anyhow::Result<String>
             .args([
                 "-dr",
                 "com.apple.quarantine",
-                vsc_path.join("Visual Studio Code.app").to_str().unwrap(),
+                vsc_path.join(flavor.app_name()).to_str().unwrap(),
             ])
             .stdout(Stdio::null())
             .stderr(Stdio::null())
             .status();
     }
 
-    Ok(code_cli_binary(&vsc_path).to_str().unwrap().to_owned())
+    super::mark_used(&vsc_path)?;
+    Ok(VsCodeInstall::from_cache(&vsc_path, &ver, flavor))
+}
+
+/// Verifies an archive against a `<archive>.sha256` file next to it, if one exists.
+fn verify_sidecar_checksum(archive: &Path) -> anyhow::Result<()> {
+    let mut sidecar = archive.as_os_str().to_os_string();
+    sidecar.push(".sha256");
+    let Ok(contents) = std::fs::read_to_string(&sidecar) else {
+        return Ok(());
+    };
+    // `sha256sum` output is "<hash>  <file name>"; a bare hash is accepted as well.
+    let expected = contents.split_whitespace().next().unwrap_or_default();
+    let actual = super::download::sha256_file(archive)?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        bail!(
+            "Checksum mismatch for {}: expected SHA-256 {}, got {}",
+            archive.display(),
+            expected,
+            actual
+        );
+    }
+    Ok(())
+}
+
+/// Extracts a `.zip` or `.tar.gz` VS Code archive into the given directory.
+pub(super) async fn extract_archive(path: &Path, vsc_path: &Path) -> anyhow::Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    if name.ends_with(".zip") {
+        let file = std::fs::File::open(path)?;
+        extract_code_zip(&file, path, vsc_path).await
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        cfg_if::cfg_if! {
+            if #[cfg(not(windows))] {
+                let tar_gz = std::fs::File::open(path)?;
+                let tar = GzDecoder::new(tar_gz);
+                let mut archive = Archive::new(tar);
+                archive.unpack(vsc_path)?;
+                Ok(())
+            } else {
+                bail!("Unable to extract VS Code; .tar.gz archives are not supported on Windows.".red())
+            }
+        }
+    } else {
+        bail!("Unable to extract VS Code; invalid extension for archive.".red())
+    }
+}
+
+/// Compares two dotted version strings numerically (e.g. `1.100.0` > `1.99.3`).
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parse = |v: &str| -> Vec<u64> {
+        v.split(['.', '-'])
+            .map_while(|seg| seg.parse::<u64>().ok())
+            .collect()
+    };
+    parse(a).cmp(&parse(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Serves a single canned JSON response on a local port, standing in for the update service.
+    /// Returns the base URL and a handle that yields the request line the server received.
+    fn serve_once(body: &'static str) -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            // Drain the remaining headers before responding.
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            request_line
+        });
+        (base_url, handle)
+    }
+
+    #[tokio::test]
+    async fn resolves_latest_release_from_update_service() {
+        let (base_url, server) = serve_once(
+            r#"{"url":"http://localhost/code-stable.tar.gz","name":"1.101.2","version":"2901c5ac6db8a986a5666c3af51ff804d05af0d4","productVersion":"1.101.2","sha256hash":"abc"}"#,
+        );
+        let release = UpdateService::new(&base_url).latest().await.unwrap();
+        assert_eq!(release.version, "1.101.2");
+        assert_eq!(release.commit, "2901c5ac6db8a986a5666c3af51ff804d05af0d4");
+        assert_eq!(release.url, "http://localhost/code-stable.tar.gz");
+        assert_eq!(release.sha256.as_deref(), Some("abc"));
+
+        let request_line = server.join().unwrap();
+        let expected_path = format!(
+            "/api/update/{}/stable/latest",
+            Flavor::default().platform().unwrap()
+        );
+        assert!(request_line.starts_with(&format!("GET {} ", expected_path)));
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_release_metadata() {
+        let (base_url, _server) = serve_once(r#"{"unexpected":true}"#);
+        assert!(UpdateService::new(&base_url).latest().await.is_err());
+    }
+
+    #[test]
+    fn builds_download_url_for_pinned_version() {
+        let url = UpdateService::new("http://mirror.local/")
+            .download_url("1.90.0")
+            .unwrap();
+        assert!(url.starts_with("http://mirror.local/1.90.0/"));
+        assert!(url.ends_with("/stable"));
+    }
+
+    #[tokio::test]
+    async fn queries_insider_channel() {
+        let (base_url, server) = serve_once(
+            r#"{"url":"http://localhost/code-insider.tar.gz","name":"1.102.0-insider","version":"0d0ab1b9fcd3e8d0ba8a4a2bf2b2d8b3f6a1c2d3","productVersion":"1.102.0-insider"}"#,
+        );
+        let flavor = Flavor::new(Distribution::Vscode, Channel::Insider);
+        let release = UpdateService::new(&base_url)
+            .with_flavor(flavor)
+            .latest()
+            .await
+            .unwrap();
+        assert_eq!(
+            cache_version(flavor, &release.version, &release.commit),
+            "1.102.0-0d0ab1b"
+        );
+        // A malformed commit from a mirror must not split a multi-byte character
+        assert_eq!(
+            cache_version(flavor, "1.102.0", "abcdeé12"),
+            "1.102.0-abcdeé1"
+        );
+        let request_line = server.join().unwrap();
+        assert!(request_line.contains("/insider/latest "));
+    }
+
+    #[tokio::test]
+    async fn downloads_insiders_through_mirror_by_commit() {
+        let (base_url, server) = serve_once(
+            r#"{"url":"https://az764295.vo.msecnd.net/insider/0d0ab1b9fcd3e8d0ba8a4a2bf2b2d8b3f6a1c2d3/code-insider.tar.gz","name":"1.102.0-insider","version":"0d0ab1b9fcd3e8d0ba8a4a2bf2b2d8b3f6a1c2d3","productVersion":"1.102.0-insider"}"#,
+        );
+        let flavor = Flavor::new(Distribution::Vscode, Channel::Insider);
+        let service = UpdateService::new(&base_url).with_flavor(flavor);
+        let release = service.latest().await.unwrap();
+        assert!(server.join().unwrap().contains("/insider/latest "));
+
+        // The cache key of the build is not a path the update service serves
+        let ver = cache_version(flavor, &release.version, &release.commit);
+        assert_eq!(
+            service.release_url(&ver, &release).unwrap(),
+            format!(
+                "{}/commit:0d0ab1b9fcd3e8d0ba8a4a2bf2b2d8b3f6a1c2d3/{}/insider",
+                base_url,
+                flavor.platform().unwrap()
+            )
+        );
+        let stable = UpdateService::new(&base_url);
+        assert_eq!(
+            stable.release_url("1.101.2", &release).unwrap(),
+            stable.download_url("1.101.2").unwrap()
+        );
+        assert_eq!(
+            UpdateService::default()
+                .with_flavor(flavor)
+                .release_url(&ver, &release)
+                .unwrap(),
+            release.url
+        );
+    }
+
+    #[tokio::test]
+    async fn resolves_latest_vscodium_release() {
+        let flavor = Flavor::new(Distribution::Vscodium, Channel::Stable);
+        let asset = super::super::vscodium::asset_name(flavor, "1.101.24242").unwrap();
+        let body = format!(
+            r#"{{"tag_name":"1.101.24242","assets":[{{"name":"{0}","browser_download_url":"https://github.com/dl/{0}"}}]}}"#,
+            asset
+        );
+        let (base_url, server) = serve_once(Box::leak(body.into_boxed_str()));
+        let release = UpdateService::new(&base_url)
+            .with_flavor(flavor)
+            .latest()
+            .await
+            .unwrap();
+        assert_eq!(release.version, "1.101.24242");
+        assert_eq!(release.url, format!("https://github.com/dl/{}", asset));
+        assert_eq!(release.sha256, None);
+        let request_line = server.join().unwrap();
+        assert!(request_line.starts_with("GET /repos/VSCodium/vscodium/releases/latest "));
+    }
+
+    #[test]
+    fn finds_version_in_archive_file_names() {
+        assert_eq!(
+            version_in_file_name("VSCode-linux-x64-1.101.2.tar.gz").as_deref(),
+            Some("1.101.2")
+        );
+        assert_eq!(
+            version_in_file_name("vscode-1.90.0-win32-x64-archive.zip").as_deref(),
+            Some("1.90.0")
+        );
+        assert_eq!(
+            version_in_file_name("code-stable-x64-1717531825.tar.gz"),
+            None
+        );
+    }
+
+    #[test]
+    fn verifies_sidecar_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("vscode-1.90.0.zip");
+        std::fs::write(&archive, b"archive").unwrap();
+        // No sidecar: nothing to verify.
+        verify_sidecar_checksum(&archive).unwrap();
+
+        let hash = super::super::download::sha256_file(&archive).unwrap();
+        let sidecar = dir.path().join("vscode-1.90.0.zip.sha256");
+        std::fs::write(&sidecar, format!("{}  vscode-1.90.0.zip\n", hash)).unwrap();
+        verify_sidecar_checksum(&archive).unwrap();
+
+        std::fs::write(&sidecar, "0".repeat(64)).unwrap();
+        assert!(verify_sidecar_checksum(&archive).is_err());
+    }
+
+    #[test]
+    fn compares_versions_numerically() {
+        use std::cmp::Ordering;
+        assert_eq!(compare_versions("1.100.0", "1.99.3"), Ordering::Greater);
+        assert_eq!(compare_versions("1.90.0", "1.90.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.9.1", "1.10.0"), Ordering::Less);
+    }
 }
@@ -0,0 +1,254 @@
+//! Downloading the VS Code server build used to serve a sandbox as VS Code for the Web (`--web`).
+//!
+//! Server builds are published by the update service next to the desktop archives (e.g.
+//! `server-linux-x64-web`) and cached in `zedc_data/<flavor>-server-<version>`. Their
+//! `bin/code-server` binary installs extensions like the Code CLI and serves the web client.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use owo_colors::OwoColorize;
+
+use super::flavor::{Channel, Distribution, Flavor};
+use super::prepare::{cache_version, compare_versions, extract_archive, fetch_archive};
+use super::{UpdateService, VsCodeInstall};
+use crate::cmd::VsCodeConfig;
+
+/// Returns the update service platform identifier of the server build for this machine.
+pub fn platform() -> anyhow::Result<&'static str> {
+    let arm = std::env::consts::ARCH == "aarch64";
+    Ok(match std::env::consts::OS {
+        "linux" if arm => "server-linux-arm64-web",
+        "linux" => "server-linux-x64-web",
+        "macos" if arm => "server-darwin-arm64-web",
+        "macos" => "server-darwin-web",
+        "windows" if arm => "server-win32-arm64-web",
+        "windows" => "server-win32-x64-web",
+        _ => bail!("OS is not supported."),
+    })
+}
+
+/// Returns whether a binary is the `code-server` binary of a server build rather than a Code CLI.
+pub fn is_server_binary(bin: &Path) -> bool {
+    bin.file_stem()
+        .is_some_and(|stem| stem.to_string_lossy().starts_with("code-server"))
+}
+
+/// Returns the cache directory of a server build.
+fn cache_dir(flavor: Flavor, version: &str) -> anyhow::Result<PathBuf> {
+    Ok(crate::util::data_dir()?.join(format!("{}-server-{}", flavor.name(), version)))
+}
+
+/// Returns the `code-server` binary inside the directory a server archive was extracted into.
+/// Archives contain a single `vscode-server-<platform>-web` folder.
+fn server_binary(dir: &Path, flavor: Flavor) -> Option<PathBuf> {
+    let name = match (flavor.channel, std::env::consts::OS) {
+        (Channel::Stable, "windows") => "code-server.cmd",
+        (Channel::Stable, _) => "code-server",
+        (Channel::Insider, "windows") => "code-server-insiders.cmd",
+        (Channel::Insider, _) => "code-server-insiders",
+    };
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path().join("bin").join(name))
+        .find(|bin| bin.is_file())
+}
+
+/// Reads a string field from the `package.json` or `product.json` at the root of a server build.
+fn read_server_json(bin: &Path, file: &str, field: &str) -> Option<String> {
+    let root = bin.parent()?.parent()?;
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(root.join(file)).ok()?).ok()?;
+    json.get(field)?.as_str().map(str::to_owned)
+}
+
+/// Describes the server build extracted into a cache directory, if there is one.
+fn from_cache(dir: &Path, version: &str, flavor: Flavor) -> Option<VsCodeInstall> {
+    let bin = server_binary(dir, flavor)?;
+    Some(VsCodeInstall {
+        version: read_server_json(&bin, "package.json", "version")
+            .unwrap_or_else(|| version.to_owned()),
+        commit: read_server_json(&bin, "product.json", "commit"),
+        flavor,
+        bin: bin.to_string_lossy().into_owned(),
+        web: true,
+    })
+}
+
+/// Returns the newest cached server build of a flavor.
+fn newest_cached(flavor: Flavor) -> anyhow::Result<Option<VsCodeInstall>> {
+    let data_dir = crate::util::data_dir()?;
+    let prefix = format!("{}-server-", flavor.name());
+    let Ok(entries) = std::fs::read_dir(&data_dir) else {
+        return Ok(None);
+    };
+    let newest = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            let version = name.strip_prefix(&prefix)?.to_owned();
+            Some((version, e.path()))
+        })
+        .filter_map(|(version, path)| from_cache(&path, &version, flavor))
+        .max_by(|a, b| compare_versions(&a.version, &b.version));
+    Ok(newest)
+}
+
+/// Prepares the VS Code server build for the version selected by the given options
+/// (default: `latest`), downloading it unless it is cached.
+///
+/// # Arguments
+/// * `config`: The VS Code version and channel to use
+pub async fn download_server(config: &VsCodeConfig) -> anyhow::Result<VsCodeInstall> {
+    let text = crate::output::text_enabled();
+    let flavor = Flavor::new(config.distribution, config.channel);
+    if flavor.distribution == Distribution::Vscodium {
+        bail!("--web is only supported for the VS Code distribution.");
+    }
+    if config.vscode_archive.is_some() {
+        bail!("--web cannot be used with --vscode-archive.");
+    }
+    let service = UpdateService::from_env(flavor).with_platform(platform()?);
+    if text {
+        println!("💿 Downloading {} Server...", flavor.display_name());
+    }
+
+    let (ver, release) = match config.vsc_version.as_deref() {
+        None | Some("latest") => match service.latest().await {
+            Ok(release) => (
+                cache_version(flavor, &release.version, &release.commit),
+                Some(release),
+            ),
+            Err(e) => match newest_cached(flavor)? {
+                Some(server) => {
+                    if text {
+                        println!(
+                            "  ⚠️  {}",
+                            format!(
+                                "Could not resolve the latest {} version ({}), using cached server {}",
+                                flavor.display_name(),
+                                e,
+                                server.version
+                            )
+                            .italic()
+                        );
+                    }
+                    return Ok(server);
+                }
+                None => return Err(e.context("Failed to resolve the latest VS Code version")),
+            },
+        },
+        Some(v) if flavor.channel == Channel::Insider => bail!(
+            "Insiders server builds can only be downloaded as `latest` (requested {})",
+            v
+        ),
+        Some(v) => (v.to_owned(), None),
+    };
+
+    let dir = cache_dir(flavor, &ver)?;
+    if let Some(cached) = from_cache(&dir, &ver, flavor) {
+        if text {
+            println!(
+                "  ⏭️  {}",
+                format!(
+                    "Found {} Server {} in cache, skipping download...",
+                    flavor.display_name(),
+                    ver
+                )
+                .italic()
+            );
+        }
+        super::mark_used(&dir)?;
+        return Ok(cached);
+    }
+
+    let release = match release {
+        Some(r) => Some(r),
+        None => service.release(&ver).await.ok(),
+    };
+    let (url, sha256) = match release {
+        Some(r) => (service.release_url(&ver, &r)?, r.sha256),
+        None => (service.download_url(&ver)?, None),
+    };
+    let zedc_path = crate::util::data_dir()?;
+    tokio::fs::create_dir_all(&zedc_path).await?;
+    let archive = zedc_path.join(format!(
+        "{}-server-{}-{}.{}",
+        flavor.name(),
+        ver,
+        platform()?,
+        flavor.archive_extension()
+    ));
+    fetch_archive(&url, &archive, sha256.as_deref())
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to download {} Server {}",
+                flavor.display_name(),
+                ver
+            )
+        })?;
+
+    if text {
+        println!("📤 Unpacking VS Code Server archive...");
+    }
+    let staging = zedc_path.join(".vscode-server-extract");
+    if staging.exists() {
+        tokio::fs::remove_dir_all(&staging).await?;
+    }
+    tokio::fs::create_dir_all(&staging).await?;
+    extract_archive(&archive, &staging).await?;
+    if server_binary(&staging, flavor).is_none() {
+        bail!(
+            "{} does not look like a VS Code server archive for this platform",
+            archive.display()
+        );
+    }
+    if dir.exists() {
+        tokio::fs::remove_dir_all(&dir).await?;
+    }
+    tokio::fs::rename(&staging, &dir).await?;
+    let _ = tokio::fs::remove_file(&archive).await;
+
+    super::mark_used(&dir)?;
+    from_cache(&dir, &ver, flavor).context("Failed to prepare the VS Code server")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_server_binary_in_archive_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join(format!("vscode-{}", platform().unwrap()));
+        let bin = if cfg!(windows) {
+            "code-server.cmd"
+        } else {
+            "code-server"
+        };
+        std::fs::create_dir_all(root.join("bin")).unwrap();
+        std::fs::write(root.join("bin").join(bin), "").unwrap();
+        std::fs::write(root.join("package.json"), r#"{ "version": "1.101.2" }"#).unwrap();
+        std::fs::write(root.join("product.json"), r#"{ "commit": "2901c5a" }"#).unwrap();
+
+        let server = from_cache(dir.path(), "latest", Flavor::default()).unwrap();
+        assert_eq!(server.version, "1.101.2");
+        assert_eq!(server.commit.as_deref(), Some("2901c5a"));
+        assert!(server.web);
+        assert!(is_server_binary(Path::new(&server.bin)));
+        assert!(!is_server_binary(Path::new(
+            "/opt/VSCode-linux-x64/bin/code"
+        )));
+
+        let url = UpdateService::new("http://mirror.local")
+            .with_platform(platform().unwrap())
+            .download_url("1.101.2")
+            .unwrap();
+        assert_eq!(
+            url,
+            format!("http://mirror.local/1.101.2/{}/stable", platform().unwrap())
+        );
+    }
+}
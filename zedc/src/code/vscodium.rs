@@ -0,0 +1,120 @@
+//! Release lookups for VSCodium, which publishes its builds as GitHub releases.
+
+use anyhow::{bail, Context};
+use reqwest::Client;
+use serde::Deserialize;
+
+use super::flavor::{Channel, Flavor};
+use super::prepare::ReleaseInfo;
+
+/// The GitHub REST API, used to look up VSCodium releases.
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+
+#[derive(Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<GitHubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GitHubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Returns the GitHub repository that publishes builds for the given channel.
+fn repository(flavor: Flavor) -> &'static str {
+    match flavor.channel {
+        Channel::Stable => "VSCodium/vscodium",
+        Channel::Insider => "VSCodium/vscodium-insiders",
+    }
+}
+
+/// Returns the name of the release asset for the current platform (e.g. `VSCodium-linux-x64-1.101.24242.tar.gz`).
+pub fn asset_name(flavor: Flavor, tag: &str) -> anyhow::Result<String> {
+    Ok(format!(
+        "VSCodium-{}-{}.{}",
+        flavor.platform()?,
+        tag,
+        flavor.archive_extension()
+    ))
+}
+
+/// Returns the download URL of the archive for a release tag, without querying the API.
+pub fn download_url(flavor: Flavor, tag: &str) -> anyhow::Result<String> {
+    Ok(format!(
+        "https://github.com/{}/releases/download/{}/{}",
+        repository(flavor),
+        tag,
+        asset_name(flavor, tag)?
+    ))
+}
+
+/// Looks up the latest release (`tag` = `None`) or a specific release tag of VSCodium.
+///
+/// # Arguments
+/// * `client` - The HTTP client to use
+/// * `api_url` - The base URL of the GitHub REST API
+/// * `flavor` - The VSCodium channel to look up
+/// * `tag` - (optional) The release tag, e.g. `1.101.24242`
+pub async fn release(
+    client: &Client,
+    api_url: &str,
+    flavor: Flavor,
+    tag: Option<&str>,
+) -> anyhow::Result<ReleaseInfo> {
+    let url = match tag {
+        Some(tag) => format!(
+            "{}/repos/{}/releases/tags/{}",
+            api_url,
+            repository(flavor),
+            tag
+        ),
+        None => format!("{}/repos/{}/releases/latest", api_url, repository(flavor)),
+    };
+    let body = get_text(client, &url).await?;
+    let release: GitHubRelease = serde_json::from_str(&body)
+        .with_context(|| format!("Invalid release metadata from {}", url))?;
+
+    let name = asset_name(flavor, &release.tag_name)?;
+    let Some(asset) = release.assets.iter().find(|a| a.name == name) else {
+        bail!(
+            "VSCodium {} does not provide a build for this platform ({})",
+            release.tag_name,
+            name
+        );
+    };
+
+    // Each archive is published with a `<asset>.sha256` file in `sha256sum` format.
+    let sha256_name = format!("{}.sha256", name);
+    let sha256 = match release.assets.iter().find(|a| a.name == sha256_name) {
+        Some(a) => get_text(client, &a.browser_download_url)
+            .await
+            .ok()
+            .and_then(|s| s.split_whitespace().next().map(str::to_owned)),
+        None => None,
+    };
+
+    Ok(ReleaseInfo {
+        version: release.tag_name,
+        commit: String::new(),
+        url: asset.browser_download_url.clone(),
+        sha256,
+    })
+}
+
+/// Fetches the body of the given URL as text. GitHub requires a user agent on API requests.
+async fn get_text(client: &Client, url: &str) -> anyhow::Result<String> {
+    let resp = client
+        .get(url)
+        .header("User-Agent", "zedc")
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .with_context(|| format!("Failed to query {}", url))?;
+    if !resp.status().is_success() {
+        bail!("{} returned HTTP {}", url, resp.status());
+    }
+    Ok(resp.text().await?)
+}
@@ -4,7 +4,7 @@ use anyhow::Result;
 use owo_colors::OwoColorize;
 use serde::Serialize;
 use serde_json::Value;
-use std::path::PathBuf;
+use std::path::Path;
 use std::process::Command;
 
 use crate::cmd;
@@ -15,14 +15,14 @@ use crate::util::find_dir_match;
 // ─── Version ──────────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-struct Version {
+pub(crate) struct Version {
     major: u32,
     minor: u32,
     patch: u32,
 }
 
 impl Version {
-    fn parse(raw: &str) -> Option<Self> {
+    pub(crate) fn parse(raw: &str) -> Option<Self> {
         let s = raw.trim().trim_start_matches('v');
         // Split on any of: '.', '-', '+' — stops at non-numeric segments
         // e.g. "2.44.0.windows.1" → [2, 44, 0]; "22.14.0-nightly" → [22, 14, 0]
@@ -158,7 +158,7 @@ fn infer_node_requirement(pkg: &Value) -> Option<(String, String)> {
 /// Splits a semver requirement string (e.g. ">=1.101.0", "^1.101.0", "~1.2.3")
 /// into its comparison operator and parsed [`Version`]. Bare versions (no operator)
 /// default to ">=".
-fn parse_requirement(req_str: &str) -> Option<(&'static str, Version)> {
+pub(crate) fn parse_requirement(req_str: &str) -> Option<(&'static str, Version)> {
     let (op, ver_str): (&'static str, &str) = if let Some(rest) = req_str.strip_prefix(">=") {
         (">=", rest.trim())
     } else if let Some(rest) = req_str.strip_prefix('^') {
@@ -175,7 +175,7 @@ fn parse_requirement(req_str: &str) -> Option<(&'static str, Version)> {
 }
 
 /// Returns `true` if `installed` satisfies the requirement described by `op`/`required`.
-fn satisfies(installed: &Version, op: &str, required: &Version) -> bool {
+pub(crate) fn satisfies(installed: &Version, op: &str, required: &Version) -> bool {
     match op {
         "^" => installed.major == required.major && installed >= required,
         "~" => {
@@ -262,7 +262,7 @@ fn check_node(pkg: &Value) -> CheckResult {
     }
 }
 
-fn check_pkg_mgr(pkg: &Value, ze_dir: &PathBuf) -> CheckResult {
+fn check_pkg_mgr(pkg: &Value, ze_dir: &Path) -> CheckResult {
     // Parse packageManager field: "pnpm@11.5.2" or "pnpm@11.5.2+sha512.xxx..."
     let (pm_name, required_version) = match pkg
         .get("packageManager")
@@ -506,7 +506,7 @@ fn check_vscode_with(pkg: &Value, installed_raw: Option<String>) -> CheckResult
     }
 }
 
-fn check_dependencies(ze_dir: &PathBuf) -> CheckResult {
+fn check_dependencies(ze_dir: &Path) -> CheckResult {
     let label = "node_modules".to_string();
     let installed = pm::check_dependencies(ze_dir);
     CheckResult {
@@ -8,13 +8,16 @@ use output::{exit, OutputFormat};
 mod cmd;
 mod code;
 mod doctor;
+mod mock;
 mod output;
 mod pm;
 mod pr;
+mod sandbox;
 mod setup;
 mod status;
 mod test;
 mod util;
+mod vsix;
 
 /// Initialize GitHub client if a personal access token is available
 fn init_github() -> Result<()> {
@@ -45,7 +48,9 @@ async fn dispatch(command: RootCommands) -> Result<i32> {
             generate_completions(shell)?;
             Ok(exit::SUCCESS)
         }
+        RootCommands::Code { subcommand } => code::handle_cmd(subcommand).await,
         RootCommands::Doctor => doctor::handle_cmd().await,
+        RootCommands::Mock { subcommand } => mock::handle_cmd(subcommand).await,
         RootCommands::PkgMgr { args } => match pm::handle_cmd(args) {
             Ok(out) => {
                 if json {
@@ -68,16 +73,34 @@ async fn dispatch(command: RootCommands) -> Result<i32> {
         },
         RootCommands::Pr {
             pr_number,
-            vsc_version,
+            vscode,
             skip_setup,
             build,
+            sandbox,
         } => {
-            pr::handle_cmd(pr_number, vsc_version, skip_setup, build).await?;
+            let report = pr::handle_cmd(pr_number, &vscode, skip_setup, build, &sandbox).await?;
+            let code = report.exit_code();
             if json {
-                output::emit_action_result("pr", true);
+                let mut body = serde_json::json!({
+                    "ok": code == exit::SUCCESS,
+                    "command": "pr",
+                    "vscode": report.vscode,
+                    "extensions": report.extensions,
+                });
+                if let Some(session) = &report.session {
+                    body["session"] = serde_json::to_value(session)?;
+                }
+                if let Some(attach) = &report.attach_config {
+                    body["attach_config"] = attach.clone();
+                }
+                if let Some(cli) = &report.cli {
+                    body["cli"] = serde_json::to_value(cli)?;
+                }
+                output::emit_json(&body);
             }
-            Ok(exit::SUCCESS)
+            Ok(code)
         }
+        RootCommands::Sandbox { subcommand } => sandbox::handle_cmd(subcommand).await,
         RootCommands::Setup { reference } => {
             setup::handle_cmd(reference).await?;
             if json {
@@ -86,9 +109,7 @@ async fn dispatch(command: RootCommands) -> Result<i32> {
             Ok(exit::SUCCESS)
         }
         RootCommands::Status { verbose } => status::handle_cmd(verbose).await,
-        RootCommands::Test { subcommand, config } => {
-            test::handle_cmd(config.install_cli, config.vsc_version, subcommand).await
-        }
+        RootCommands::Test { subcommand, config } => test::handle_cmd(config, subcommand).await,
         RootCommands::Version => {
             if json {
                 output::emit_json(&serde_json::json!({ "zedc": env!("CARGO_PKG_VERSION") }));
@@ -97,6 +118,7 @@ async fn dispatch(command: RootCommands) -> Result<i32> {
             }
             Ok(exit::SUCCESS)
         }
+        RootCommands::Vsix { subcommand } => vsix::handle_cmd(subcommand).await,
     }
 }
 
@@ -0,0 +1,193 @@
+//! Command module for handling `mock` commands.
+
+use super::ftp::{self, Ftp};
+use super::http::{self, Handler};
+use super::state::MockState;
+use super::zosmf::{self, Zosmf};
+use crate::output::{self, exit};
+use anyhow::Context;
+use owo_colors::OwoColorize;
+use std::{net::TcpListener, path::PathBuf, sync::Arc};
+
+/// Environment variable with the password of the `--cert` PKCS #12 file.
+pub const CERT_PASSWORD_ENV: &str = "ZEDC_MOCK_CERT_PASSWORD";
+
+/// Options that select the data served by a mock.
+#[derive(clap::Args)]
+pub struct FixtureArgs {
+    /// Directory with the data sets, USS files and jobs to serve (default: a small built-in sample)
+    #[arg(long, value_name = "DIR")]
+    fixtures: Option<PathBuf>,
+    /// Write changes back to the fixture directory instead of keeping them in memory
+    #[arg(long, requires = "fixtures")]
+    persist: bool,
+    /// The address to listen on
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+}
+
+impl FixtureArgs {
+    fn load(&self) -> anyhow::Result<MockState> {
+        match &self.fixtures {
+            Some(dir) => MockState::load(dir, self.persist),
+            None => Ok(MockState::sample()),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match &self.fixtures {
+            Some(dir) if self.persist => format!("{} (file-backed)", dir.display()),
+            Some(dir) => format!("{} (in memory)", dir.display()),
+            None => "built-in sample (in memory)".to_owned(),
+        }
+    }
+}
+
+#[derive(clap::Subcommand)]
+pub enum Commands {
+    /// Serve a mock z/OSMF REST API for the Data Sets, USS and Jobs trees
+    Zosmf {
+        /// The port to listen on
+        #[arg(long, default_value_t = zosmf::DEFAULT_PORT)]
+        port: u16,
+        /// Serve HTTPS with the certificate and key in a PKCS #12 file (password from $ZEDC_MOCK_CERT_PASSWORD)
+        #[arg(long, value_name = "FILE")]
+        cert: Option<PathBuf>,
+        #[command(flatten)]
+        fixtures: FixtureArgs,
+    },
+    /// Serve a mock z/OS FTP server for the FTP extension, logging every command it receives
+    Ftp {
+        /// The port to listen on
+        #[arg(long, default_value_t = ftp::DEFAULT_PORT)]
+        port: u16,
+        /// Also append the commands and replies to a file
+        #[arg(long, value_name = "FILE")]
+        log: Option<PathBuf>,
+        #[command(flatten)]
+        fixtures: FixtureArgs,
+    },
+}
+
+/// Loads a PKCS #12 certificate for serving HTTPS.
+fn tls_acceptor(cert: &PathBuf) -> anyhow::Result<native_tls::TlsAcceptor> {
+    let der = std::fs::read(cert).with_context(|| format!("Failed to read {}", cert.display()))?;
+    let password = std::env::var(CERT_PASSWORD_ENV).unwrap_or_default();
+    let identity = native_tls::Identity::from_pkcs12(&der, &password)
+        .with_context(|| format!("Failed to load the certificate in {}", cert.display()))?;
+    Ok(native_tls::TlsAcceptor::new(identity)?)
+}
+
+/// Wraps a handler so that every request is logged in text mode.
+fn logged(handler: Handler) -> Handler {
+    if !output::text_enabled() {
+        return handler;
+    }
+    Arc::new(move |request| {
+        let response = handler(request);
+        let status = if response.status < 400 {
+            response.status.green().to_string()
+        } else {
+            response.status.red().to_string()
+        };
+        let query: Vec<String> = request
+            .query
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        let target = if query.is_empty() {
+            request.path.clone()
+        } else {
+            format!("{}?{}", request.path, query.join("&"))
+        };
+        println!("  {} {} {}", status, request.method.bold(), target.dimmed());
+        response
+    })
+}
+
+/// Handles the logic for the `zedc mock` command.
+///
+/// # Arguments
+/// * `cmd` - The `mock` subcommand to run
+pub async fn handle_cmd(cmd: Commands) -> anyhow::Result<i32> {
+    match cmd {
+        Commands::Zosmf {
+            port,
+            cert,
+            fixtures,
+        } => {
+            let state = fixtures.load()?;
+            let tls = cert.as_ref().map(tls_acceptor).transpose()?;
+            let listener = TcpListener::bind((fixtures.host.as_str(), port))
+                .with_context(|| format!("Failed to listen on {}:{}", fixtures.host, port))?;
+            let zosmf = Arc::new(Zosmf::new(state, tls.is_some()));
+            let url = format!("{}://{}", zosmf.scheme, listener.local_addr()?);
+
+            if output::json_enabled() {
+                output::emit_json(&serde_json::json!({
+                    "ok": true,
+                    "command": "mock zosmf",
+                    "url": url,
+                    "fixtures": fixtures.describe(),
+                }));
+            } else {
+                println!("🧪 Mock z/OSMF listening on {}", url.bold());
+                println!("   {} {}", "Fixtures:".dimmed(), fixtures.describe());
+                println!(
+                    "   {}",
+                    "Connect a sandbox with `zedc test ... --zowe-config mock`; any user and password are accepted. Press Ctrl+C to stop."
+                        .dimmed()
+                );
+            }
+
+            let handler = logged(Arc::new(move |request| zosmf.handle(request)));
+            tokio::task::spawn_blocking(move || http::serve(listener, tls, handler)).await??;
+        }
+        Commands::Ftp {
+            port,
+            log,
+            fixtures,
+        } => {
+            let state = fixtures.load()?;
+            let log_file = log
+                .as_ref()
+                .map(|path| {
+                    std::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                        .with_context(|| format!("Failed to open {}", path.display()))
+                })
+                .transpose()?;
+            let listener = TcpListener::bind((fixtures.host.as_str(), port))
+                .with_context(|| format!("Failed to listen on {}:{}", fixtures.host, port))?;
+            let url = format!("ftp://{}", listener.local_addr()?);
+
+            if output::json_enabled() {
+                output::emit_json(&serde_json::json!({
+                    "ok": true,
+                    "command": "mock ftp",
+                    "url": url,
+                    "fixtures": fixtures.describe(),
+                    "log": log,
+                }));
+            } else {
+                println!("🧪 Mock z/OS FTP listening on {}", url.bold());
+                println!("   {} {}", "Fixtures:".dimmed(), fixtures.describe());
+                if let Some(log) = &log {
+                    println!("   {} {}", "Command log:".dimmed(), log.display());
+                }
+                println!(
+                    "   {}",
+                    "Connect a sandbox with `zedc test ... --zowe-config mock`; any user and password are accepted. Press Ctrl+C to stop."
+                        .dimmed()
+                );
+            }
+
+            let ftp = Arc::new(Ftp::new(state, log_file));
+            tokio::task::spawn_blocking(move || ftp.serve(listener)).await??;
+        }
+    }
+
+    Ok(exit::SUCCESS)
+}
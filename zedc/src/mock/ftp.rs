@@ -0,0 +1,918 @@
+//! A mock of the z/OS FTP server, covering what `zowe-explorer-ftp-extension` uses: MVS data set and
+//! member listings, USS listings, transfers in both directions, and the JES interface
+//! (`SITE FILETYPE=JES`) for listing jobs, reading spool files and submitting JCL.
+//!
+//! Only passive (`PASV`/`EPSV`) and active (`PORT`) data connections without TLS are supported.
+//! Every command is logged with its reply, so that a session can be replayed when reproducing bugs.
+
+use super::state::{normalize_path, Dataset, Job, MockError, MockState, UssEntry};
+use owo_colors::OwoColorize;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{IpAddr, SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+};
+
+/// The port the mock FTP server listens on by default.
+pub const DEFAULT_PORT: u16 = 2121;
+
+/// The mock FTP server.
+pub struct Ftp {
+    pub state: Mutex<MockState>,
+    /// The file that the command log is appended to, if any.
+    log: Option<Mutex<File>>,
+}
+
+impl Ftp {
+    pub fn new(state: MockState, log: Option<File>) -> Self {
+        Self {
+            state: Mutex::new(state),
+            log: log.map(Mutex::new),
+        }
+    }
+
+    /// Serves FTP sessions on a listener until the process exits.
+    pub fn serve(self: Arc<Self>, listener: TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let ftp = self.clone();
+            std::thread::spawn(move || {
+                let _ = Session::run(&ftp, stream);
+            });
+        }
+        Ok(())
+    }
+
+    /// Logs a command received from a client and the final line of the reply.
+    fn log(&self, peer: &str, command: &str, reply: &str) {
+        if crate::output::text_enabled() {
+            let code = reply.get(..3).unwrap_or(reply);
+            let code = if reply.starts_with(['4', '5']) {
+                code.red().to_string()
+            } else {
+                code.green().to_string()
+            };
+            println!("  {} {} {}", code, command.bold(), peer.dimmed());
+        }
+        if let Some(file) = &self.log {
+            let mut file = file.lock().unwrap();
+            let _ = writeln!(file, "{} > {}", peer, command);
+            let _ = writeln!(file, "{} < {}", peer, reply);
+        }
+    }
+}
+
+/// The current working directory of a session.
+#[derive(Debug, Clone, PartialEq)]
+enum Cwd {
+    /// A data set name prefix ending in `.`, or the name of a partitioned data set.
+    Mvs(String),
+    /// A USS directory.
+    Uss(String),
+}
+
+/// The target of a command, resolved against the working directory.
+#[derive(Debug, PartialEq)]
+enum Target {
+    Dataset(String, Option<String>),
+    Uss(String),
+}
+
+impl Cwd {
+    fn resolve(&self, name: &str, state: &MockState) -> Target {
+        let name = name.trim();
+        if let Some(quoted) = name.strip_prefix('\'') {
+            return dataset_target(quoted.trim_end_matches('\''));
+        }
+        if name.starts_with('/') {
+            return Target::Uss(normalize_path(name));
+        }
+        match self {
+            Cwd::Uss(dir) => Target::Uss(normalize_path(&format!("{}/{}", dir, name))),
+            Cwd::Mvs(prefix) if prefix.ends_with('.') => {
+                dataset_target(&format!("{}{}", prefix, name))
+            }
+            Cwd::Mvs(pds) if state.datasets.contains_key(pds) && !name.contains('(') => {
+                Target::Dataset(pds.clone(), Some(name.to_uppercase()))
+            }
+            Cwd::Mvs(pds) => dataset_target(&format!("{}.{}", pds, name)),
+        }
+    }
+}
+
+fn dataset_target(name: &str) -> Target {
+    let name = name.to_uppercase();
+    match name.split_once('(') {
+        Some((dsn, member)) => Target::Dataset(
+            dsn.to_owned(),
+            Some(member.trim_end_matches(')').to_owned()),
+        ),
+        None => Target::Dataset(name, None),
+    }
+}
+
+/// The state of one client connection.
+struct Session<'a> {
+    ftp: &'a Ftp,
+    peer: String,
+    writer: TcpStream,
+    local_ip: IpAddr,
+    pending_user: Option<String>,
+    user: Option<String>,
+    cwd: Cwd,
+    binary: bool,
+    /// Whether `SITE FILETYPE=JES` is active.
+    jes: bool,
+    jes_owner: Option<String>,
+    jes_jobname: String,
+    passive: Option<TcpListener>,
+    active: Option<SocketAddr>,
+    last_reply: String,
+}
+
+impl<'a> Session<'a> {
+    fn run(ftp: &'a Ftp, stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut session = Session {
+            ftp,
+            peer: stream.peer_addr()?.to_string(),
+            local_ip: stream.local_addr()?.ip(),
+            writer: stream,
+            pending_user: None,
+            user: None,
+            cwd: Cwd::Uss("/".to_owned()),
+            binary: false,
+            jes: false,
+            jes_owner: None,
+            jes_jobname: "*".to_owned(),
+            passive: None,
+            active: None,
+            last_reply: String::new(),
+        };
+        session.reply("220-zedc mock z/OS FTP server\r\n220 Connection will close if idle for more than 5 minutes.")?;
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            let (command, arg) = line.split_once(' ').unwrap_or((line, ""));
+            let command = command.to_uppercase();
+            let keep_open = session.command(&command, arg.trim())?;
+            let logged = if command == "PASS" {
+                "PASS ********".to_owned()
+            } else {
+                line.to_owned()
+            };
+            ftp.log(&session.peer, &logged, &session.last_reply);
+            if !keep_open {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Writes a (possibly multi-line) reply.
+    fn reply(&mut self, text: &str) -> io::Result<()> {
+        self.last_reply = text.rsplit("\r\n").next().unwrap_or(text).to_owned();
+        write!(self.writer, "{}\r\n", text)
+    }
+
+    /// Handles a command, returning `false` when the client quit.
+    fn command(&mut self, command: &str, arg: &str) -> io::Result<bool> {
+        match command {
+            "USER" => {
+                self.pending_user = Some(arg.to_uppercase());
+                self.reply("331 Send password please.")?;
+            }
+            "PASS" => {
+                let user = self
+                    .pending_user
+                    .take()
+                    .unwrap_or_else(|| "IBMUSER".to_owned());
+                self.cwd = Cwd::Mvs(format!("{}.", user));
+                self.reply(&format!(
+                    "230 {} is logged on.  Working directory is \"{}.\".",
+                    user, user
+                ))?;
+                self.user = Some(user);
+            }
+            "QUIT" => {
+                self.reply("221 Quit command received. Goodbye.")?;
+                return Ok(false);
+            }
+            "SYST" => self.reply(
+                "215 MVS is the operating system of this server. FTP Server is running on z/OS.",
+            )?,
+            "FEAT" => self.reply("211-Extensions supported\r\n EPSV\r\n PASV\r\n211 End")?,
+            "AUTH" => {
+                self.reply("534 TLS is not supported by the mock server; set secureFtp to false")?
+            }
+            "NOOP" => self.reply("200 OK")?,
+            _ if self.user.is_none() => self.reply("530 Not logged in.")?,
+            "TYPE" => {
+                self.binary = arg.to_uppercase().starts_with('I');
+                let kind = if self.binary {
+                    "Image"
+                } else {
+                    "Ascii NonPrint"
+                };
+                self.reply(&format!("200 Representation type is {}", kind))?;
+            }
+            "STRU" | "MODE" | "OPTS" => self.reply("200 OK")?,
+            "PWD" | "XPWD" => self.pwd()?,
+            "CWD" | "XCWD" => self.cwd(arg)?,
+            "CDUP" | "XCUP" => self.cwd("..")?,
+            "PASV" => self.pasv(false)?,
+            "EPSV" => self.pasv(true)?,
+            "PORT" => self.port(arg)?,
+            "SITE" => self.site(arg)?,
+            "LIST" | "NLST" => self.list(arg, command == "NLST")?,
+            "RETR" => self.retr(arg)?,
+            "STOR" => self.stor(arg)?,
+            "DELE" | "RMD" | "XRMD" => self.delete(arg)?,
+            "MKD" | "XMKD" => self.mkd(arg)?,
+            _ => self.reply(&format!("502 {} command not implemented.", command))?,
+        }
+        Ok(true)
+    }
+
+    fn pwd(&mut self) -> io::Result<()> {
+        match self.cwd.clone() {
+            Cwd::Mvs(prefix) if prefix.ends_with('.') => {
+                self.reply(&format!("257 \"'{}'\" is working directory.", prefix))
+            }
+            Cwd::Mvs(pds) => self.reply(&format!(
+                "257 \"'{}'\" partitioned data set is working directory.",
+                pds
+            )),
+            Cwd::Uss(dir) => self.reply(&format!("257 \"{}\" is the HFS working directory.", dir)),
+        }
+    }
+
+    fn cwd(&mut self, arg: &str) -> io::Result<()> {
+        let state = self.ftp.state.lock().unwrap();
+        let cwd = match (&self.cwd, arg) {
+            (Cwd::Uss(dir), "..") => Cwd::Uss(normalize_path(
+                dir.rsplit_once('/').map_or("/", |(parent, _)| parent),
+            )),
+            (Cwd::Mvs(prefix), "..") => {
+                let mut qualifiers: Vec<&str> =
+                    prefix.split('.').filter(|q| !q.is_empty()).collect();
+                qualifiers.pop();
+                Cwd::Mvs(format!("{}.", qualifiers.join(".")))
+            }
+            _ => match self.cwd.resolve(arg, &state) {
+                Target::Uss(path) => match state.uss_entry(&path) {
+                    Ok(UssEntry::Dir) => Cwd::Uss(path),
+                    _ => {
+                        drop(state);
+                        return self.reply(&format!("550 {} is not a directory.", path));
+                    }
+                },
+                Target::Dataset(dsn, _)
+                    if state
+                        .datasets
+                        .get(&dsn)
+                        .is_some_and(|d| d.members.is_some()) =>
+                {
+                    Cwd::Mvs(dsn)
+                }
+                Target::Dataset(dsn, _) => Cwd::Mvs(format!("{}.", dsn.trim_end_matches('.'))),
+            },
+        };
+        drop(state);
+        self.cwd = cwd.clone();
+        match cwd {
+            Cwd::Mvs(prefix) if prefix.ends_with('.') => self.reply(&format!(
+                "250 \"'{}'\" is the working directory name prefix.",
+                prefix
+            )),
+            Cwd::Mvs(pds) => self.reply(&format!(
+                "250 The working directory \"{}\" is a partitioned data set",
+                pds
+            )),
+            Cwd::Uss(dir) => self.reply(&format!(
+                "250 HFS directory {} is the current working directory",
+                dir
+            )),
+        }
+    }
+
+    fn pasv(&mut self, extended: bool) -> io::Result<()> {
+        let listener = TcpListener::bind((self.local_ip, 0))?;
+        let port = listener.local_addr()?.port();
+        self.passive = Some(listener);
+        self.active = None;
+        match (extended, self.local_ip) {
+            (true, _) => self.reply(&format!(
+                "229 Entering Extended Passive Mode (|||{}|)",
+                port
+            )),
+            (false, IpAddr::V4(ip)) => {
+                let [a, b, c, d] = ip.octets();
+                self.reply(&format!(
+                    "227 Entering Passive Mode ({},{},{},{},{},{})",
+                    a,
+                    b,
+                    c,
+                    d,
+                    port >> 8,
+                    port & 0xff
+                ))
+            }
+            (false, IpAddr::V6(_)) => self.reply("522 Use EPSV for IPv6 connections."),
+        }
+    }
+
+    fn port(&mut self, arg: &str) -> io::Result<()> {
+        let parts: Vec<u8> = arg
+            .split(',')
+            .filter_map(|p| p.trim().parse().ok())
+            .collect();
+        let [a, b, c, d, p1, p2] = parts[..] else {
+            return self.reply("501 Invalid PORT command.");
+        };
+        self.active = Some(SocketAddr::from((
+            [a, b, c, d],
+            u16::from(p1) << 8 | u16::from(p2),
+        )));
+        self.passive = None;
+        self.reply("200 Port request OK.")
+    }
+
+    fn site(&mut self, arg: &str) -> io::Result<()> {
+        for param in arg.split_whitespace() {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            match key.to_uppercase().as_str() {
+                "FILETYPE" => match value.to_uppercase().as_str() {
+                    "JES" => self.jes = true,
+                    "SEQ" => self.jes = false,
+                    _ => return self.reply(&format!("501 Unsupported FILETYPE {}", value)),
+                },
+                "JESOWNER" => self.jes_owner = Some(value.to_uppercase()),
+                "JESJOBNAME" => self.jes_jobname = value.to_uppercase(),
+                // Allocation attributes (RECFM, LRECL, ...) and JESSTATUS are accepted and ignored
+                _ => {}
+            }
+        }
+        self.reply("200 SITE command was accepted")
+    }
+
+    /// Opens the data connection set up by `PASV`, `EPSV` or `PORT`.
+    fn open_data(&mut self) -> io::Result<TcpStream> {
+        if let Some(listener) = self.passive.take() {
+            return Ok(listener.accept()?.0);
+        }
+        match self.active.take() {
+            Some(addr) => TcpStream::connect(addr),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "Use PORT or PASV first",
+            )),
+        }
+    }
+
+    fn send_data(&mut self, what: &str, data: &[u8]) -> io::Result<()> {
+        let data = if self.binary {
+            data.to_vec()
+        } else {
+            to_crlf(data)
+        };
+        self.reply(&format!("125 Sending data set {}", what))?;
+        match self.open_data() {
+            Ok(mut conn) => {
+                conn.write_all(&data)?;
+                drop(conn);
+                self.reply("250 Transfer completed successfully.")
+            }
+            Err(e) => self.reply(&format!("425 Can't open data connection: {}", e)),
+        }
+    }
+
+    fn receive_data(&mut self, what: &str) -> io::Result<Option<Vec<u8>>> {
+        self.reply(&format!("125 Storing data set {}", what))?;
+        let mut data = Vec::new();
+        match self.open_data() {
+            Ok(mut conn) => {
+                conn.read_to_end(&mut data)?;
+            }
+            Err(e) => {
+                self.reply(&format!("425 Can't open data connection: {}", e))?;
+                return Ok(None);
+            }
+        }
+        Ok(Some(if self.binary { data } else { from_crlf(&data) }))
+    }
+
+    fn owner(&self) -> String {
+        self.jes_owner
+            .clone()
+            .or_else(|| self.user.clone())
+            .unwrap_or_else(|| "*".to_owned())
+    }
+
+    fn list(&mut self, arg: &str, names_only: bool) -> io::Result<()> {
+        // Ignore `ls`-style flags such as `-al`
+        let arg = arg
+            .split_whitespace()
+            .filter(|a| !a.starts_with('-'))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let state = self.ftp.state.lock().unwrap();
+        let lines = if self.jes {
+            jes_listing(&state, &arg, &self.owner(), &self.jes_jobname, names_only)
+        } else {
+            let target = if arg.is_empty() {
+                match &self.cwd {
+                    Cwd::Mvs(prefix) if prefix.ends_with('.') => {
+                        Target::Dataset(format!("{}**", prefix), None)
+                    }
+                    Cwd::Mvs(pds) => Target::Dataset(pds.clone(), Some("*".to_owned())),
+                    Cwd::Uss(dir) => Target::Uss(dir.clone()),
+                }
+            } else {
+                self.cwd.resolve(&arg, &state)
+            };
+            match target {
+                Target::Dataset(dsn, Some(pattern)) => {
+                    member_listing(&state, &dsn, &pattern, names_only)
+                }
+                Target::Dataset(pattern, None) => {
+                    dataset_listing(&state, &pattern, &self.cwd, names_only)
+                }
+                Target::Uss(path) => uss_listing(&state, &path, names_only),
+            }
+        };
+        drop(state);
+        match lines {
+            Ok(lines) => self.send_data("listing", format!("{}\n", lines.join("\n")).as_bytes()),
+            Err(e) => self.reply(&format!("550 {}", e)),
+        }
+    }
+
+    fn retr(&mut self, arg: &str) -> io::Result<()> {
+        let state = self.ftp.state.lock().unwrap();
+        let data = if self.jes {
+            spool(&state, arg)
+        } else {
+            match self.cwd.resolve(arg, &state) {
+                Target::Dataset(dsn, member) => {
+                    state.read(&dsn, member.as_deref()).map(<[u8]>::to_vec)
+                }
+                Target::Uss(path) => match state.uss_entry(&path) {
+                    Ok(UssEntry::File(data)) => Ok(data.clone()),
+                    Ok(UssEntry::Dir) => {
+                        Err(MockError::Invalid(format!("{} is a directory", path)))
+                    }
+                    Err(e) => Err(e),
+                },
+            }
+        };
+        drop(state);
+        match data {
+            Ok(data) => self.send_data(arg, &data),
+            Err(e) => self.reply(&format!("550 {}", e)),
+        }
+    }
+
+    fn stor(&mut self, arg: &str) -> io::Result<()> {
+        let Some(data) = self.receive_data(arg)? else {
+            return Ok(());
+        };
+        let mut state = self.ftp.state.lock().unwrap();
+        if self.jes {
+            let owner = self.user.clone().unwrap_or_default();
+            let result = state
+                .submit(&String::from_utf8_lossy(&data), &owner)
+                .map(|job| job.jobid.clone());
+            drop(state);
+            return match result {
+                Ok(jobid) => self.reply(&format!(
+                    "250-It is known to JES as {}\r\n250 Transfer completed successfully.",
+                    jobid
+                )),
+                Err(e) => self.reply(&format!("550 {}", e)),
+            };
+        }
+        let result = match self.cwd.resolve(arg, &state) {
+            Target::Dataset(dsn, None) if !state.datasets.contains_key(&dsn) => {
+                state.create_dataset(&dsn, Dataset::sequential(data))
+            }
+            Target::Dataset(dsn, member) => state.write(&dsn, member.as_deref(), data).map(|_| ()),
+            Target::Uss(path) => state.write_file(&path, data).map(|_| ()),
+        };
+        drop(state);
+        match result {
+            Ok(()) => self.reply("250 Transfer completed successfully."),
+            Err(e) => self.reply(&format!("550 {}", e)),
+        }
+    }
+
+    fn delete(&mut self, arg: &str) -> io::Result<()> {
+        let mut state = self.ftp.state.lock().unwrap();
+        let result = if self.jes {
+            state.delete_job(arg).map(|_| "250 Cancel successful")
+        } else {
+            match self.cwd.resolve(arg, &state) {
+                Target::Dataset(dsn, member) => state
+                    .delete_dataset(&dsn, member.as_deref())
+                    .map(|_| "250 Delete completed"),
+                Target::Uss(path) => state.delete_path(&path).map(|_| "250 Delete completed"),
+            }
+        };
+        drop(state);
+        match result {
+            Ok(reply) => self.reply(reply),
+            Err(e) => self.reply(&format!("550 {}", e)),
+        }
+    }
+
+    fn mkd(&mut self, arg: &str) -> io::Result<()> {
+        let mut state = self.ftp.state.lock().unwrap();
+        let result = match self.cwd.resolve(arg, &state) {
+            Target::Dataset(dsn, None) => state
+                .create_dataset(&dsn, Dataset::partitioned())
+                .map(|_| format!("257 \"'{}'\" created.", dsn)),
+            Target::Dataset(dsn, Some(member)) => Err(MockError::Invalid(format!(
+                "Cannot create a directory for member {}({})",
+                dsn, member
+            ))),
+            Target::Uss(path) => state
+                .mkdir(&path)
+                .map(|_| format!("257 \"{}\" created.", path)),
+        };
+        drop(state);
+        match result {
+            Ok(reply) => self.reply(&reply),
+            Err(e) => self.reply(&format!("550 {}", e)),
+        }
+    }
+}
+
+/// Converts line endings to CRLF for ASCII transfers.
+fn to_crlf(data: &[u8]) -> Vec<u8> {
+    let mut converted = Vec::with_capacity(data.len());
+    for (i, &b) in data.iter().enumerate() {
+        if b == b'\n' && (i == 0 || data[i - 1] != b'\r') {
+            converted.push(b'\r');
+        }
+        converted.push(b);
+    }
+    converted
+}
+
+/// Converts CRLF line endings to LF for ASCII transfers.
+fn from_crlf(data: &[u8]) -> Vec<u8> {
+    let mut converted = Vec::with_capacity(data.len());
+    for (i, &b) in data.iter().enumerate() {
+        if b == b'\r' && data.get(i + 1) == Some(&b'\n') {
+            continue;
+        }
+        converted.push(b);
+    }
+    converted
+}
+
+/// Lists data sets in the z/OS FTP format. Names below the working directory prefix are shown
+/// relative to it, like on z/OS; other names are quoted.
+fn dataset_listing(
+    state: &MockState,
+    pattern: &str,
+    cwd: &Cwd,
+    names_only: bool,
+) -> Result<Vec<String>, MockError> {
+    let datasets = state.list_datasets(pattern);
+    if datasets.is_empty() {
+        return Err(MockError::NotFound("No data sets found.".to_owned()));
+    }
+    let prefix = match cwd {
+        Cwd::Mvs(prefix) if prefix.ends_with('.') => prefix.as_str(),
+        _ => "",
+    };
+    let mut lines = Vec::new();
+    if !names_only {
+        lines.push("Volume Unit    Referred Ext Used Recfm Lrecl BlkSz Dsorg Dsname".to_owned());
+    }
+    for (name, dataset) in datasets {
+        let display = match name.strip_prefix(prefix) {
+            Some(relative) if !prefix.is_empty() => relative.to_owned(),
+            _ => format!("'{}'", name),
+        };
+        if names_only {
+            lines.push(display);
+        } else {
+            lines.push(format!(
+                "MOCK01 3390   2024/01/01  1   15  {:<5}{:>6} {:>5}  {:<4}{}",
+                dataset.recfm,
+                dataset.lrecl,
+                dataset.lrecl * 349,
+                dataset.dsorg(),
+                display
+            ));
+        }
+    }
+    Ok(lines)
+}
+
+/// Lists the members of a partitioned data set in the z/OS FTP format.
+fn member_listing(
+    state: &MockState,
+    dsn: &str,
+    pattern: &str,
+    names_only: bool,
+) -> Result<Vec<String>, MockError> {
+    let members: Vec<&String> = state
+        .members(dsn)?
+        .into_iter()
+        .filter(|m| super::state::matches_wildcard(pattern, m))
+        .collect();
+    if members.is_empty() {
+        return Err(MockError::NotFound("No members found.".to_owned()));
+    }
+    let mut lines = Vec::new();
+    if !names_only {
+        lines.push(" Name     VV.MM   Created       Changed      Size  Init   Mod   Id".to_owned());
+    }
+    for member in members {
+        if names_only {
+            lines.push(member.clone());
+            continue;
+        }
+        let size = state.read(dsn, Some(member)).map_or(0, |data| {
+            data.split(|&b| b == b'\n')
+                .filter(|l| !l.is_empty())
+                .count()
+        });
+        lines.push(format!(
+            "{:<8}  01.00 2024/01/01 2024/01/01 00:00 {:>5} {:>5}     0 IBMUSER",
+            member, size, size
+        ));
+    }
+    Ok(lines)
+}
+
+/// Lists a USS directory (or a single file) in `ls -l` format.
+fn uss_listing(state: &MockState, path: &str, names_only: bool) -> Result<Vec<String>, MockError> {
+    let entries: Vec<(String, UssEntry)> = match state.uss_entry(path)? {
+        UssEntry::Dir => state
+            .list_dir(path)?
+            .into_iter()
+            .map(|(name, entry)| (name.to_owned(), entry.clone()))
+            .collect(),
+        file => vec![(
+            path.rsplit('/').next().unwrap_or(path).to_owned(),
+            file.clone(),
+        )],
+    };
+    if names_only {
+        return Ok(entries.into_iter().map(|(name, _)| name).collect());
+    }
+    let mut lines = vec![format!("total {}", entries.len())];
+    for (name, entry) in entries {
+        let (mode, links, size) = match entry {
+            UssEntry::Dir => ("drwxr-xr-x", 2, 8192),
+            UssEntry::File(data) => ("-rw-r--r--", 1, data.len()),
+        };
+        lines.push(format!(
+            "{}  {:>2} IBMUSER  SYS1     {:>10} Jan  1  2024 {}",
+            mode, links, size, name
+        ));
+    }
+    Ok(lines)
+}
+
+/// Formats the return code of a job like the JES interface does (`RC=0000`).
+fn job_rc(job: &Job) -> String {
+    match &job.retcode {
+        Some(rc) => match rc.strip_prefix("CC ") {
+            Some(code) => format!("RC={}", code),
+            None => format!("({})", rc),
+        },
+        None => String::new(),
+    }
+}
+
+const JES_HEADER: &str = "JOBNAME  JOBID    OWNER    STATUS CLASS";
+
+/// Lists jobs in the JES interface format, or the spool files of a job if `arg` is a job ID.
+fn jes_listing(
+    state: &MockState,
+    arg: &str,
+    owner: &str,
+    jobname: &str,
+    names_only: bool,
+) -> Result<Vec<String>, MockError> {
+    let job_line = |job: &Job, suffix: &str| {
+        format!(
+            "{:<8} {:<8} {:<8} {:<6} {:<8} {} {}",
+            job.jobname,
+            job.jobid,
+            job.owner,
+            job.status,
+            job.class,
+            job_rc(job),
+            suffix
+        )
+        .trim_end()
+        .to_owned()
+    };
+
+    if let Ok(job) = state.job(arg) {
+        let mut lines = vec![
+            JES_HEADER.to_owned(),
+            job_line(job, ""),
+            "--------".to_owned(),
+        ];
+        lines.push("         ID  STEPNAME PROCSTEP C DDNAME   BYTE-COUNT".to_owned());
+        for (i, file) in job.spool.iter().enumerate() {
+            lines.push(format!(
+                "         {:03} {:<8} {:<8} A {:<8} {:>10}",
+                i + 1,
+                file.stepname,
+                "",
+                file.ddname,
+                file.records.len()
+            ));
+        }
+        lines.push(format!("{} spool files", job.spool.len()));
+        return Ok(lines);
+    }
+
+    let pattern = if arg.is_empty() { jobname } else { arg };
+    let jobs = state.list_jobs(owner, pattern);
+    if jobs.is_empty() {
+        return Err(MockError::NotFound(
+            "No jobs found on Held queue".to_owned(),
+        ));
+    }
+    if names_only {
+        return Ok(jobs.into_iter().map(|j| j.jobid.clone()).collect());
+    }
+    let mut lines = vec![JES_HEADER.to_owned()];
+    lines.extend(
+        jobs.into_iter()
+            .map(|job| job_line(job, &format!("{} spool files", job.spool.len()))),
+    );
+    Ok(lines)
+}
+
+/// Returns a spool file (`JOB00001.2`), or all spool files of a job (`JOB00001.X`).
+fn spool(state: &MockState, arg: &str) -> Result<Vec<u8>, MockError> {
+    let (jobid, file) = arg.split_once('.').unwrap_or((arg, "X"));
+    let job = state.job(jobid)?;
+    if file.eq_ignore_ascii_case("X") {
+        return Ok(job
+            .spool
+            .iter()
+            .map(|f| f.records.as_str())
+            .collect::<Vec<_>>()
+            .join("!! END OF JES SPOOL FILE !!\n")
+            .into_bytes());
+    }
+    file.parse::<usize>()
+        .ok()
+        .and_then(|i| job.spool.get(i.checked_sub(1)?))
+        .map(|f| f.records.clone().into_bytes())
+        .ok_or_else(|| MockError::NotFound(format!("Spool file {} of {} not found", file, jobid)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_listings() {
+        let state = MockState::sample();
+        let cwd = Cwd::Mvs("IBMUSER.".to_owned());
+        let lines = dataset_listing(&state, "IBMUSER.**", &cwd, false).unwrap();
+        assert!(lines[0].starts_with("Volume Unit"));
+        assert!(lines[1].ends_with("PO  CNTL"), "{}", lines[1]);
+        let names = dataset_listing(&state, "IBMUSER.**", &Cwd::Uss("/".to_owned()), true).unwrap();
+        assert_eq!(names, ["'IBMUSER.CNTL'", "'IBMUSER.DATA'"]);
+
+        let members = member_listing(&state, "IBMUSER.CNTL", "*", false).unwrap();
+        assert!(members[1].starts_with("IEFBR14   01.00"), "{}", members[1]);
+        assert_eq!(
+            cwd.resolve("CNTL(IEFBR14)", &state),
+            Target::Dataset("IBMUSER.CNTL".to_owned(), Some("IEFBR14".to_owned()))
+        );
+        assert_eq!(
+            Cwd::Mvs("IBMUSER.CNTL".to_owned()).resolve("iefbr14", &state),
+            Target::Dataset("IBMUSER.CNTL".to_owned(), Some("IEFBR14".to_owned()))
+        );
+
+        let jobs = jes_listing(&state, "", "IBMUSER", "*", false).unwrap();
+        assert_eq!(jobs[0], JES_HEADER);
+        assert_eq!(
+            jobs[1],
+            "IEFBR14  JOB00001 IBMUSER  OUTPUT A        RC=0000 3 spool files"
+        );
+        let files = jes_listing(&state, "JOB00001", "IBMUSER", "*", false).unwrap();
+        assert_eq!(files.last().unwrap(), "3 spool files");
+        assert!(jes_listing(&state, "", "OTHER", "*", false).is_err());
+    }
+
+    /// A minimal FTP client for driving the server in tests.
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Client {
+        fn send(&mut self, command: &str) -> String {
+            write!(self.writer, "{}\r\n", command).unwrap();
+            self.read_reply()
+        }
+
+        fn read_reply(&mut self) -> String {
+            let mut reply = String::new();
+            loop {
+                let mut line = String::new();
+                self.reader.read_line(&mut line).unwrap();
+                reply.push_str(&line);
+                if line.as_bytes().get(3) == Some(&b' ') {
+                    return reply;
+                }
+            }
+        }
+
+        /// Runs a command over a passive data connection, returning the data and the final reply.
+        fn transfer(&mut self, command: &str, upload: Option<&str>) -> (String, String) {
+            let reply = self.send("EPSV");
+            let port: u16 = reply.split('|').nth(3).unwrap().parse().unwrap();
+            let ip = self.writer.peer_addr().unwrap().ip();
+            let mut data = TcpStream::connect((ip, port)).unwrap();
+            let start = self.send(command);
+            assert!(start.starts_with("125"), "{}", start);
+            let mut text = String::new();
+            match upload {
+                Some(body) => data.write_all(body.as_bytes()).unwrap(),
+                None => {
+                    data.read_to_string(&mut text).unwrap();
+                }
+            }
+            drop(data);
+            (text, self.read_reply())
+        }
+    }
+
+    #[test]
+    fn serves_sessions_and_logs_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("ftp.log");
+        let ftp = Arc::new(Ftp::new(
+            MockState::sample(),
+            Some(File::create(&log).unwrap()),
+        ));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || ftp.serve(listener));
+
+        let control = TcpStream::connect(addr).unwrap();
+        let mut client = Client {
+            reader: BufReader::new(control.try_clone().unwrap()),
+            writer: control,
+        };
+        assert!(client.read_reply().starts_with("220"));
+        assert!(client.send("USER ibmuser").starts_with("331"));
+        assert!(client
+            .send("PASS secret")
+            .contains("Working directory is \"IBMUSER.\""));
+
+        let (listing, done) = client.transfer("LIST", None);
+        assert!(done.starts_with("250"));
+        assert!(listing.contains("PS  DATA\r\n"), "{}", listing);
+        let (data, _) = client.transfer("RETR 'IBMUSER.DATA'", None);
+        assert_eq!(data, "Hello from the zedc mock z/OSMF server\r\n");
+
+        assert!(client.send("SITE FILETYPE=JES").starts_with("200"));
+        let (_, done) = client.transfer(
+            "STOR JCL",
+            Some("//FTPJOB   JOB (ACCT)\r\n//S1 EXEC PGM=IEFBR14\r\n"),
+        );
+        assert!(done.contains("It is known to JES as JOB00002"), "{}", done);
+        let (jobs, _) = client.transfer("LIST", None);
+        assert!(jobs.contains("FTPJOB   JOB00002"), "{}", jobs);
+        let (job_log, _) = client.transfer("RETR JOB00002.1", None);
+        assert!(job_log.contains("$HASP395 FTPJOB   ENDED"), "{}", job_log);
+        assert!(client.send("QUIT").starts_with("221"));
+
+        let mut log_text = String::new();
+        for _ in 0..50 {
+            log_text = std::fs::read_to_string(&log).unwrap();
+            if log_text.contains("> QUIT") {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert!(log_text.contains("> PASS ********"), "{}", log_text);
+        assert!(!log_text.contains("secret"));
+        assert!(log_text.contains("> SITE FILETYPE=JES"));
+        assert!(log_text.contains("< 250 Transfer completed successfully."));
+    }
+}
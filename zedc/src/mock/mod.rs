@@ -0,0 +1,12 @@
+//! "Root" module for the mock mainframe services started by `zedc mock`.
+//!
+//! The mocks let Zowe Explorer run against a local, deterministic backend: data sets, USS files and
+//! jobs are seeded from a fixture directory (see [`state`]) and served over the same protocols that
+//! the extensions use to talk to z/OS: the z/OSMF REST API and the z/OS FTP server.
+
+mod cmd;
+pub mod ftp;
+mod http;
+mod state;
+pub mod zosmf;
+pub use cmd::{handle_cmd, Commands};
@@ -0,0 +1,807 @@
+//! The data sets, USS files and jobs served by the mock servers.
+//!
+//! The state is kept in memory and seeded from a fixture directory (or a small built-in sample).
+//! When it is file-backed, every change is also written back to the fixture directory:
+//!
+//! ```text
+//! <fixtures>/
+//!   datasets/IBMUSER.DATA        sequential data set
+//!   datasets/IBMUSER.CNTL/JCL    member of a partitioned data set
+//!   uss/u/ibmuser/hello.txt      USS file (`uss/` is the root directory `/`)
+//!   jobs/JOB00001.json           job with its spool files
+//! ```
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// An error from a mock operation, mapped to an HTTP status or FTP reply by each server.
+#[derive(Debug, PartialEq)]
+pub enum MockError {
+    NotFound(String),
+    Conflict(String),
+    Invalid(String),
+}
+
+impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MockError::NotFound(m) | MockError::Conflict(m) | MockError::Invalid(m) => {
+                f.write_str(m)
+            }
+        }
+    }
+}
+
+pub type MockResult<T> = Result<T, MockError>;
+
+/// A data set and its contents.
+#[derive(Debug, Clone)]
+pub struct Dataset {
+    pub recfm: String,
+    pub lrecl: u32,
+    /// The members of a partitioned data set, or `None` for a sequential data set.
+    pub members: Option<BTreeMap<String, Vec<u8>>>,
+    /// The contents of a sequential data set.
+    pub data: Vec<u8>,
+}
+
+impl Dataset {
+    pub fn sequential(data: Vec<u8>) -> Self {
+        Self {
+            recfm: "FB".to_owned(),
+            lrecl: 80,
+            members: None,
+            data,
+        }
+    }
+
+    pub fn partitioned() -> Self {
+        Self {
+            members: Some(BTreeMap::new()),
+            ..Self::sequential(Vec::new())
+        }
+    }
+
+    /// Returns `PO` for partitioned and `PS` for sequential data sets.
+    pub fn dsorg(&self) -> &'static str {
+        if self.members.is_some() {
+            "PO"
+        } else {
+            "PS"
+        }
+    }
+}
+
+/// A file or directory in the mock USS file system.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UssEntry {
+    Dir,
+    File(Vec<u8>),
+}
+
+/// A spool file of a job.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpoolFile {
+    pub ddname: String,
+    pub stepname: String,
+    pub records: String,
+}
+
+/// A job in the mock JES output queue.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Job {
+    pub jobid: String,
+    pub jobname: String,
+    pub owner: String,
+    #[serde(default = "default_class")]
+    pub class: String,
+    #[serde(default = "default_status")]
+    pub status: String,
+    #[serde(default)]
+    pub retcode: Option<String>,
+    #[serde(default)]
+    pub spool: Vec<SpoolFile>,
+}
+
+fn default_class() -> String {
+    "A".to_owned()
+}
+
+fn default_status() -> String {
+    "OUTPUT".to_owned()
+}
+
+/// The state shared by the mock servers.
+#[derive(Default)]
+pub struct MockState {
+    /// The fixture directory that changes are written back to, if the state is file-backed.
+    root: Option<PathBuf>,
+    pub datasets: BTreeMap<String, Dataset>,
+    pub uss: BTreeMap<String, UssEntry>,
+    pub jobs: BTreeMap<String, Job>,
+}
+
+impl MockState {
+    /// Returns a small sample state, used when no fixture directory is given.
+    pub fn sample() -> Self {
+        let mut state = Self::default();
+        state.datasets.insert(
+            "IBMUSER.DATA".to_owned(),
+            Dataset::sequential(b"Hello from the zedc mock z/OSMF server\n".to_vec()),
+        );
+        let mut cntl = Dataset::partitioned();
+        cntl.members.as_mut().unwrap().insert(
+            "IEFBR14".to_owned(),
+            b"//IEFBR14 JOB (ACCT),'ZEDC',CLASS=A,MSGCLASS=X\n//STEP1    EXEC PGM=IEFBR14\n"
+                .to_vec(),
+        );
+        state.datasets.insert("IBMUSER.CNTL".to_owned(), cntl);
+        for dir in ["/u", "/u/ibmuser"] {
+            state.uss.insert(dir.to_owned(), UssEntry::Dir);
+        }
+        state.uss.insert(
+            "/u/ibmuser/hello.txt".to_owned(),
+            UssEntry::File(b"Hello from USS\n".to_vec()),
+        );
+        let jcl = String::from_utf8_lossy(
+            &state.datasets["IBMUSER.CNTL"].members.as_ref().unwrap()["IEFBR14"],
+        )
+        .into_owned();
+        state.submit(&jcl, "IBMUSER").ok();
+        state
+    }
+
+    /// Loads the state from a fixture directory.
+    ///
+    /// # Arguments
+    /// * `dir` - The fixture directory
+    /// * `file_backed` - Whether changes should be written back to the directory
+    pub fn load(dir: &Path, file_backed: bool) -> anyhow::Result<Self> {
+        if !dir.is_dir() {
+            anyhow::bail!("Fixture directory {} does not exist", dir.display());
+        }
+        let mut state = Self::default();
+
+        let datasets = dir.join("datasets");
+        if datasets.is_dir() {
+            for entry in std::fs::read_dir(&datasets)? {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().to_uppercase();
+                if entry.file_type()?.is_dir() {
+                    let mut dataset = Dataset::partitioned();
+                    for member in std::fs::read_dir(entry.path())? {
+                        let member = member?;
+                        dataset.members.as_mut().unwrap().insert(
+                            member.file_name().to_string_lossy().to_uppercase(),
+                            std::fs::read(member.path())?,
+                        );
+                    }
+                    state.datasets.insert(name, dataset);
+                } else {
+                    state
+                        .datasets
+                        .insert(name, Dataset::sequential(std::fs::read(entry.path())?));
+                }
+            }
+        }
+
+        let uss = dir.join("uss");
+        if uss.is_dir() {
+            load_uss(&uss, "", &mut state.uss)?;
+        }
+
+        let jobs = dir.join("jobs");
+        if jobs.is_dir() {
+            for entry in std::fs::read_dir(&jobs)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|e| e == "json") {
+                    let job: Job = serde_json::from_str(&std::fs::read_to_string(&path)?)
+                        .with_context(|| format!("Invalid job fixture {}", path.display()))?;
+                    state.jobs.insert(job.jobid.clone(), job);
+                }
+            }
+        }
+
+        if file_backed {
+            state.root = Some(dir.to_owned());
+        }
+        Ok(state)
+    }
+
+    /// Writes a change back to the fixture directory if the state is file-backed.
+    fn persist(&self, relative: &str, contents: Option<&[u8]>) -> MockResult<()> {
+        let Some(root) = &self.root else {
+            return Ok(());
+        };
+        let path = root.join(check_relative(relative)?);
+        let result = match contents {
+            Some(data) => path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&path, data)),
+            None if path.is_dir() => std::fs::remove_dir_all(&path),
+            None if path.exists() => std::fs::remove_file(&path),
+            None => Ok(()),
+        };
+        result
+            .map_err(|e| MockError::Invalid(format!("Failed to update {}: {}", path.display(), e)))
+    }
+
+    /// Creates a directory in the fixture directory if the state is file-backed.
+    fn persist_dir(&self, relative: &str) -> MockResult<()> {
+        let Some(root) = &self.root else {
+            return Ok(());
+        };
+        let dir = root.join(check_relative(relative)?);
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| MockError::Invalid(format!("Failed to create {}: {}", dir.display(), e)))
+    }
+
+    /// Returns the data sets matching a `dslevel` pattern, e.g. `IBMUSER.*` or `IBMUSER.**`.
+    /// A pattern without wildcards matches the data set itself and all data sets below it.
+    pub fn list_datasets(&self, pattern: &str) -> Vec<(&String, &Dataset)> {
+        let pattern = pattern.trim().to_uppercase();
+        let patterns = if pattern.contains(['*', '%']) {
+            vec![pattern]
+        } else {
+            vec![pattern.clone(), format!("{}.**", pattern)]
+        };
+        self.datasets
+            .iter()
+            .filter(|(name, _)| patterns.iter().any(|p| matches_dslevel(p, name)))
+            .collect()
+    }
+
+    fn dataset(&self, dsn: &str) -> MockResult<&Dataset> {
+        self.datasets
+            .get(&dataset_name(dsn)?)
+            .ok_or_else(|| MockError::NotFound(format!("Data set {} not found", dsn)))
+    }
+
+    /// Returns the members of a partitioned data set.
+    pub fn members(&self, dsn: &str) -> MockResult<Vec<&String>> {
+        match &self.dataset(dsn)?.members {
+            Some(members) => Ok(members.keys().collect()),
+            None => Err(MockError::Invalid(format!(
+                "Data set {} is not partitioned",
+                dsn
+            ))),
+        }
+    }
+
+    /// Returns the contents of a sequential data set or a member.
+    pub fn read(&self, dsn: &str, member: Option<&str>) -> MockResult<&[u8]> {
+        let dataset = self.dataset(dsn)?;
+        match (&dataset.members, member) {
+            (None, None) => Ok(&dataset.data),
+            (Some(members), Some(member)) => members
+                .get(&member_name(member)?)
+                .map(Vec::as_slice)
+                .ok_or_else(|| {
+                    MockError::NotFound(format!("Member {}({}) not found", dsn, member))
+                }),
+            (Some(_), None) => Err(MockError::Invalid(format!(
+                "Data set {} is partitioned; specify a member",
+                dsn
+            ))),
+            (None, Some(_)) => Err(MockError::Invalid(format!(
+                "Data set {} is not partitioned",
+                dsn
+            ))),
+        }
+    }
+
+    /// Writes a sequential data set or a member, returning whether the member was created.
+    pub fn write(&mut self, dsn: &str, member: Option<&str>, data: Vec<u8>) -> MockResult<bool> {
+        let dsn = dataset_name(dsn)?;
+        let Some(dataset) = self.datasets.get_mut(&dsn) else {
+            return Err(MockError::NotFound(format!("Data set {} not found", dsn)));
+        };
+        let (created, relative) = match (&mut dataset.members, member) {
+            (None, None) => {
+                dataset.data = data.clone();
+                (false, format!("datasets/{}", dsn))
+            }
+            (Some(members), Some(member)) => {
+                let member = member_name(member)?;
+                let created = members.insert(member.clone(), data.clone()).is_none();
+                (created, format!("datasets/{}/{}", dsn, member))
+            }
+            (Some(_), None) => {
+                return Err(MockError::Invalid(format!(
+                    "Data set {} is partitioned; specify a member",
+                    dsn
+                )))
+            }
+            (None, Some(_)) => {
+                return Err(MockError::Invalid(format!(
+                    "Data set {} is not partitioned",
+                    dsn
+                )))
+            }
+        };
+        self.persist(&relative, Some(&data))?;
+        Ok(created)
+    }
+
+    /// Allocates a new, empty data set.
+    pub fn create_dataset(&mut self, dsn: &str, dataset: Dataset) -> MockResult<()> {
+        let dsn = dataset_name(dsn)?;
+        if self.datasets.contains_key(&dsn) {
+            return Err(MockError::Conflict(format!(
+                "Data set {} already exists",
+                dsn
+            )));
+        }
+        let relative = format!("datasets/{}", dsn);
+        match dataset.members {
+            Some(_) => self.persist_dir(&relative)?,
+            None => self.persist(&relative, Some(&dataset.data))?,
+        }
+        self.datasets.insert(dsn, dataset);
+        Ok(())
+    }
+
+    /// Deletes a data set or one of its members.
+    pub fn delete_dataset(&mut self, dsn: &str, member: Option<&str>) -> MockResult<()> {
+        let dsn = dataset_name(dsn)?;
+        match member {
+            None => {
+                self.datasets
+                    .remove(&dsn)
+                    .ok_or_else(|| MockError::NotFound(format!("Data set {} not found", dsn)))?;
+                self.persist(&format!("datasets/{}", dsn), None)
+            }
+            Some(member) => {
+                let member = member_name(member)?;
+                let removed = self
+                    .datasets
+                    .get_mut(&dsn)
+                    .and_then(|d| d.members.as_mut())
+                    .and_then(|m| m.remove(&member));
+                if removed.is_none() {
+                    return Err(MockError::NotFound(format!(
+                        "Member {}({}) not found",
+                        dsn, member
+                    )));
+                }
+                self.persist(&format!("datasets/{}/{}", dsn, member), None)
+            }
+        }
+    }
+
+    /// Returns the entry at a USS path. The root directory always exists.
+    pub fn uss_entry(&self, path: &str) -> MockResult<&UssEntry> {
+        let path = uss_path(path)?;
+        if path == "/" {
+            return Ok(&UssEntry::Dir);
+        }
+        self.uss
+            .get(&path)
+            .ok_or_else(|| MockError::NotFound(format!("{} not found", path)))
+    }
+
+    /// Returns the names and entries of a USS directory, sorted by name.
+    pub fn list_dir(&self, path: &str) -> MockResult<Vec<(&str, &UssEntry)>> {
+        let path = uss_path(path)?;
+        if self.uss_entry(&path)? != &UssEntry::Dir {
+            return Err(MockError::Invalid(format!("{} is not a directory", path)));
+        }
+        let prefix = if path == "/" {
+            "/".to_owned()
+        } else {
+            format!("{}/", path)
+        };
+        Ok(self
+            .uss
+            .iter()
+            .filter_map(|(p, entry)| {
+                let name = p.strip_prefix(&prefix)?;
+                (!name.is_empty() && !name.contains('/')).then_some((name, entry))
+            })
+            .collect())
+    }
+
+    /// Checks that the parent directory of a USS path exists.
+    fn check_parent(&self, path: &str) -> MockResult<()> {
+        let parent = match path.rsplit_once('/') {
+            Some(("", _)) | None => "/",
+            Some((parent, _)) => parent,
+        };
+        match self.uss_entry(parent) {
+            Ok(UssEntry::Dir) => Ok(()),
+            _ => Err(MockError::NotFound(format!(
+                "Directory {} not found",
+                parent
+            ))),
+        }
+    }
+
+    /// Writes a USS file, returning whether it was created.
+    pub fn write_file(&mut self, path: &str, data: Vec<u8>) -> MockResult<bool> {
+        let path = uss_path(path)?;
+        self.check_parent(&path)?;
+        if self.uss.get(&path) == Some(&UssEntry::Dir) {
+            return Err(MockError::Invalid(format!("{} is a directory", path)));
+        }
+        self.persist(&format!("uss{}", path), Some(&data))?;
+        Ok(self.uss.insert(path, UssEntry::File(data)).is_none())
+    }
+
+    /// Creates a USS directory.
+    pub fn mkdir(&mut self, path: &str) -> MockResult<()> {
+        let path = uss_path(path)?;
+        self.check_parent(&path)?;
+        if path == "/" || self.uss.contains_key(&path) {
+            return Err(MockError::Conflict(format!("{} already exists", path)));
+        }
+        self.persist_dir(&format!("uss{}", path))?;
+        self.uss.insert(path, UssEntry::Dir);
+        Ok(())
+    }
+
+    /// Deletes a USS file, or a directory and everything below it.
+    pub fn delete_path(&mut self, path: &str) -> MockResult<()> {
+        let path = uss_path(path)?;
+        self.uss_entry(&path)?;
+        if path == "/" {
+            return Err(MockError::Invalid(
+                "Cannot delete the root directory".to_owned(),
+            ));
+        }
+        let prefix = format!("{}/", path);
+        self.uss
+            .retain(|p, _| p != &path && !p.starts_with(&prefix));
+        self.persist(&format!("uss{}", path), None)
+    }
+
+    /// Returns the jobs matching an owner and job name prefix (`*` matches anything).
+    pub fn list_jobs(&self, owner: &str, prefix: &str) -> Vec<&Job> {
+        self.jobs
+            .values()
+            .filter(|j| matches_wildcard(&owner.to_uppercase(), &j.owner))
+            .filter(|j| matches_wildcard(&prefix.to_uppercase(), &j.jobname))
+            .collect()
+    }
+
+    /// Returns a job by its ID.
+    pub fn job(&self, jobid: &str) -> MockResult<&Job> {
+        self.jobs
+            .get(&jobid.to_uppercase())
+            .ok_or_else(|| MockError::NotFound(format!("Job {} not found", jobid)))
+    }
+
+    /// "Runs" a job: the job completes immediately with `CC 0000` and a generated job log.
+    ///
+    /// # Arguments
+    /// * `jcl` - The JCL of the job; the job name is taken from the `JOB` card
+    /// * `owner` - The user that submitted the job
+    pub fn submit(&mut self, jcl: &str, owner: &str) -> MockResult<&Job> {
+        let jobname = jcl
+            .lines()
+            .find_map(|line| {
+                let mut words = line.strip_prefix("//")?.split_whitespace();
+                let name = words.next()?;
+                (words.next()? == "JOB").then(|| name.to_uppercase())
+            })
+            .ok_or_else(|| MockError::Invalid("The JCL does not contain a JOB card".to_owned()))?;
+        let number = self
+            .jobs
+            .keys()
+            .filter_map(|id| id.strip_prefix("JOB")?.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let jobid = format!("JOB{:05}", number);
+        let steps: Vec<&str> = jcl
+            .lines()
+            .filter_map(|line| {
+                let mut words = line.strip_prefix("//")?.split_whitespace();
+                let name = words.next()?;
+                words.next()?.eq("EXEC").then_some(name)
+            })
+            .collect();
+
+        let mut log = format!(
+            " $HASP373 {:<8} STARTED - INIT 1    - CLASS A        - SYS MOCK\n",
+            jobname
+        );
+        for step in &steps {
+            log.push_str(&format!(" -{:<8} {:<8} RC 0000\n", jobname, step));
+        }
+        log.push_str(&format!(" $HASP395 {:<8} ENDED - RC=0000\n", jobname));
+        let numbered: String = jcl
+            .lines()
+            .enumerate()
+            .map(|(i, line)| format!("{:>10} {}\n", i + 1, line))
+            .collect();
+        let job = Job {
+            jobid: jobid.clone(),
+            jobname: jobname.clone(),
+            owner: owner.to_uppercase(),
+            class: default_class(),
+            status: default_status(),
+            retcode: Some("CC 0000".to_owned()),
+            spool: vec![
+                SpoolFile {
+                    ddname: "JESMSGLG".to_owned(),
+                    stepname: "JES2".to_owned(),
+                    records: log,
+                },
+                SpoolFile {
+                    ddname: "JESJCL".to_owned(),
+                    stepname: "JES2".to_owned(),
+                    records: numbered,
+                },
+                SpoolFile {
+                    ddname: "JESYSMSG".to_owned(),
+                    stepname: "JES2".to_owned(),
+                    records: steps
+                        .iter()
+                        .map(|s| {
+                            format!(
+                                "IEF142I {} {} - STEP WAS EXECUTED - COND CODE 0000\n",
+                                jobname, s
+                            )
+                        })
+                        .collect(),
+                },
+            ],
+        };
+        if self.root.is_some() {
+            let json =
+                serde_json::to_vec_pretty(&job).map_err(|e| MockError::Invalid(e.to_string()))?;
+            self.persist(&format!("jobs/{}.json", jobid), Some(&json))?;
+        }
+        self.jobs.insert(jobid.clone(), job);
+        Ok(&self.jobs[&jobid])
+    }
+
+    /// Deletes a job and its output.
+    pub fn delete_job(&mut self, jobid: &str) -> MockResult<Job> {
+        let job = self
+            .jobs
+            .remove(&jobid.to_uppercase())
+            .ok_or_else(|| MockError::NotFound(format!("Job {} not found", jobid)))?;
+        self.persist(&format!("jobs/{}.json", job.jobid), None)?;
+        Ok(job)
+    }
+}
+
+/// Recursively loads the files and directories below `dir` into the USS tree.
+fn load_uss(dir: &Path, prefix: &str, uss: &mut BTreeMap<String, UssEntry>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if entry.file_type()?.is_dir() {
+            uss.insert(path.clone(), UssEntry::Dir);
+            load_uss(&entry.path(), &path, uss)?;
+        } else {
+            uss.insert(path, UssEntry::File(std::fs::read(entry.path())?));
+        }
+    }
+    Ok(())
+}
+
+/// Normalizes a USS path from a client, rejecting `..` so that file-backed state cannot reach
+/// outside the fixture directory.
+fn uss_path(path: &str) -> MockResult<String> {
+    if path.split('/').any(|p| p == "..") {
+        return Err(MockError::Invalid(format!(
+            "{}: '..' is not supported in paths",
+            path
+        )));
+    }
+    Ok(normalize_path(path))
+}
+
+/// Checks a path below the fixture directory, as a second line of defense for [`MockState::persist`].
+fn check_relative(relative: &str) -> MockResult<&Path> {
+    let path = Path::new(relative.trim_start_matches('/'));
+    if path
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)))
+    {
+        Ok(path)
+    } else {
+        Err(MockError::Invalid(format!("Invalid path {}", relative)))
+    }
+}
+
+/// Returns whether a data set qualifier or member name follows the z/OS naming rules: 1 to 8
+/// characters, starting with a letter or `#`, `@`, `$`, followed by letters, digits or `#@$` (and
+/// `-` in qualifiers).
+fn is_valid_name(name: &str, allow_hyphen: bool) -> bool {
+    let national = |c: char| matches!(c, '#' | '@' | '$');
+    let mut chars = name.chars();
+    (1..=8).contains(&name.len())
+        && chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || national(c))
+        && chars.all(|c| c.is_ascii_alphanumeric() || national(c) || (allow_hyphen && c == '-'))
+}
+
+/// Validates a data set name from a client and returns it in upper case.
+fn dataset_name(dsn: &str) -> MockResult<String> {
+    let dsn = dsn.trim().to_uppercase();
+    if dsn.len() > 44 || !dsn.split('.').all(|q| is_valid_name(q, true)) {
+        return Err(MockError::Invalid(format!("Invalid data set name {}", dsn)));
+    }
+    Ok(dsn)
+}
+
+/// Validates a member name from a client and returns it in upper case.
+fn member_name(member: &str) -> MockResult<String> {
+    let member = member.trim().to_uppercase();
+    if !is_valid_name(&member, false) {
+        return Err(MockError::Invalid(format!(
+            "Invalid member name {}",
+            member
+        )));
+    }
+    Ok(member)
+}
+
+/// Normalizes a USS path to an absolute path without a trailing slash.
+pub fn normalize_path(path: &str) -> String {
+    let parts: Vec<&str> = path
+        .split('/')
+        .filter(|p| !p.is_empty() && *p != ".")
+        .collect();
+    format!("/{}", parts.join("/"))
+}
+
+/// Matches a data set name against a `dslevel` pattern: `**` matches any number of qualifiers,
+/// `*` any characters within one qualifier and `%` a single character.
+pub fn matches_dslevel(pattern: &str, name: &str) -> bool {
+    fn qualifiers(pattern: &[&str], name: &[&str]) -> bool {
+        match (pattern.first(), name.first()) {
+            (None, None) => true,
+            (Some(&"**"), _) => {
+                qualifiers(&pattern[1..], name)
+                    || (!name.is_empty() && qualifiers(pattern, &name[1..]))
+            }
+            (Some(p), Some(n)) => matches_wildcard(p, n) && qualifiers(&pattern[1..], &name[1..]),
+            _ => false,
+        }
+    }
+    let pattern: Vec<&str> = pattern.split('.').collect();
+    let name: Vec<&str> = name.split('.').collect();
+    qualifiers(&pattern, &name)
+}
+
+/// Matches a name against a pattern where `*` matches any characters and `%` a single character.
+pub fn matches_wildcard(pattern: &str, name: &str) -> bool {
+    fn chars(pattern: &[char], name: &[char]) -> bool {
+        match (pattern.first(), name.first()) {
+            (None, None) => true,
+            (Some('*'), _) => {
+                chars(&pattern[1..], name) || (!name.is_empty() && chars(pattern, &name[1..]))
+            }
+            (Some('%'), Some(_)) => chars(&pattern[1..], &name[1..]),
+            (Some(p), Some(n)) => p.eq_ignore_ascii_case(n) && chars(&pattern[1..], &name[1..]),
+            _ => false,
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    chars(&pattern, &name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_dataset_patterns() {
+        assert!(matches_dslevel("IBMUSER.*", "IBMUSER.CNTL"));
+        assert!(!matches_dslevel("IBMUSER.*", "IBMUSER.TEST.CNTL"));
+        assert!(matches_dslevel("IBMUSER.**", "IBMUSER.TEST.CNTL"));
+        assert!(matches_dslevel("IBMUSER.C%TL", "IBMUSER.CNTL"));
+        assert!(matches_dslevel("**.CNTL", "IBMUSER.TEST.CNTL"));
+
+        let state = MockState::sample();
+        let names: Vec<_> = state
+            .list_datasets("ibmuser")
+            .into_iter()
+            .map(|(n, _)| n.as_str())
+            .collect();
+        assert_eq!(names, ["IBMUSER.CNTL", "IBMUSER.DATA"]);
+    }
+
+    #[test]
+    fn reads_and_writes_fixtures() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("datasets/IBMUSER.CNTL")).unwrap();
+        std::fs::write(dir.path().join("datasets/IBMUSER.CNTL/JCL"), "//A JOB\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("uss/u/ibmuser")).unwrap();
+
+        let mut state = MockState::load(dir.path(), true).unwrap();
+        assert_eq!(
+            state.read("ibmuser.cntl", Some("jcl")).unwrap(),
+            b"//A JOB\n"
+        );
+        assert!(state
+            .write("IBMUSER.CNTL", Some("NEW"), b"data".to_vec())
+            .unwrap());
+        assert_eq!(
+            std::fs::read(dir.path().join("datasets/IBMUSER.CNTL/NEW")).unwrap(),
+            b"data"
+        );
+        assert_eq!(
+            state.write("IBMUSER.NOPE", None, Vec::new()),
+            Err(MockError::NotFound(
+                "Data set IBMUSER.NOPE not found".to_owned()
+            ))
+        );
+
+        assert!(state.write_file("/u/ibmuser/a.txt", b"a".to_vec()).unwrap());
+        assert!(state.write_file("/u/nobody/a.txt", b"a".to_vec()).is_err());
+        let names: Vec<_> = state
+            .list_dir("/u/ibmuser")
+            .unwrap()
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(names, ["a.txt"]);
+        state.delete_path("/u/ibmuser").unwrap();
+        assert!(!dir.path().join("uss/u/ibmuser").exists());
+    }
+
+    #[test]
+    fn rejects_paths_outside_fixtures() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixtures = dir.path().join("fixtures");
+        std::fs::create_dir_all(fixtures.join("uss/u")).unwrap();
+        std::fs::write(dir.path().join("outside.txt"), "keep").unwrap();
+        let mut state = MockState::load(&fixtures, true).unwrap();
+
+        assert!(matches!(state.mkdir("/.."), Err(MockError::Invalid(_))));
+        assert!(matches!(
+            state.delete_path("/.."),
+            Err(MockError::Invalid(_))
+        ));
+        assert!(state.delete_path("/u/../..").is_err());
+        assert!(state.write_file("/../../x", b"x".to_vec()).is_err());
+        assert!(state
+            .create_dataset("../../x", Dataset::sequential(b"x".to_vec()))
+            .is_err());
+        assert!(state.delete_dataset("../../outside.txt", None).is_err());
+        assert!(state.delete_dataset("IBMUSER.CNTL", Some("../X")).is_err());
+        assert!(state
+            .create_dataset("IBMUSER.TOOLONGQUAL", Dataset::partitioned())
+            .is_err());
+        assert!(state.check_parent("/u").is_ok());
+
+        assert!(fixtures.join("uss/u").is_dir());
+        assert!(dir.path().join("outside.txt").is_file());
+        assert!(!dir.path().join("x").exists());
+        state
+            .create_dataset("ibmuser.new#1", Dataset::partitioned())
+            .unwrap();
+        assert!(fixtures.join("datasets/IBMUSER.NEW#1").is_dir());
+    }
+
+    #[test]
+    fn submits_jobs() {
+        let mut state = MockState::default();
+        let job = state
+            .submit(
+                "//TESTJOB  JOB (ACCT)\n//STEP1    EXEC PGM=IEFBR14\n",
+                "ibmuser",
+            )
+            .unwrap();
+        assert_eq!(job.jobid, "JOB00001");
+        assert_eq!(job.jobname, "TESTJOB");
+        assert_eq!(job.owner, "IBMUSER");
+        assert!(job.spool[0].records.contains("$HASP395 TESTJOB  ENDED"));
+        assert_eq!(state.list_jobs("*", "TEST*").len(), 1);
+        assert!(state.list_jobs("OTHER", "*").is_empty());
+        assert!(state.submit("no job card", "ibmuser").is_err());
+    }
+}
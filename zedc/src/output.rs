@@ -111,6 +111,10 @@ pub mod exit {
     pub const TESTS_FAILED: i32 = 3;
     /// Patch coverage fell below the requested `--threshold`.
     pub const COVERAGE_BELOW_THRESHOLD: i32 = 4;
+    /// One or more `.vsix` files exceeded their size budget in `vsix budget`.
+    pub const BUDGET_EXCEEDED: i32 = 5;
+    /// Zowe Explorer failed to activate in a sandbox session followed with `--wait`.
+    pub const ACTIVATION_FAILED: i32 = 6;
 }
 
 #[cfg(test)]
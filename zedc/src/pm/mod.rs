@@ -1,9 +1,6 @@
 //! "Root" module containing all related logic for the `pkg-manager` command.
 
-use std::{
-    path::{Path, PathBuf},
-    process::Command,
-};
+use std::{path::Path, process::Command};
 
 mod cmd;
 pub use cmd::handle_cmd;
@@ -40,6 +37,6 @@ pub fn pkg_mgr(name: &str) -> Command {
     crate::cmd::as_binary(name)
 }
 
-pub fn check_dependencies(ze_dir: &PathBuf) -> bool {
+pub fn check_dependencies(ze_dir: &Path) -> bool {
     ze_dir.join("node_modules").exists()
 }
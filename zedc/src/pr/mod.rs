@@ -1,5 +1,7 @@
 //! Module for the `zedc pr` command — fetch, check out, build, and sandbox a PR in one step.
 
+use crate::cmd::{SandboxConfig, VsCodeConfig};
+use crate::test::InstallReport;
 use anyhow::{bail, Context, Result};
 use octocrab::{models::ArtifactId, params::actions::ArchiveFormat, Octocrab};
 use owo_colors::OwoColorize;
@@ -174,6 +176,23 @@ async fn install_deps(ze_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Runs `<pm> package` in a folder of the repository, using the package manager detected for the
+/// repository root. Returns the name of the package manager.
+pub(crate) fn run_package(ze_dir: &Path, dir: &Path) -> Result<String> {
+    let pkg_mgr = crate::pm::detect_pkg_mgr(ze_dir)?;
+    let mut package = crate::pm::pkg_mgr(&pkg_mgr);
+    package.arg("package").current_dir(dir);
+    silence_if_json(&mut package);
+    let status = package
+        .status()
+        .with_context(|| format!("Failed to run `{} package`", pkg_mgr))?;
+
+    if !status.success() {
+        bail!("`{} package` exited with a non-zero status", pkg_mgr);
+    }
+    Ok(pkg_mgr)
+}
+
 /// Runs `pnpm package` (or the detected PM equivalent) and returns paths to the produced VSIXes.
 ///
 /// VSIXes are written to `dist/` at the repo root by each package's `mv-pack.js` post-step.
@@ -185,7 +204,7 @@ fn build_vsix(ze_dir: &Path) -> Result<Vec<String>> {
     if dist_dir.exists() {
         for entry in std::fs::read_dir(&dist_dir)? {
             let entry = entry?;
-            if entry.path().extension().map_or(false, |e| e == "vsix") {
+            if entry.path().extension().is_some_and(|e| e == "vsix") {
                 let _ = std::fs::remove_file(entry.path());
             }
         }
@@ -193,23 +212,13 @@ fn build_vsix(ze_dir: &Path) -> Result<Vec<String>> {
         std::fs::create_dir_all(&dist_dir)?;
     }
 
-    let pkg_mgr = crate::pm::detect_pkg_mgr(ze_dir)?;
-    let mut package = crate::pm::pkg_mgr(&pkg_mgr);
-    package.arg("package").current_dir(ze_dir);
-    silence_if_json(&mut package);
-    let status = package
-        .status()
-        .with_context(|| format!("Failed to run `{} package`", pkg_mgr))?;
-
-    if !status.success() {
-        bail!("`{} package` exited with a non-zero status", pkg_mgr);
-    }
+    let pkg_mgr = run_package(ze_dir, ze_dir)?;
 
     let mut vsix_paths: Vec<String> = Vec::new();
     for entry in std::fs::read_dir(&dist_dir)? {
         let entry = entry?;
         let path = entry.path();
-        if path.extension().map_or(false, |e| e == "vsix") {
+        if path.extension().is_some_and(|e| e == "vsix") {
             text_println!(
                 "  📦 {}",
                 path.file_name().unwrap().to_string_lossy().bold()
@@ -310,7 +319,7 @@ async fn download_artifact_vsixes(gh: &Octocrab, artifact_id: u64) -> Result<Vec
     let mut vsix_paths = Vec::new();
     for entry in std::fs::read_dir(&vsix_dir)? {
         let path = entry?.path();
-        if path.extension().map_or(false, |e| e == "vsix") {
+        if path.extension().is_some_and(|e| e == "vsix") {
             text_println!(
                 "  📦 {}",
                 path.file_name().unwrap().to_string_lossy().bold()
@@ -427,13 +436,15 @@ async fn build_from_source(
     build_vsix(ze_dir)
 }
 
-/// Handles the `zedc pr <NUMBER>` command.
+/// Handles the `zedc pr <NUMBER>` command. Returns the copy of VS Code the sandbox was prepared with
+/// and the install result for each extension.
 pub async fn handle_cmd(
     pr_number: u64,
-    vsc_version: Option<String>,
+    vscode: &VsCodeConfig,
     skip_setup: bool,
     build: bool,
-) -> Result<()> {
+    sandbox: &SandboxConfig,
+) -> Result<InstallReport> {
     if crate::output::text_enabled() {
         println!("{}\n", format!("zedc pr #{}", pr_number).bold());
     }
@@ -455,10 +466,9 @@ pub async fn handle_cmd(
         }
     };
 
-    let vsc_bin = crate::code::download_vscode(vsc_version).await?;
-    crate::test::install_from_paths(vsc_bin, vsix_paths).await?;
-
-    Ok(())
+    let vscode = crate::test::resolve_version(vscode, &vsix_paths)?;
+    let vsc = crate::code::download_vscode(&vscode).await?;
+    crate::test::install_from_paths(vsc, vsix_paths, sandbox).await
 }
 
 #[cfg(test)]
@@ -0,0 +1,385 @@
+//! Installing Zowe CLI and its plugins into a sandbox (`--install-cli`, `--cli-plugin`).
+//!
+//! Zowe CLI is installed with `npm install -g --prefix` into the `cli/` folder of the sandbox, whose
+//! `bin` folder is put on the `PATH` of VS Code (see [`Sandbox::code_command`]). Plugins are installed
+//! with `zowe plugins install` into the sandbox `.zowe` folder. An install is skipped when the
+//! requested version is already present.
+
+use super::Sandbox;
+use anyhow::{bail, Context};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// The npm package of Zowe CLI.
+pub const CLI_PACKAGE: &str = "@zowe/cli";
+
+/// A package installed by [`install`].
+#[derive(Serialize, Debug)]
+pub struct CliPackage {
+    pub name: String,
+    pub version: String,
+    /// Whether the requested version was already installed.
+    pub cached: bool,
+}
+
+/// Zowe CLI and the plugins installed into a sandbox.
+#[derive(Serialize, Debug)]
+pub struct CliInstall {
+    pub cli: CliPackage,
+    /// The folder with the `zowe` binary that is put on the `PATH` of the sandbox.
+    pub bin_dir: String,
+    pub plugins: Vec<CliPackage>,
+}
+
+/// Returns the folder with the binaries of an npm global prefix.
+pub fn bin_dir(prefix: &Path) -> PathBuf {
+    if cfg!(windows) {
+        prefix.to_owned()
+    } else {
+        prefix.join("bin")
+    }
+}
+
+/// Returns the `node_modules` folder of an npm global prefix.
+fn modules_dir(prefix: &Path) -> PathBuf {
+    if cfg!(windows) {
+        prefix.join("node_modules")
+    } else {
+        prefix.join("lib").join("node_modules")
+    }
+}
+
+/// Returns the version of a package installed in an npm global prefix.
+fn installed_version(prefix: &Path, package: &str) -> Option<String> {
+    let path = modules_dir(prefix).join(package).join("package.json");
+    let manifest: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    manifest["version"].as_str().map(str::to_owned)
+}
+
+/// Splits `name@version` into the package name and version; scoped names such as
+/// `@zowe/zos-ftp-for-zowe-cli@3.0.0` keep their leading `@`.
+fn split_spec(spec: &str) -> anyhow::Result<(&str, Option<&str>)> {
+    let offset = if spec.starts_with('@') { 1 } else { 0 };
+    let (name, version) = match spec[offset..].rfind('@') {
+        Some(i) => (
+            &spec[..offset + i],
+            Some(&spec[offset + i + 1..]).filter(|v| !v.is_empty()),
+        ),
+        None => (spec, None),
+    };
+    if !is_package_name(name) {
+        bail!(
+            "Invalid Zowe CLI plugin '{}': expected an npm package name, optionally followed by @<version>",
+            spec
+        );
+    }
+    Ok((name, version))
+}
+
+/// Returns whether a name is a valid npm package name (`name` or `@scope/name`).
+fn is_package_name(name: &str) -> bool {
+    let is_valid = |part: &str| {
+        !part.is_empty()
+            && !part.starts_with(['.', '_'])
+            && part
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"-._~".contains(&b))
+    };
+    match name.strip_prefix('@') {
+        Some(scoped) => scoped
+            .split_once('/')
+            .is_some_and(|(scope, name)| is_valid(scope) && is_valid(name)),
+        None => is_valid(name),
+    }
+}
+
+/// Resolves a version, dist-tag or range of a package to the highest matching version using
+/// `npm view`. Returns `None` when npm cannot be reached.
+fn resolve_version(package: &str, version: &str) -> Option<String> {
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    let is_exact =
+        core.split('.').count() == 3 && core.split('.').all(|p| p.parse::<u64>().is_ok());
+    if is_exact {
+        return Some(version.to_owned());
+    }
+    let output = crate::pm::npm()
+        .args([
+            "view",
+            &format!("{}@{}", package, version),
+            "version",
+            "--json",
+        ])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    match serde_json::from_slice(&output.stdout).ok()? {
+        serde_json::Value::String(v) => Some(v),
+        serde_json::Value::Array(versions) => versions
+            .iter()
+            .filter_map(|v| v.as_str())
+            .max_by(|a, b| crate::code::compare_versions(a, b))
+            .map(str::to_owned),
+        _ => None,
+    }
+}
+
+/// Runs an install command, showing its output in text mode.
+fn run(mut command: Command, what: &str) -> anyhow::Result<()> {
+    if !crate::output::text_enabled() {
+        command.stdout(Stdio::null()).stderr(Stdio::null());
+    }
+    let status = command
+        .stdin(Stdio::null())
+        .status()
+        .with_context(|| format!("Could not install {}", what))?;
+    if !status.success() {
+        bail!("Could not install {}", what);
+    }
+    Ok(())
+}
+
+/// Returns a command that runs the `zowe` binary of a sandbox with its `.zowe` folder and `PATH`.
+fn zowe_command(sandbox: &Sandbox) -> Command {
+    let mut command =
+        crate::cmd::as_binary(&bin_dir(&sandbox.cli_dir()).join("zowe").to_string_lossy());
+    command.env("ZOWE_CLI_HOME", sandbox.zowe_dir());
+    if let Some(path) = sandbox.path_env() {
+        command.env("PATH", path);
+    }
+    command
+}
+
+/// Installs Zowe CLI into a sandbox unless the requested version is already installed, then checks
+/// the version reported by `zowe --version`.
+fn install_cli(sandbox: &Sandbox, version: &str) -> anyhow::Result<CliPackage> {
+    let text = crate::output::text_enabled();
+    let prefix = sandbox.cli_dir();
+    let wanted = resolve_version(CLI_PACKAGE, version);
+    let cached = match (installed_version(&prefix, CLI_PACKAGE), &wanted) {
+        (Some(installed), Some(wanted)) => installed == *wanted,
+        (Some(installed), None) => {
+            if text {
+                println!(
+                    "  ⚠️  {}",
+                    format!(
+                        "Could not resolve Zowe CLI {} from npm, using installed version {}",
+                        version, installed
+                    )
+                    .italic()
+                );
+            }
+            true
+        }
+        (None, _) => false,
+    };
+
+    if cached {
+        if text {
+            println!(
+                "  ⏭️  {}",
+                "Zowe CLI is already installed in the sandbox, skipping install...".italic()
+            );
+        }
+    } else {
+        if text {
+            println!(
+                "💿 {}",
+                format!("Installing Zowe CLI (version: {})...", version).blue()
+            );
+        }
+        std::fs::create_dir_all(&prefix)?;
+        let mut npm = crate::pm::npm();
+        npm.args(["install", "-g", "--no-save", "--prefix"])
+            .arg(&prefix)
+            .arg(format!(
+                "{}@{}",
+                CLI_PACKAGE,
+                wanted.as_deref().unwrap_or(version)
+            ));
+        run(npm, "Zowe CLI")?;
+    }
+
+    let Some(installed) = installed_version(&prefix, CLI_PACKAGE) else {
+        bail!("Zowe CLI was not installed into {}", prefix.display());
+    };
+    if let Some(wanted) = wanted.filter(|w| *w != installed) {
+        bail!(
+            "Installed Zowe CLI {}, but {} was requested",
+            installed,
+            wanted
+        );
+    }
+    let output = zowe_command(sandbox)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .context("Could not run the installed Zowe CLI")?;
+    let reported = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    if !output.status.success() || reported != installed {
+        bail!(
+            "`zowe --version` reported '{}' instead of the installed Zowe CLI {}",
+            reported,
+            installed
+        );
+    }
+    Ok(CliPackage {
+        name: CLI_PACKAGE.to_owned(),
+        version: installed,
+        cached,
+    })
+}
+
+/// Returns the version of a Zowe CLI plugin installed in the sandbox `.zowe` folder.
+fn plugin_version(sandbox: &Sandbox, name: &str) -> Option<String> {
+    installed_version(&sandbox.zowe_dir().join("plugins").join("installed"), name)
+}
+
+/// Installs a Zowe CLI plugin (`name` or `name@version`) into a sandbox unless the requested
+/// version is already installed.
+fn install_plugin(sandbox: &Sandbox, spec: &str) -> anyhow::Result<CliPackage> {
+    let text = crate::output::text_enabled();
+    let (name, version) = split_spec(spec)?;
+    let wanted = resolve_version(name, version.unwrap_or("latest"));
+    let installed = plugin_version(sandbox, name);
+    let cached = installed.is_some() && (wanted.is_none() || installed == wanted);
+    if cached {
+        if text {
+            println!(
+                "  ⏭️  {}",
+                format!("Plugin {} is already installed, skipping install...", name).italic()
+            );
+        }
+    } else {
+        if text {
+            println!("💿 {}", format!("Installing plugin {}...", spec).blue());
+        }
+        let mut zowe = zowe_command(sandbox);
+        zowe.args(["plugins", "install"]).arg(match &wanted {
+            Some(v) => format!("{}@{}", name, v),
+            None => spec.to_owned(),
+        });
+        run(zowe, &format!("Zowe CLI plugin {}", spec))?;
+    }
+
+    let Some(installed) = plugin_version(sandbox, name) else {
+        bail!("Zowe CLI plugin {} was not installed", name);
+    };
+    if let Some(wanted) = wanted.filter(|w| *w != installed) {
+        bail!(
+            "Installed plugin {} {}, but {} was requested",
+            name,
+            installed,
+            wanted
+        );
+    }
+    Ok(CliPackage {
+        name: name.to_owned(),
+        version: installed,
+        cached,
+    })
+}
+
+/// Installs Zowe CLI and the given plugins into a sandbox, skipping the packages whose requested
+/// version is already installed.
+///
+/// # Arguments
+/// * `sandbox` - The sandbox to install Zowe CLI into
+/// * `version` - The version, dist-tag or range of Zowe CLI to install from `npm`
+/// * `plugins` - The plugins to install (`name` or `name@version`)
+pub fn install(sandbox: &Sandbox, version: &str, plugins: &[String]) -> anyhow::Result<CliInstall> {
+    let text = crate::output::text_enabled();
+    for spec in plugins {
+        split_spec(spec)?;
+    }
+    sandbox.prepare()?;
+    let cli = install_cli(sandbox, version)?;
+    let plugins = plugins
+        .iter()
+        .map(|spec| install_plugin(sandbox, spec))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if text {
+        println!("✔️  Installed Zowe CLI {}", cli.version);
+        for plugin in &plugins {
+            println!("  🔌 {}@{}", plugin.name.bold(), plugin.version);
+        }
+    }
+    Ok(CliInstall {
+        cli,
+        bin_dir: bin_dir(&sandbox.cli_dir()).to_string_lossy().into_owned(),
+        plugins,
+    })
+}
+
+/// Returns the version of Zowe CLI installed in a sandbox.
+pub fn version(sandbox: &Sandbox) -> Option<String> {
+    installed_version(&sandbox.cli_dir(), CLI_PACKAGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_installed_versions() {
+        assert_eq!(
+            split_spec("@zowe/cli@8.1.0").unwrap(),
+            ("@zowe/cli", Some("8.1.0"))
+        );
+        assert_eq!(
+            split_spec("@zowe/zos-ftp-for-zowe-cli").unwrap(),
+            ("@zowe/zos-ftp-for-zowe-cli", None)
+        );
+        assert_eq!(
+            split_spec("some-plugin@latest").unwrap(),
+            ("some-plugin", Some("latest"))
+        );
+        assert_eq!(split_spec("some-plugin@").unwrap(), ("some-plugin", None));
+        for spec in [
+            "",
+            "@",
+            "@zowe",
+            "@zowe/",
+            "é-plugin",
+            "€@1.0.0",
+            "Plugin",
+            "../evil",
+        ] {
+            assert!(split_spec(spec).is_err(), "{}", spec);
+        }
+        assert_eq!(
+            resolve_version(CLI_PACKAGE, "8.1.0").as_deref(),
+            Some("8.1.0")
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let sandbox = Sandbox {
+            name: "cli".to_owned(),
+            root: dir.path().join("cli"),
+        };
+        assert_eq!(version(&sandbox), None);
+        assert!(sandbox.path_env().is_none());
+        let package = modules_dir(&sandbox.cli_dir()).join(CLI_PACKAGE);
+        std::fs::create_dir_all(&package).unwrap();
+        std::fs::write(package.join("package.json"), r#"{ "version": "8.1.0" }"#).unwrap();
+        std::fs::create_dir_all(bin_dir(&sandbox.cli_dir())).unwrap();
+        assert_eq!(version(&sandbox).as_deref(), Some("8.1.0"));
+
+        // The sandbox bin folder comes first on the PATH of VS Code, including for `open` on macOS
+        let path = sandbox.path_env().unwrap();
+        let first = std::env::split_paths(&path).next().unwrap();
+        assert_eq!(first, bin_dir(&sandbox.cli_dir()));
+        assert!(sandbox
+            .env()
+            .iter()
+            .any(|(key, value)| *key == "PATH" && *value == path));
+    }
+}
@@ -0,0 +1,392 @@
+//! Command module for handling `sandbox` commands.
+
+use super::logs::SandboxLogs;
+use super::snapshot;
+use super::{Sandbox, SandboxInfo, DEFAULT_SANDBOX};
+use crate::output::{self, exit};
+use crate::util::{format_age, format_bytes};
+use anyhow::{bail, Context};
+use owo_colors::OwoColorize;
+use std::path::{Path, PathBuf};
+
+#[derive(clap::Subcommand)]
+pub enum Commands {
+    /// List all sandboxes with their VS Code version, disk usage and last launch time
+    #[command(alias = "ls")]
+    List,
+    /// Show the installed extensions, disk usage and Zowe config of a sandbox
+    Info {
+        /// Name of the sandbox
+        #[arg(default_value = DEFAULT_SANDBOX)]
+        name: String,
+    },
+    /// Reset a sandbox's user data and workspace without re-downloading VS Code
+    Reset {
+        /// Name of the sandbox
+        #[arg(default_value = DEFAULT_SANDBOX)]
+        name: String,
+        /// Keep the extensions installed in the sandbox
+        #[arg(long)]
+        keep_extensions: bool,
+    },
+    /// Delete a sandbox and all of its data
+    #[command(alias = "rm")]
+    Delete {
+        /// Name of the sandbox
+        name: String,
+    },
+    /// Launch a sandbox again using the VS Code version it was last prepared with
+    Open {
+        /// Name of the sandbox
+        #[arg(default_value = DEFAULT_SANDBOX)]
+        name: String,
+        /// Stay attached until the VS Code window is closed, reporting Zowe Explorer activation errors
+        #[arg(long)]
+        wait: bool,
+    },
+    /// Save the user data, workspace and `.zowe` folder of a sandbox to a zip file
+    Snapshot {
+        /// Name of the sandbox
+        #[arg(default_value = DEFAULT_SANDBOX)]
+        name: String,
+        /// The zip file to create (default: `<name>-snapshot.zip`)
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Also include the installed extensions
+        #[arg(long)]
+        include_extensions: bool,
+    },
+    /// Replace the user data, workspace and `.zowe` folder of a sandbox with a snapshot
+    Restore {
+        /// Name of the sandbox to restore into (created if it does not exist)
+        name: String,
+        /// The snapshot zip file created by `sandbox snapshot`
+        file: PathBuf,
+    },
+    /// Collect the VS Code, Zowe Explorer and Imperative logs of a sandbox
+    Logs {
+        /// Name of the sandbox
+        #[arg(default_value = DEFAULT_SANDBOX)]
+        name: String,
+        /// Write the logs and sandbox metadata to a zip file to attach to an issue
+        #[arg(long, value_name = "FILE")]
+        bundle: Option<PathBuf>,
+        /// Mask credentials and hostnames in the bundled files
+        #[arg(long, requires = "bundle")]
+        redact: bool,
+    },
+}
+
+/// Returns the sandbox with the given name, failing if it does not exist on disk.
+fn existing_sandbox(name: &str) -> anyhow::Result<Sandbox> {
+    let sandbox = Sandbox::new(name)?;
+    if !sandbox.exists() {
+        bail!(
+            "Sandbox '{}' does not exist. Run `zedc test` or `zedc pr` to create it.",
+            name
+        );
+    }
+    Ok(sandbox)
+}
+
+/// Prints the details of a sandbox in human-readable form.
+fn print_info(info: &SandboxInfo) {
+    println!("{}", info.name.bold().blue());
+    println!("  {} {}", "Path:".dimmed(), info.path);
+    println!(
+        "  {} {}",
+        "Disk usage:".dimmed(),
+        format_bytes(info.size_bytes)
+    );
+    println!(
+        "  {} {}",
+        "VS Code:".dimmed(),
+        info.vscode_version.as_deref().unwrap_or("unknown")
+    );
+    println!(
+        "  {} {}",
+        "Last launch:".dimmed(),
+        info.last_launch
+            .map(format_age)
+            .unwrap_or_else(|| "never".to_owned())
+    );
+
+    if !info.launch.is_empty() {
+        println!("  {}", "Launch options:".dimmed());
+        if let Some(locale) = &info.launch.locale {
+            println!("    --locale {}", locale);
+        }
+        if let Some(port) = info.launch.inspect {
+            println!("    --inspect={}", port);
+        }
+        for (key, value) in &info.launch.settings {
+            println!("    --setting {}={}", key, value);
+        }
+        for (key, value) in &info.launch.env {
+            println!("    --env {}={}", key, value);
+        }
+    }
+
+    println!("  {}", "Extensions:".dimmed());
+    if info.extensions.is_empty() {
+        println!("    (none)");
+    }
+    for ext in &info.extensions {
+        println!("    📦 {}@{}", ext.id.bold(), ext.version);
+    }
+
+    match &info.zowe_config {
+        Some(config) => {
+            println!("  {} {}", "Zowe config:".dimmed(), config.path);
+            for profile in &config.profiles {
+                let default_for = config
+                    .defaults
+                    .iter()
+                    .filter(|(_, name)| *name == profile)
+                    .map(|(ty, _)| ty.as_str())
+                    .collect::<Vec<_>>();
+                if default_for.is_empty() {
+                    println!("    {}", profile);
+                } else {
+                    println!(
+                        "    {} {}",
+                        profile,
+                        format!("(default {})", default_for.join(", ")).dimmed()
+                    );
+                }
+            }
+        }
+        None => println!("  {} none", "Zowe config:".dimmed()),
+    }
+    if let Some(version) = &info.zowe_cli {
+        println!("  {} {}", "Zowe CLI:".dimmed(), version);
+    }
+}
+
+/// Prints the log files found in a sandbox.
+fn print_logs(logs: &SandboxLogs) {
+    println!(
+        "📜 Logs of sandbox '{}' (VS Code session: {})",
+        logs.sandbox.bold(),
+        logs.session.as_deref().unwrap_or("none")
+    );
+    if logs.files.is_empty() {
+        println!("    (no log files yet; launch the sandbox first)");
+    }
+    for file in &logs.files {
+        println!(
+            "    {:<12} {:>10}  {}",
+            file.kind.dimmed(),
+            format_bytes(file.size),
+            file.name
+        );
+    }
+}
+
+/// Handles the logic for the `zedc sandbox` command.
+///
+/// # Arguments
+/// * `cmd` - The `sandbox` subcommand to run
+pub async fn handle_cmd(cmd: Commands) -> anyhow::Result<i32> {
+    let json = output::json_enabled();
+
+    match cmd {
+        Commands::List => {
+            let sandboxes = Sandbox::list()?;
+            let infos = sandboxes.iter().map(Sandbox::info).collect::<Vec<_>>();
+            if json {
+                output::emit_json(&serde_json::json!({ "sandboxes": infos }));
+            } else if infos.is_empty() {
+                println!("No sandboxes found.");
+            } else {
+                println!(
+                    "{}",
+                    format!(
+                        "{:<20} {:<12} {:>10}  {}",
+                        "NAME", "VS CODE", "SIZE", "LAST LAUNCH"
+                    )
+                    .bold()
+                );
+                for info in &infos {
+                    println!(
+                        "{:<20} {:<12} {:>10}  {}",
+                        info.name,
+                        info.vscode_version.as_deref().unwrap_or("-"),
+                        format_bytes(info.size_bytes),
+                        info.last_launch
+                            .map(format_age)
+                            .unwrap_or_else(|| "never".to_owned())
+                    );
+                }
+            }
+        }
+        Commands::Info { name } => {
+            let info = existing_sandbox(&name)?.info();
+            if json {
+                output::emit_json(&info);
+            } else {
+                print_info(&info);
+            }
+        }
+        Commands::Reset {
+            name,
+            keep_extensions,
+        } => {
+            let sandbox = existing_sandbox(&name)?;
+            sandbox.reset(keep_extensions)?;
+            if json {
+                output::emit_action_result("sandbox reset", true);
+            } else {
+                println!("🧹 Reset sandbox '{}'", name.bold());
+            }
+        }
+        Commands::Delete { name } => {
+            let sandbox = existing_sandbox(&name)?;
+            std::fs::remove_dir_all(sandbox.root())
+                .with_context(|| format!("Failed to delete sandbox '{}'", name))?;
+            if json {
+                output::emit_action_result("sandbox delete", true);
+            } else {
+                println!("🗑️  Deleted sandbox '{}'", name.bold());
+            }
+        }
+        Commands::Open { name, wait } => {
+            let sandbox = existing_sandbox(&name)?;
+            let vsc_bin = match sandbox.metadata().vscode_bin {
+                Some(bin) if Path::new(&bin).exists() => bin,
+                _ => bail!(
+                    "The VS Code copy used by sandbox '{}' is no longer cached. Run `zedc test --sandbox {}` to prepare it again.",
+                    name,
+                    name
+                ),
+            };
+            if crate::code::is_server_binary(Path::new(&vsc_bin)) {
+                super::web::serve(&sandbox, Path::new(&vsc_bin)).await?;
+            } else if wait {
+                let summary = super::session::run(&sandbox, Path::new(&vsc_bin)).await?;
+                if json {
+                    output::emit_json(&serde_json::json!({
+                        "ok": summary.ok(),
+                        "command": "sandbox open",
+                        "session": summary,
+                    }));
+                } else {
+                    summary.print();
+                }
+                if !summary.ok() {
+                    return Ok(exit::ACTIVATION_FAILED);
+                }
+            } else {
+                sandbox.launch(Path::new(&vsc_bin))?;
+                if json {
+                    output::emit_action_result("sandbox open", true);
+                }
+            }
+        }
+        Commands::Snapshot {
+            name,
+            output,
+            include_extensions,
+        } => {
+            let sandbox = existing_sandbox(&name)?;
+            let path = output.unwrap_or_else(|| PathBuf::from(format!("{}-snapshot.zip", name)));
+            let (info, files) = snapshot::create(&sandbox, &path, include_extensions)?;
+            let size = path.metadata().map(|m| m.len()).unwrap_or(0);
+            if json {
+                output::emit_json(&serde_json::json!({
+                    "ok": true,
+                    "command": "sandbox snapshot",
+                    "file": path,
+                    "files": files,
+                    "size_bytes": size,
+                    "snapshot": info,
+                }));
+            } else {
+                println!(
+                    "📸 Saved snapshot of sandbox '{}' to {} ({} files, {})",
+                    name.bold(),
+                    path.display(),
+                    files,
+                    format_bytes(size)
+                );
+                if !include_extensions && !info.extensions.is_empty() {
+                    println!(
+                        "   {}",
+                        "Extensions are not included; use --include-extensions to add them."
+                            .dimmed()
+                    );
+                }
+            }
+        }
+        Commands::Restore { name, file } => {
+            let sandbox = Sandbox::new(&name)?;
+            let info = snapshot::restore(&sandbox, &file)?;
+            if json {
+                output::emit_json(&serde_json::json!({
+                    "ok": true,
+                    "command": "sandbox restore",
+                    "sandbox": name,
+                    "snapshot": info,
+                }));
+            } else {
+                println!(
+                    "⏪ Restored sandbox '{}' from {} (taken from '{}' {})",
+                    name.bold(),
+                    file.display(),
+                    info.sandbox,
+                    format_age(info.created)
+                );
+                if !info.includes_extensions && !info.extensions.is_empty() {
+                    println!(
+                        "   {}",
+                        "The snapshot was taken with these extensions:".dimmed()
+                    );
+                    for ext in &info.extensions {
+                        println!("     {}@{}", ext.id, ext.version.dimmed());
+                    }
+                }
+            }
+        }
+        Commands::Logs {
+            name,
+            bundle,
+            redact,
+        } => {
+            let sandbox = existing_sandbox(&name)?;
+            let logs = SandboxLogs::collect(&sandbox);
+            if let Some(path) = &bundle {
+                logs.write_bundle(&sandbox, path, redact)?;
+            }
+            if json {
+                output::emit_json(&serde_json::json!({
+                    "ok": true,
+                    "command": "sandbox logs",
+                    "logs": logs,
+                    "bundle": bundle,
+                    "redacted": redact,
+                }));
+            } else {
+                print_logs(&logs);
+                if let Some(path) = &bundle {
+                    println!(
+                        "🗜️  Wrote {}{}",
+                        path.display().bold(),
+                        if redact {
+                            " (credentials and hostnames redacted)"
+                        } else {
+                            ""
+                        }
+                    );
+                } else {
+                    println!(
+                        "{}",
+                        "Run with --bundle <file.zip> [--redact] to package them for an issue."
+                            .dimmed()
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(exit::SUCCESS)
+}
@@ -0,0 +1,562 @@
+//! Debugging the extensions installed in a sandbox (`--inspect`).
+//!
+//! The sandbox VS Code is launched with `--inspect-extensions=<port>`, and a Node.js `attach`
+//! configuration is generated for the repository: its `outFiles` point at the extensions folder of
+//! the sandbox, and its source-map path overrides map the bundled sources of each VSIX back to the
+//! matching package in the checkout, so breakpoints can be set in a built artifact. Source maps that
+//! are not packaged in the VSIX are taken from a matching build in the repository.
+
+use super::Sandbox;
+use anyhow::{bail, Context};
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Returns the root of the checked-out repository: the folder containing `packages/*/package.json`
+/// for a monorepo like Zowe Explorer, otherwise the nearest folder containing `package.json`.
+pub fn repo_root() -> anyhow::Result<Option<PathBuf>> {
+    if let Some(package) = crate::util::find_dir_match(&["packages/*/package.json"])? {
+        return Ok(package.parent().and_then(Path::parent).map(Path::to_owned));
+    }
+    crate::util::find_dir_match(&["package.json"])
+}
+
+/// Returns the name of the attach configuration for a sandbox.
+pub fn config_name(sandbox: &Sandbox) -> String {
+    format!("Attach to zedc sandbox '{}'", sandbox.name)
+}
+
+/// Builds a Node.js `attach` configuration for the extension host of a sandbox. Returns the
+/// configuration and warnings for the installed extensions whose sources cannot be mapped to the
+/// repository.
+///
+/// VSIXes usually leave out their source maps, so the maps of a matching build in the repository
+/// (e.g. from `zedc test local --build`) are copied next to the bundled files in the sandbox.
+/// The source-map path overrides are derived from the `sources` of those maps, which webpack
+/// records with the absolute path of the build (`webpack:///[absolute-resource-path]`).
+///
+/// # Arguments
+/// * `sandbox` - The sandbox whose extensions are debugged
+/// * `port` - The port passed to `--inspect-extensions`
+/// * `repo` - The repository with the sources of the installed `.vsix` files, if any
+pub fn attach_config(sandbox: &Sandbox, port: u16, repo: Option<&Path>) -> (Value, Vec<String>) {
+    let extensions = sandbox
+        .extensions_dir()
+        .to_string_lossy()
+        .replace('\\', "/");
+    let mut overrides = Map::new();
+    let mut warnings = Vec::new();
+    if let Some(repo) = repo {
+        let packages = repo_packages(repo);
+        for vsix in &sandbox.metadata().vsix {
+            let Ok(manifest) = crate::vsix::read_manifest(Path::new(vsix)) else {
+                continue;
+            };
+            let Some((_, dir)) = packages.iter().find(|(name, _)| *name == manifest.name) else {
+                continue;
+            };
+            let Some(installed) = installed_dir(sandbox, &manifest) else {
+                continue;
+            };
+            let package_dir = repo.join(dir);
+            let mut maps = map_files(&installed);
+            if maps.is_empty() {
+                maps = copy_build_maps(&package_dir, &installed);
+            }
+            if maps.is_empty() {
+                warnings.push(format!(
+                    "{} has no source maps and does not match a build in {}; build it there (e.g. `zedc test local --build`) to set breakpoints in its sources",
+                    manifest.name,
+                    package_dir.display()
+                ));
+                continue;
+            }
+            let Some(prefix) = maps.iter().find_map(|map| source_prefix(map, &package_dir)) else {
+                warnings.push(format!(
+                    "The source maps of {} do not refer to files in {}",
+                    manifest.name,
+                    package_dir.display()
+                ));
+                continue;
+            };
+            let target = if dir.is_empty() {
+                "${workspaceFolder}/*".to_owned()
+            } else {
+                format!("${{workspaceFolder}}/{}/*", dir)
+            };
+            overrides.insert(format!("{}*", prefix), json!(target));
+        }
+    }
+
+    let config = json!({
+        "type": "node",
+        "request": "attach",
+        "name": config_name(sandbox),
+        "port": port,
+        "restart": true,
+        "sourceMaps": true,
+        "outFiles": [format!("{}/**/*.js", extensions), "!**/node_modules/**"],
+        "resolveSourceMapLocations": [format!("{}/**", extensions), "!**/node_modules/**"],
+        "sourceMapPathOverrides": overrides,
+    });
+    (config, warnings)
+}
+
+/// Returns the folder of an extension installed in a sandbox (`<publisher>.<name>-<version>`).
+fn installed_dir(sandbox: &Sandbox, manifest: &crate::vsix::VsixManifest) -> Option<PathBuf> {
+    std::fs::read_dir(sandbox.extensions_dir())
+        .ok()?
+        .filter_map(|e| e.ok())
+        .find(|e| {
+            super::parse_extension_dir_name(&e.file_name().to_string_lossy()).is_some_and(|ext| {
+                ext.id.eq_ignore_ascii_case(&manifest.id()) && ext.version == manifest.version
+            })
+        })
+        .map(|e| e.path())
+}
+
+/// Returns the `.map` files below a folder, skipping `node_modules`.
+fn map_files(dir: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return found;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(t) if t.is_dir() && entry.file_name() != "node_modules" => {
+                found.extend(map_files(&path))
+            }
+            Ok(t) if t.is_file() && path.extension().is_some_and(|ext| ext == "map") => {
+                found.push(path)
+            }
+            _ => {}
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Copies the source maps of a build in the repository into an installed extension, next to the
+/// bundled files they belong to. Maps are only copied when the installed file is identical to the
+/// one in the repository, so that they match the code that runs. Returns the copied maps.
+fn copy_build_maps(package_dir: &Path, installed: &Path) -> Vec<PathBuf> {
+    let mut copied = Vec::new();
+    for map in map_files(package_dir) {
+        let Ok(relative) = map.strip_prefix(package_dir) else {
+            continue;
+        };
+        let built = map.with_extension("");
+        let target = installed.join(relative);
+        let bundled = target.with_extension("");
+        let identical = match (std::fs::read(&built), std::fs::read(&bundled)) {
+            (Ok(built), Ok(bundled)) => built == bundled,
+            _ => false,
+        };
+        if identical && std::fs::copy(&map, &target).is_ok() {
+            copied.push(target);
+        }
+    }
+    copied
+}
+
+/// Returns the part of the `sources` in a source map that comes before the paths of the package
+/// files, e.g. `webpack:////home/ci/zowe-explorer/packages/zowe-explorer/` for
+/// `webpack:////home/ci/zowe-explorer/packages/zowe-explorer/src/extension.ts`.
+fn source_prefix(map: &Path, package_dir: &Path) -> Option<String> {
+    let map: Value = serde_json::from_str(&std::fs::read_to_string(map).ok()?).ok()?;
+    let root = map["sourceRoot"].as_str().unwrap_or_default();
+    map["sources"]
+        .as_array()?
+        .iter()
+        .filter_map(|source| Some(format!("{}{}", root, source.as_str()?)))
+        .filter(|source| !source.contains("/node_modules/"))
+        .find_map(|source| {
+            source.match_indices('/').find_map(|(i, _)| {
+                let relative = Path::new(&source[i + 1..]);
+                let is_relative = relative
+                    .components()
+                    .all(|c| matches!(c, std::path::Component::Normal(_)));
+                (is_relative && package_dir.join(relative).is_file())
+                    .then(|| source[..i + 1].to_owned())
+            })
+        })
+}
+
+/// Returns the name and folder (relative to the repository root) of every package in a repository.
+fn repo_packages(repo: &Path) -> Vec<(String, String)> {
+    let mut manifests = vec![(repo.join("package.json"), String::new())];
+    if let Ok(entries) = std::fs::read_dir(repo.join("packages")) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let dir = format!("packages/{}", entry.file_name().to_string_lossy());
+            manifests.push((entry.path().join("package.json"), dir));
+        }
+    }
+    manifests
+        .into_iter()
+        .filter_map(|(path, dir)| {
+            let manifest: Value =
+                serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+            Some((manifest["name"].as_str()?.to_owned(), dir))
+        })
+        .collect()
+}
+
+/// Adds an attach configuration to `.vscode/launch.json` in a repository, replacing a configuration
+/// with the same name. Returns the path of the file.
+///
+/// The configuration is spliced into the text of an existing file, so that its comments and the
+/// formatting of the other configurations are kept.
+pub fn write_launch_json(repo: &Path, config: &Value) -> anyhow::Result<PathBuf> {
+    let path = repo.join(".vscode").join("launch.json");
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) if !text.trim().is_empty() => splice_configuration(&text, config)
+            .with_context(|| format!("Invalid launch configuration {}", path.display()))?,
+        _ => serde_json::to_string_pretty(&json!({
+            "version": "0.2.0",
+            "configurations": [config],
+        }))?,
+    };
+    std::fs::create_dir_all(repo.join(".vscode"))?;
+    std::fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Returns the text of a `launch.json` with a configuration added to its `configurations`, or
+/// replacing the configuration with the same name.
+fn splice_configuration(text: &str, config: &Value) -> anyhow::Result<String> {
+    let launch = crate::util::parse_jsonc(text)?;
+    if !launch.is_object() {
+        bail!("the file must contain a JSON object");
+    }
+    let existing = match &launch["configurations"] {
+        Value::Array(configurations) => configurations
+            .iter()
+            .position(|c| c["name"] == config["name"]),
+        Value::Null => None,
+        _ => bail!("`configurations` must be an array"),
+    };
+
+    let code = mask_comments(text);
+    let root = skip_blank(&code, 0);
+    let Some(configurations) = members(&code, root)?
+        .into_iter()
+        .find(|(key, _)| key.as_deref() == Some("configurations"))
+        .map(|(_, span)| span)
+    else {
+        // Add the array after the last member of the object
+        let members = members(&code, root)?;
+        let (at, separator) = match members.last() {
+            Some((_, (_, end))) => (*end, ","),
+            None => (root + 1, ""),
+        };
+        let indent = line_indent(text, members.first().map_or(at, |(_, (start, _))| *start));
+        let indent = if indent.is_empty() { "  " } else { indent };
+        let array = indent_lines(&serde_json::to_string_pretty(&json!([config]))?, indent);
+        return Ok(format!(
+            "{}{}\n{}\"configurations\": {}{}",
+            &text[..at],
+            separator,
+            indent,
+            array,
+            &text[at..]
+        ));
+    };
+
+    let elements = members(&code, configurations.0)?;
+    let indent = match elements.first() {
+        Some((_, (start, _))) => line_indent(text, *start).to_owned(),
+        None => format!("{}  ", line_indent(text, configurations.0)),
+    };
+    let config = indent_lines(&serde_json::to_string_pretty(config)?, &indent);
+    Ok(match (existing, elements.last()) {
+        (Some(i), _) => {
+            let (start, end) = elements[i].1;
+            format!("{}{}{}", &text[..start], config, &text[end..])
+        }
+        (None, Some((_, (_, end)))) => {
+            format!("{},\n{}{}{}", &text[..*end], indent, config, &text[*end..])
+        }
+        (None, None) => {
+            let at = configurations.0 + 1;
+            let closing = line_indent(text, configurations.0);
+            format!(
+                "{}\n{}{}\n{}{}",
+                &text[..at],
+                indent,
+                config,
+                closing,
+                text[at..].trim_start()
+            )
+        }
+    })
+}
+
+/// Returns a copy of a JSONC text with its comments replaced by spaces, so that the positions of
+/// the JSON tokens are unchanged.
+fn mask_comments(text: &str) -> Vec<u8> {
+    let mut code = text.as_bytes().to_vec();
+    let mut i = 0;
+    while i < code.len() {
+        match (code[i], code.get(i + 1)) {
+            (b'"', _) => i = string_end(&code, i),
+            (b'/', Some(b'/')) => {
+                while i < code.len() && code[i] != b'\n' {
+                    code[i] = b' ';
+                    i += 1;
+                }
+            }
+            (b'/', Some(b'*')) => {
+                let end = (i + 2..code.len().saturating_sub(1))
+                    .find(|&j| code[j] == b'*' && code[j + 1] == b'/')
+                    .map_or(code.len(), |j| j + 2);
+                for c in &mut code[i..end] {
+                    if *c != b'\n' {
+                        *c = b' ';
+                    }
+                }
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    code
+}
+
+/// Returns the position after the string starting at `start`.
+fn string_end(code: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < code.len() {
+        match code[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    code.len()
+}
+
+/// Returns the position of the next character that is not whitespace.
+fn skip_blank(code: &[u8], mut i: usize) -> usize {
+    while i < code.len() && code[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+/// Returns the position after the JSON value starting at `start`.
+fn value_end(code: &[u8], start: usize) -> usize {
+    match code.get(start) {
+        Some(b'"') => string_end(code, start),
+        Some(b'{' | b'[') => {
+            let mut depth = 0;
+            let mut i = start;
+            while i < code.len() {
+                match code[i] {
+                    b'"' => {
+                        i = string_end(code, i);
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return i + 1;
+                        }
+                    }
+                    _ => {}
+                }
+                i += 1;
+            }
+            code.len()
+        }
+        _ => (start..code.len())
+            .find(|&i| matches!(code[i], b',' | b'}' | b']') || code[i].is_ascii_whitespace())
+            .unwrap_or(code.len()),
+    }
+}
+
+/// The start and end position of a JSON value in a text.
+type Span = (usize, usize);
+
+/// Returns the members of the object or array starting at `start`: the key (for objects) and the
+/// span of each value.
+fn members(code: &[u8], start: usize) -> anyhow::Result<Vec<(Option<String>, Span)>> {
+    let is_object = match code.get(start) {
+        Some(b'{') => true,
+        Some(b'[') => false,
+        _ => bail!("expected an object or array"),
+    };
+    let mut members = Vec::new();
+    let mut i = skip_blank(code, start + 1);
+    while i < code.len() && !matches!(code[i], b'}' | b']') {
+        let key = if is_object {
+            let end = string_end(code, i);
+            let key = serde_json::from_slice::<String>(&code[i..end])?;
+            i = skip_blank(code, skip_blank(code, end) + 1);
+            Some(key)
+        } else {
+            None
+        };
+        let end = value_end(code, i);
+        members.push((key, (i, end)));
+        i = skip_blank(code, end);
+        if code.get(i) == Some(&b',') {
+            i = skip_blank(code, i + 1);
+        }
+    }
+    Ok(members)
+}
+
+/// Returns the whitespace at the start of the line containing position `i`.
+fn line_indent(text: &str, i: usize) -> &str {
+    let line = text[..i].rfind('\n').map_or(0, |n| n + 1);
+    let rest = &text[line..];
+    &rest[..rest.len() - rest.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Indents every line but the first of a pretty-printed value.
+fn indent_lines(value: &str, indent: &str) -> String {
+    value.replace('\n', &format!("\n{}", indent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_vsix_sources_to_repo_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        let package = repo.join("packages").join("zowe-explorer");
+        std::fs::create_dir_all(package.join("src")).unwrap();
+        std::fs::create_dir_all(package.join("out").join("src")).unwrap();
+        std::fs::write(
+            repo.join("package.json"),
+            r#"{ "name": "zowe-explorer-root" }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            package.join("package.json"),
+            r#"{ "name": "vscode-extension-for-zowe" }"#,
+        )
+        .unwrap();
+        std::fs::write(package.join("src").join("extension.ts"), "export {}").unwrap();
+        // Built on another machine, like the VSIX of a PR
+        std::fs::write(package.join("out/src/extension.js"), "bundle").unwrap();
+        std::fs::write(
+            package.join("out/src/extension.js.map"),
+            r#"{ "version": 3, "sources": [
+                "webpack:///webpack/bootstrap",
+                "webpack:////home/ci/zowe-explorer/node_modules/semver/index.js",
+                "webpack:////home/ci/zowe-explorer/packages/zowe-explorer/src/extension.ts"
+            ] }"#,
+        )
+        .unwrap();
+        let vsix = crate::vsix::tests::write_vsix(
+            dir.path(),
+            "ze.vsix",
+            r#"{ "publisher": "Zowe", "name": "vscode-extension-for-zowe", "version": "3.2.0" }"#,
+            &[],
+        );
+
+        let sandbox = Sandbox {
+            name: "pr-1234".to_owned(),
+            root: dir.path().join("sandbox"),
+        };
+        sandbox.prepare().unwrap();
+        let mut metadata = sandbox.metadata();
+        metadata.vsix = vec![vsix.to_string_lossy().into_owned()];
+        sandbox.save_metadata(&metadata).unwrap();
+        let installed = sandbox
+            .extensions_dir()
+            .join("zowe.vscode-extension-for-zowe-3.2.0");
+        std::fs::create_dir_all(installed.join("out").join("src")).unwrap();
+
+        // The installed bundle differs from the build in the repository, so its maps do not apply
+        std::fs::write(installed.join("out/src/extension.js"), "other bundle").unwrap();
+        let (config, warnings) = attach_config(&sandbox, 9230, Some(&repo));
+        assert_eq!(config["sourceMapPathOverrides"], json!({}));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("has no source maps"));
+
+        std::fs::write(installed.join("out/src/extension.js"), "bundle").unwrap();
+        let (config, warnings) = attach_config(&sandbox, 9230, Some(&repo));
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(config["name"], "Attach to zedc sandbox 'pr-1234'");
+        assert_eq!(config["port"], 9230);
+        assert_eq!(
+            config["sourceMapPathOverrides"],
+            json!({
+                "webpack:////home/ci/zowe-explorer/packages/zowe-explorer/*": "${workspaceFolder}/packages/zowe-explorer/*"
+            })
+        );
+        assert!(installed.join("out/src/extension.js.map").is_file());
+        assert!(config["outFiles"][0]
+            .as_str()
+            .unwrap()
+            .ends_with("sandbox/data/extensions/**/*.js"));
+    }
+
+    #[test]
+    fn updates_launch_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".vscode").join("launch.json");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &path,
+            r#"{
+  // Use IntelliSense to learn about possible attributes.
+  // Hover to view descriptions of existing attributes.
+  "version": "0.2.0",
+  "configurations": [
+    /* Runs the checkout with F5, see "https://code.visualstudio.com/api" */
+    { "name": "Run Extension", "type": "extensionHost", "request": "launch" },
+    { "name": "Attach to zedc sandbox 'default'", "port": 1 },
+  ]
+}"#,
+        )
+        .unwrap();
+
+        let config = json!({ "name": "Attach to zedc sandbox 'default'", "port": 9229 });
+        assert_eq!(write_launch_json(dir.path(), &config).unwrap(), path);
+        let other = json!({ "name": "Attach to zedc sandbox 'other'", "port": 9230 });
+        write_launch_json(dir.path(), &other).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("  // Use IntelliSense to learn about possible attributes.\n"));
+        assert!(text.contains(
+            "/* Runs the checkout with F5, see \"https://code.visualstudio.com/api\" */"
+        ));
+        assert!(text.contains(
+            "    { \"name\": \"Run Extension\", \"type\": \"extensionHost\", \"request\": \"launch\" },"
+        ));
+        let launch = crate::util::parse_jsonc(&text).unwrap();
+        let configurations = launch["configurations"].as_array().unwrap();
+        assert_eq!(configurations.len(), 3);
+        assert_eq!(configurations[0]["name"], "Run Extension");
+        assert_eq!(configurations[1], config);
+        assert_eq!(configurations[2], other);
+    }
+
+    #[test]
+    fn adds_configurations_to_launch_json() {
+        let config = json!({ "name": "Attach to zedc sandbox 'default'", "port": 9229 });
+        for text in [
+            "{ // comment\n  \"version\": \"0.2.0\" }",
+            "{\n  \"version\": \"0.2.0\",\n  \"configurations\": []\n}",
+            "{}",
+        ] {
+            let spliced = splice_configuration(text, &config).unwrap();
+            let launch = crate::util::parse_jsonc(&spliced).unwrap();
+            assert_eq!(launch["configurations"], json!([config]), "{}", spliced);
+        }
+        assert!(splice_configuration("[]", &config).is_err());
+        assert!(splice_configuration("{ \"configurations\": {} }", &config).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_launch_json(dir.path(), &config).unwrap();
+        let launch: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(launch["version"], "0.2.0");
+        assert_eq!(launch["configurations"], json!([config]));
+    }
+}
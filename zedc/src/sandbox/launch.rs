@@ -0,0 +1,275 @@
+//! Settings, environment variables and locale applied when a sandbox is launched.
+//!
+//! The options are recorded in `sandbox.json`, so `zedc sandbox open` re-launches a sandbox the
+//! same way as the `test` or `pr` run that prepared it. Values of environment variables that look
+//! like secrets are only kept in memory for the current run and recorded as [`REDACTED`].
+
+use crate::cmd::SandboxConfig;
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// The value recorded in place of a secret environment variable.
+pub const REDACTED: &str = "<redacted>";
+
+/// The values of secret environment variables set for sandboxes in this run, by sandbox folder.
+static SECRETS: Mutex<BTreeMap<PathBuf, BTreeMap<String, String>>> = Mutex::new(BTreeMap::new());
+
+/// The launch options of a sandbox.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct LaunchOptions {
+    /// Settings merged into the sandbox `settings.json` before launching.
+    pub settings: BTreeMap<String, Value>,
+    /// Environment variables passed to the VS Code process. Secret values are serialized as
+    /// [`REDACTED`].
+    #[serde(serialize_with = "serialize_env")]
+    pub env: BTreeMap<String, String>,
+    /// The display language passed to VS Code with `--locale` (e.g. `de`).
+    pub locale: Option<String>,
+    /// The port passed to VS Code with `--inspect-extensions` to debug the extension host.
+    pub inspect: Option<u16>,
+}
+
+impl LaunchOptions {
+    /// Builds the launch options from the command line. Settings from `--settings-file` are applied
+    /// first, so that `--setting` can override them.
+    pub fn from_config(config: &SandboxConfig) -> anyhow::Result<Self> {
+        let mut settings = BTreeMap::new();
+        if let Some(path) = &config.settings_file {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            match crate::util::parse_jsonc(&text)
+                .with_context(|| format!("Invalid settings file {}", path.display()))?
+            {
+                Value::Object(map) => settings.extend(map),
+                _ => bail!("{} must contain a JSON object", path.display()),
+            }
+        }
+        for setting in &config.settings {
+            let (key, value) = parse_setting(setting)?;
+            settings.insert(key, value);
+        }
+
+        let mut env = BTreeMap::new();
+        for var in &config.env {
+            match var.split_once('=') {
+                Some((key, value)) if !key.is_empty() => {
+                    env.insert(key.to_owned(), value.to_owned());
+                }
+                _ => bail!("Invalid --env '{}': expected KEY=VALUE", var),
+            }
+        }
+
+        Ok(Self {
+            settings,
+            env,
+            locale: config.locale.clone(),
+            inspect: config.inspect,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.settings.is_empty()
+            && self.env.is_empty()
+            && self.locale.is_none()
+            && self.inspect.is_none()
+    }
+
+    /// Keeps the values of secret environment variables in memory, so that launching the sandbox
+    /// at `root` later in this run passes them to VS Code.
+    pub fn remember_secrets(&self, root: &Path) {
+        let secrets = self
+            .env
+            .iter()
+            .filter(|(key, value)| is_secret(key) && *value != REDACTED)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        if let Ok(mut all) = SECRETS.lock() {
+            all.insert(root.to_owned(), secrets);
+        }
+    }
+
+    /// Returns the environment variables to pass to VS Code for the sandbox at `root`. Redacted
+    /// secrets are replaced by the values remembered in this run, or left out so that VS Code
+    /// inherits them from the environment of zedc.
+    pub fn process_env(&self, root: &Path) -> BTreeMap<String, String> {
+        let all = SECRETS.lock().ok();
+        let secrets = all.as_ref().and_then(|all| all.get(root));
+        self.env
+            .iter()
+            .filter_map(|(key, value)| {
+                if value != REDACTED {
+                    return Some((key.clone(), value.clone()));
+                }
+                Some((key.clone(), secrets?.get(key)?.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Serializes environment variables with the values of secrets replaced by [`REDACTED`].
+fn serialize_env<S: Serializer>(
+    env: &BTreeMap<String, String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(env.iter().map(|(key, value)| {
+        let value = if is_secret(key) {
+            REDACTED
+        } else {
+            value.as_str()
+        };
+        (key, value)
+    }))
+}
+
+/// Returns whether an environment variable name looks like it holds a secret.
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    ["PASSWORD", "TOKEN", "SECRET", "PASSPHRASE"]
+        .iter()
+        .any(|s| name.contains(s))
+}
+
+/// Parses a `--setting key=value` argument. Values that are valid JSON (`14`, `true`, `["a"]`) are
+/// used as-is; anything else is treated as a string.
+pub fn parse_setting(setting: &str) -> anyhow::Result<(String, Value)> {
+    let Some((key, value)) = setting.split_once('=') else {
+        bail!("Invalid --setting '{}': expected KEY=VALUE", setting);
+    };
+    let key = key.trim();
+    if key.is_empty() {
+        bail!("Invalid --setting '{}': the key is empty", setting);
+    }
+    let value =
+        serde_json::from_str(value.trim()).unwrap_or_else(|_| Value::String(value.to_owned()));
+    Ok((key.to_owned(), value))
+}
+
+/// Merges settings into a `settings.json` file, creating it if necessary.
+/// Existing settings that are not overridden are kept; comments in the file are not preserved.
+pub fn merge_settings(path: &Path, settings: &BTreeMap<String, Value>) -> anyhow::Result<()> {
+    let mut current = match std::fs::read_to_string(path) {
+        Ok(text) if !text.trim().is_empty() => match crate::util::parse_jsonc(&text)
+            .with_context(|| format!("Invalid settings file {}", path.display()))?
+        {
+            Value::Object(map) => map,
+            _ => bail!("{} must contain a JSON object", path.display()),
+        },
+        _ => Default::default(),
+    };
+    current.extend(settings.iter().map(|(k, v)| (k.clone(), v.clone())));
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&current)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_setting_values() {
+        assert_eq!(
+            parse_setting("editor.fontSize=14").unwrap(),
+            ("editor.fontSize".to_owned(), json!(14))
+        );
+        assert_eq!(
+            parse_setting("zowe.logger=DEBUG").unwrap().1,
+            json!("DEBUG")
+        );
+        assert_eq!(
+            parse_setting("zowe.ds.paginate=false").unwrap().1,
+            json!(false)
+        );
+        assert!(parse_setting("no-value").is_err());
+    }
+
+    #[test]
+    fn builds_options_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("settings.json");
+        std::fs::write(
+            &file,
+            "{\n  // Reproduce the reported setup\n  \"zowe.logger\": \"INFO\",\n  \"files.autoSave\": \"off\",\n}",
+        )
+        .unwrap();
+        let config = SandboxConfig {
+            settings: vec!["zowe.logger=DEBUG".to_owned()],
+            settings_file: Some(file),
+            env: vec!["ZOWE_OPT_RESPONSE_TIMEOUT=600".to_owned()],
+            locale: Some("de".to_owned()),
+            inspect: Some(9230),
+            ..Default::default()
+        };
+        let options = LaunchOptions::from_config(&config).unwrap();
+        assert_eq!(options.settings["zowe.logger"], json!("DEBUG"));
+        assert_eq!(options.settings["files.autoSave"], json!("off"));
+        assert_eq!(options.env["ZOWE_OPT_RESPONSE_TIMEOUT"], "600");
+        assert_eq!(options.locale.as_deref(), Some("de"));
+        assert_eq!(options.inspect, Some(9230));
+
+        let config = SandboxConfig {
+            env: vec!["=oops".to_owned()],
+            ..Default::default()
+        };
+        assert!(LaunchOptions::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn keeps_secrets_out_of_the_metadata() {
+        let config = SandboxConfig {
+            env: vec![
+                "ZOWE_OPT_HOST=example.com".to_owned(),
+                "ZOWE_OPT_PASSWORD=hunter2".to_owned(),
+            ],
+            ..Default::default()
+        };
+        let options = LaunchOptions::from_config(&config).unwrap();
+        let saved = serde_json::to_value(&options).unwrap();
+        assert_eq!(
+            saved["env"],
+            json!({ "ZOWE_OPT_HOST": "example.com", "ZOWE_OPT_PASSWORD": REDACTED })
+        );
+
+        // The secret is passed to VS Code in the run that set it, and inherited from the environment
+        // of zedc when the sandbox is re-opened later
+        let dir = tempfile::tempdir().unwrap();
+        let loaded: LaunchOptions = serde_json::from_value(saved).unwrap();
+        assert!(!loaded
+            .process_env(dir.path())
+            .contains_key("ZOWE_OPT_PASSWORD"));
+        options.remember_secrets(dir.path());
+        let env = loaded.process_env(dir.path());
+        assert_eq!(env["ZOWE_OPT_PASSWORD"], "hunter2");
+        assert_eq!(env["ZOWE_OPT_HOST"], "example.com");
+    }
+
+    #[test]
+    fn merges_into_existing_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("User").join("settings.json");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &path,
+            "{ /* user */ \"window.zoomLevel\": 1, \"zowe.logger\": \"INFO\", }",
+        )
+        .unwrap();
+        let settings = BTreeMap::from([("zowe.logger".to_owned(), json!("DEBUG"))]);
+        merge_settings(&path, &settings).unwrap();
+
+        let merged: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            merged,
+            json!({ "window.zoomLevel": 1, "zowe.logger": "DEBUG" })
+        );
+    }
+}
@@ -0,0 +1,518 @@
+//! "Root" module for the VS Code sandboxes launched by `zedc test` and `zedc pr`.
+//!
+//! Each sandbox lives in `zedc_data/sandboxes/<name>` and is independent of the cached copies of
+//! VS Code, so it can be inspected, reset or deleted without re-downloading VS Code:
+//! * `data/` - VS Code portable data (`user-data`, `extensions`), selected using `VSCODE_PORTABLE`
+//! * `workspace/` - the folder opened in VS Code; `workspace/.zowe` is used as `ZOWE_CLI_HOME`
+//! * `cli/` - the npm prefix Zowe CLI is installed into with `--install-cli`, put on the `PATH`
+//! * `sandbox.json` - metadata recorded when the sandbox is launched
+
+use anyhow::{bail, Context};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+};
+
+pub mod cli;
+mod cmd;
+pub mod debug;
+mod launch;
+mod logs;
+pub mod session;
+mod snapshot;
+pub mod web;
+pub mod zowe;
+pub use cmd::{handle_cmd, Commands};
+pub use launch::LaunchOptions;
+
+/// The name of the sandbox used when `--sandbox` is not provided.
+pub const DEFAULT_SANDBOX: &str = "default";
+
+/// Metadata persisted in `sandbox.json` for each sandbox.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SandboxMetadata {
+    /// The VS Code version the sandbox was last prepared with.
+    pub vscode_version: Option<String>,
+    /// Absolute path to the Code CLI binary, used to re-launch the sandbox.
+    pub vscode_bin: Option<String>,
+    /// The `.vsix` files installed into the sandbox by the last `test`/`pr` run.
+    pub vsix: Vec<String>,
+    /// Unix timestamp of the last launch.
+    pub last_launch: Option<u64>,
+    /// The settings, environment variables and locale to launch the sandbox with.
+    pub launch: LaunchOptions,
+}
+
+/// An extension installed in a sandbox.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InstalledExtension {
+    pub id: String,
+    pub version: String,
+}
+
+/// Summary of the Zowe team configuration found in the sandbox `.zowe` directory.
+#[derive(Serialize)]
+pub struct ZoweConfigInfo {
+    pub path: String,
+    pub profiles: Vec<String>,
+    pub defaults: BTreeMap<String, String>,
+}
+
+/// Machine-readable details for a sandbox (`zedc sandbox info/list --json`).
+#[derive(Serialize)]
+pub struct SandboxInfo {
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub vscode_version: Option<String>,
+    pub last_launch: Option<u64>,
+    pub launch: LaunchOptions,
+    pub extensions: Vec<InstalledExtension>,
+    pub zowe_config: Option<ZoweConfigInfo>,
+    /// The version of Zowe CLI installed in the sandbox.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zowe_cli: Option<String>,
+}
+
+/// A named VS Code sandbox in `zedc_data/sandboxes`.
+pub struct Sandbox {
+    pub name: String,
+    root: PathBuf,
+}
+
+impl Sandbox {
+    /// Returns the sandbox with the given name. The sandbox is not created on disk.
+    pub fn new(name: &str) -> anyhow::Result<Self> {
+        if name.is_empty()
+            || name.starts_with('.')
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            bail!(
+                "Invalid sandbox name '{}': use letters, digits, '-', '_' or '.'",
+                name
+            );
+        }
+        Ok(Self {
+            name: name.to_owned(),
+            root: sandboxes_dir()?.join(name),
+        })
+    }
+
+    /// Returns all sandboxes that exist on disk, sorted by name.
+    pub fn list() -> anyhow::Result<Vec<Self>> {
+        Self::list_in(&sandboxes_dir()?)
+    }
+
+    /// Returns the sandboxes in the given folder, sorted by name.
+    pub(crate) fn list_in(dir: &Path) -> anyhow::Result<Vec<Self>> {
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut sandboxes = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                if let Ok(mut sandbox) = Self::new(&entry.file_name().to_string_lossy()) {
+                    sandbox.root = entry.path();
+                    sandboxes.push(sandbox);
+                }
+            }
+        }
+        sandboxes.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(sandboxes)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn exists(&self) -> bool {
+        self.root.exists()
+    }
+
+    /// The VS Code portable data directory for this sandbox.
+    pub fn data_dir(&self) -> PathBuf {
+        self.root.join("data")
+    }
+
+    pub fn user_data_dir(&self) -> PathBuf {
+        self.data_dir().join("user-data")
+    }
+
+    pub fn extensions_dir(&self) -> PathBuf {
+        self.data_dir().join("extensions")
+    }
+
+    /// The data directory of the VS Code server when the sandbox is served with `--web`.
+    pub fn server_data_dir(&self) -> PathBuf {
+        self.data_dir().join("server")
+    }
+
+    /// The folder that is opened in VS Code when the sandbox is launched.
+    pub fn workspace_dir(&self) -> PathBuf {
+        self.root.join("workspace")
+    }
+
+    /// The directory used as `ZOWE_CLI_HOME` for the sandbox.
+    pub fn zowe_dir(&self) -> PathBuf {
+        self.workspace_dir().join(".zowe")
+    }
+
+    /// The npm prefix Zowe CLI is installed into (see [`cli::install`]).
+    pub fn cli_dir(&self) -> PathBuf {
+        self.root.join("cli")
+    }
+
+    /// Returns the `PATH` for processes of the sandbox, with the Zowe CLI binaries of the sandbox
+    /// first, or `None` if Zowe CLI is not installed in it.
+    pub fn path_env(&self) -> Option<std::ffi::OsString> {
+        let bin = cli::bin_dir(&self.cli_dir());
+        if !bin.is_dir() {
+            return None;
+        }
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        std::env::join_paths(std::iter::once(bin).chain(std::env::split_paths(&path))).ok()
+    }
+
+    fn metadata_path(&self) -> PathBuf {
+        self.root.join("sandbox.json")
+    }
+
+    /// Creates the sandbox directories if they do not exist yet.
+    pub fn prepare(&self) -> anyhow::Result<()> {
+        std::fs::create_dir_all(self.data_dir())
+            .with_context(|| format!("Failed to create sandbox '{}'", self.name))?;
+        std::fs::create_dir_all(self.zowe_dir())?;
+        Ok(())
+    }
+
+    /// Reads the sandbox metadata, returning defaults when none has been recorded.
+    pub fn metadata(&self) -> SandboxMetadata {
+        std::fs::read_to_string(self.metadata_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_metadata(&self, metadata: &SandboxMetadata) -> anyhow::Result<()> {
+        std::fs::write(
+            self.metadata_path(),
+            serde_json::to_string_pretty(metadata)?,
+        )
+        .with_context(|| format!("Failed to write metadata for sandbox '{}'", self.name))
+    }
+
+    /// Returns the environment variables that point VS Code at this sandbox: its portable data
+    /// folder, `ZOWE_CLI_HOME` and, when Zowe CLI is installed in it, the `PATH`.
+    fn env(&self) -> Vec<(&'static str, std::ffi::OsString)> {
+        let mut env = vec![
+            ("VSCODE_PORTABLE", self.data_dir().into_os_string()),
+            ("ZOWE_CLI_HOME", self.zowe_dir().into_os_string()),
+        ];
+        if let Some(path) = self.path_env() {
+            env.push(("PATH", path));
+        }
+        env
+    }
+
+    /// Returns a `Command` for the given Code CLI binary that operates on this sandbox's data.
+    ///
+    /// `VSCODE_PORTABLE` overrides the portable data folder of the extracted VS Code copy,
+    /// so several sandboxes can share one cached VS Code version. The `code-server` binary of a
+    /// server build ignores it, so the sandbox folders are passed as arguments instead.
+    pub fn code_command(&self, vsc_bin: &Path) -> Command {
+        let mut cmd = Command::new(vsc_bin);
+        cmd.envs(self.env());
+        if crate::code::is_server_binary(vsc_bin) {
+            cmd.arg("--server-data-dir")
+                .arg(self.server_data_dir())
+                .arg("--user-data-dir")
+                .arg(self.user_data_dir())
+                .arg("--extensions-dir")
+                .arg(self.extensions_dir());
+        }
+        cmd
+    }
+
+    /// Lists the extensions installed in the sandbox.
+    ///
+    /// Reads the `extensions.json` index maintained by VS Code, falling back to the
+    /// `<publisher>.<name>-<version>` folder names when the index is missing.
+    pub fn installed_extensions(&self) -> Vec<InstalledExtension> {
+        let ext_dir = self.extensions_dir();
+        let index: Option<Vec<Value>> = std::fs::read_to_string(ext_dir.join("extensions.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok());
+        let mut extensions: Vec<InstalledExtension> = match index {
+            Some(entries) => entries
+                .iter()
+                .filter_map(|e| {
+                    Some(InstalledExtension {
+                        id: e.pointer("/identifier/id")?.as_str()?.to_owned(),
+                        version: e.get("version")?.as_str()?.to_owned(),
+                    })
+                })
+                .collect(),
+            None => std::fs::read_dir(&ext_dir)
+                .map(|entries| {
+                    entries
+                        .filter_map(|e| e.ok())
+                        .filter(|e| e.path().is_dir())
+                        .filter_map(|e| parse_extension_dir_name(&e.file_name().to_string_lossy()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+        extensions.sort_by(|a, b| a.id.cmp(&b.id));
+        extensions
+    }
+
+    /// Summarizes the `zowe.config.json` in the sandbox `.zowe` directory, if present.
+    pub fn zowe_config(&self) -> Option<ZoweConfigInfo> {
+        let path = self.zowe_dir().join("zowe.config.json");
+        let config: Value = serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+        let mut profiles = Vec::new();
+        if let Some(p) = config.get("profiles") {
+            collect_profile_names(p, "", &mut profiles);
+        }
+        let defaults = config
+            .get("defaults")
+            .and_then(|d| d.as_object())
+            .map(|d| {
+                d.iter()
+                    .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_owned())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(ZoweConfigInfo {
+            path: path.to_string_lossy().into_owned(),
+            profiles,
+            defaults,
+        })
+    }
+
+    /// Collects the details reported by `zedc sandbox info`.
+    pub fn info(&self) -> SandboxInfo {
+        let metadata = self.metadata();
+        SandboxInfo {
+            name: self.name.clone(),
+            path: self.root.to_string_lossy().into_owned(),
+            size_bytes: crate::util::dir_size(&self.root),
+            vscode_version: metadata.vscode_version,
+            last_launch: metadata.last_launch,
+            launch: metadata.launch,
+            extensions: self.installed_extensions(),
+            zowe_config: self.zowe_config(),
+            zowe_cli: cli::version(self),
+        }
+    }
+
+    /// Removes the sandbox user data and workspace, optionally keeping installed extensions.
+    /// The recorded VS Code binary is kept so the sandbox can be re-opened right away.
+    pub fn reset(&self, keep_extensions: bool) -> anyhow::Result<()> {
+        let mut targets = vec![self.user_data_dir(), self.workspace_dir()];
+        if !keep_extensions {
+            targets.push(self.extensions_dir());
+        }
+        for dir in targets {
+            if dir.exists() {
+                std::fs::remove_dir_all(&dir)
+                    .with_context(|| format!("Failed to remove {}", dir.display()))?;
+            }
+        }
+        self.prepare()?;
+
+        let mut metadata = self.metadata();
+        metadata.last_launch = None;
+        if !keep_extensions {
+            metadata.vsix.clear();
+        }
+        self.save_metadata(&metadata)
+    }
+
+    /// Writes a team configuration to `zowe.config.json` in the sandbox `.zowe` directory,
+    /// replacing any existing one.
+    pub fn write_zowe_config(&self, config: &Value) -> anyhow::Result<()> {
+        self.prepare()?;
+        let path = self.zowe_dir().join("zowe.config.json");
+        std::fs::write(&path, serde_json::to_string_pretty(config)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        if crate::output::text_enabled() {
+            println!("🔑 Wrote {}", path.display());
+        }
+        Ok(())
+    }
+
+    /// Records the options the sandbox is launched with, replacing the ones of the previous run.
+    /// Secret environment variables are only kept in memory for this run.
+    pub fn set_launch_options(&self, options: LaunchOptions) -> anyhow::Result<()> {
+        self.prepare()?;
+        options.remember_secrets(&self.root);
+        let mut metadata = self.metadata();
+        metadata.launch = options;
+        self.save_metadata(&metadata)
+    }
+
+    /// Launches VS Code for this sandbox using the given Code CLI binary.
+    ///
+    /// The recorded launch options are applied first: settings are merged into the sandbox
+    /// `settings.json`, and the environment variables and locale are passed to VS Code.
+    pub fn launch(&self, vsc_bin: &Path) -> anyhow::Result<()> {
+        self.spawn(vsc_bin, false).map(drop)
+    }
+
+    /// Launches VS Code like [`Sandbox::launch`], but returns a process that only exits once the
+    /// VS Code window is closed (`--wait` for the Code CLI, `open -W` on macOS).
+    pub fn launch_attached(&self, vsc_bin: &Path) -> anyhow::Result<Child> {
+        self.spawn(vsc_bin, true)
+    }
+
+    fn spawn(&self, vsc_bin: &Path, wait: bool) -> anyhow::Result<Child> {
+        let text = crate::output::text_enabled();
+        self.prepare()?;
+
+        let vsc = crate::code::code_binary(vsc_bin)
+            .with_context(|| format!("{} is not a VS Code CLI binary", vsc_bin.display()))?;
+        let workspace_dir = self.workspace_dir();
+        let options = self.metadata().launch;
+        let env = options.process_env(&self.root);
+        if !options.settings.is_empty() {
+            launch::merge_settings(
+                &self.user_data_dir().join("User").join("settings.json"),
+                &options.settings,
+            )?;
+        }
+        let mut args = vec!["--new-window".to_owned()];
+        if wait && std::env::consts::OS != "macos" {
+            args.push("--wait".to_owned());
+        }
+        if let Some(locale) = &options.locale {
+            args.extend(["--locale".to_owned(), locale.clone()]);
+        }
+        if let Some(port) = options.inspect {
+            args.push(format!("--inspect-extensions={}", port));
+        }
+
+        let spawned = if std::env::consts::OS == "macos" {
+            Command::new("open")
+                .args(wait.then_some("-W"))
+                .arg(&vsc)
+                .arg("--args")
+                .args(&args)
+                .arg("--disable-updates")
+                .arg(&workspace_dir)
+                .envs(self.env())
+                .envs(&env)
+                .stdout(Stdio::null())
+                .spawn()
+        } else {
+            self.code_command(&vsc)
+                .args(&args)
+                .arg(&workspace_dir)
+                .envs(&env)
+                .stdout(Stdio::null())
+                .spawn()
+        };
+        let child = match spawned {
+            Ok(child) => child,
+            Err(e) => bail!(
+                "Failed to launch VS Code for sandbox '{}': {}",
+                self.name,
+                e
+            ),
+        };
+
+        let mut metadata = self.metadata();
+        metadata.last_launch = Some(crate::util::unix_now());
+        self.save_metadata(&metadata)?;
+
+        if text {
+            println!("🚀 Launched VS Code (sandbox: {})", self.name.bold());
+            if let Some(port) = options.inspect {
+                println!(
+                    "🐞 Extension host debugger listening on port {}",
+                    port.bold()
+                );
+            }
+        }
+        Ok(child)
+    }
+}
+
+/// Returns the directory that contains all sandboxes.
+fn sandboxes_dir() -> anyhow::Result<PathBuf> {
+    Ok(crate::util::data_dir()?.join("sandboxes"))
+}
+
+/// Parses an extension folder name such as `zowe.vscode-extension-for-zowe-3.2.0`.
+fn parse_extension_dir_name(name: &str) -> Option<InstalledExtension> {
+    let (id, version) = name
+        .match_indices('-')
+        .map(|(i, _)| (&name[..i], &name[i + 1..]))
+        .find(|(_, version)| version.starts_with(|c: char| c.is_ascii_digit()))?;
+    if !id.contains('.') {
+        return None;
+    }
+    Some(InstalledExtension {
+        id: id.to_owned(),
+        version: version.to_owned(),
+    })
+}
+
+/// Recursively collects profile names from a team config `profiles` object,
+/// joining nested profiles with `.` (e.g. `lpar1.zosmf`).
+fn collect_profile_names(profiles: &Value, prefix: &str, out: &mut Vec<String>) {
+    let Some(map) = profiles.as_object() else {
+        return;
+    };
+    for (name, profile) in map {
+        let full_name = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        out.push(full_name.clone());
+        if let Some(nested) = profile.get("profiles") {
+            collect_profile_names(nested, &full_name, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_extension_folder_names() {
+        let ext = parse_extension_dir_name("zowe.vscode-extension-for-zowe-3.2.0").unwrap();
+        assert_eq!(ext.id, "zowe.vscode-extension-for-zowe");
+        assert_eq!(ext.version, "3.2.0");
+        assert!(parse_extension_dir_name("not-an-extension").is_none());
+    }
+
+    #[test]
+    fn collects_nested_profile_names() {
+        let profiles = json!({
+            "lpar1": { "properties": {}, "profiles": { "zosmf": { "type": "zosmf" } } },
+            "base": { "type": "base" }
+        });
+        let mut names = Vec::new();
+        collect_profile_names(&profiles, "", &mut names);
+        names.sort();
+        assert_eq!(names, vec!["base", "lpar1", "lpar1.zosmf"]);
+    }
+
+    #[test]
+    fn rejects_sandbox_names_with_path_separators() {
+        assert!(Sandbox::new("../escape").is_err());
+        assert!(Sandbox::new("feature-123").is_ok());
+    }
+}
@@ -0,0 +1,366 @@
+//! Following a sandbox VS Code session until its window is closed (`--wait`).
+//!
+//! While VS Code runs, the extension host logs of the new log session
+//! (`user-data/logs/<session>/window*/exthost/exthost.log`) are tailed: errors and Zowe-related
+//! entries are printed as they appear, and the activation of Zowe Explorer is tracked so that a
+//! summary can be reported once the window is closed.
+
+use super::Sandbox;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// The extension ID of Zowe Explorer.
+pub const ZOWE_EXPLORER_ID: &str = "zowe.vscode-extension-for-zowe";
+
+/// How often the VS Code process and the extension host logs are polled.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How Zowe Explorer activated during a session.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Activation {
+    /// Zowe Explorer started activating and no activation error was logged.
+    Activated,
+    /// The extension host logged an error while activating Zowe Explorer.
+    Failed,
+    /// The extension host started, but Zowe Explorer was never activated.
+    NotActivated,
+    /// No extension host log was found, e.g. because VS Code reused a running instance.
+    Unknown,
+}
+
+/// The result of a VS Code session that zedc stayed attached to.
+#[derive(Serialize, Debug)]
+pub struct SessionSummary {
+    pub sandbox: String,
+    /// The VS Code log session that was followed (e.g. `20250101T120000`).
+    pub log_session: Option<String>,
+    /// How long the VS Code window was open, in milliseconds.
+    pub duration_ms: u64,
+    pub activation: Activation,
+    /// Time from launch until Zowe Explorer started activating, in milliseconds.
+    pub activation_ms: Option<u64>,
+    /// The messages of all error entries in the extension host logs.
+    pub errors: Vec<String>,
+}
+
+impl SessionSummary {
+    /// Whether Zowe Explorer activated, or its activation could not be observed.
+    pub fn ok(&self) -> bool {
+        matches!(self.activation, Activation::Activated | Activation::Unknown)
+    }
+
+    /// Prints the summary in text mode.
+    pub fn print(&self) {
+        println!(
+            "\n📋 {} (sandbox: {})",
+            "Session summary".bold(),
+            self.sandbox.bold()
+        );
+        println!(
+            "   {} {}",
+            "Duration:  ".dimmed(),
+            format_duration(Duration::from_millis(self.duration_ms))
+        );
+        let after = self
+            .activation_ms
+            .map(|ms| format!(" after {}", format_duration(Duration::from_millis(ms))))
+            .unwrap_or_default();
+        let activation = match self.activation {
+            Activation::Activated => format!("Zowe Explorer activated{}", after)
+                .green()
+                .to_string(),
+            Activation::Failed => format!("Zowe Explorer failed to activate{}", after)
+                .red()
+                .to_string(),
+            Activation::NotActivated => "Zowe Explorer was not activated".red().to_string(),
+            Activation::Unknown => "No extension host log was found".yellow().to_string(),
+        };
+        println!("   {} {}", "Activation:".dimmed(), activation);
+        println!("   {} {}", "Errors:    ".dimmed(), self.errors.len());
+        for error in &self.errors {
+            println!("     {} {}", "-".red(), error);
+        }
+    }
+}
+
+/// An entry of the extension host log that is shown while following a session.
+#[derive(Debug, PartialEq, Eq)]
+enum LogEvent {
+    Error(String),
+    Zowe(String),
+}
+
+/// Tracks Zowe Explorer activation and errors across the extension host log entries of a session.
+#[derive(Default)]
+struct LogMonitor {
+    activation_started: Option<Duration>,
+    activation_failed: bool,
+    errors: Vec<String>,
+}
+
+impl LogMonitor {
+    /// Processes one log line, observed `elapsed` after launch. Returns the entry to show, if any.
+    ///
+    /// Lines that do not start with a timestamp and level continue the previous entry (e.g. stack
+    /// traces) and are skipped.
+    fn observe(&mut self, line: &str, elapsed: Duration) -> Option<LogEvent> {
+        let (level, message) = parse_entry(line)?;
+        let lower = message.to_lowercase();
+        if lower.contains(ZOWE_EXPLORER_ID) {
+            if lower.contains("_doactivateextension") && self.activation_started.is_none() {
+                self.activation_started = Some(elapsed);
+            } else if lower.contains("activating extension") && lower.contains("failed") {
+                self.activation_failed = true;
+            }
+        }
+        if level == "error" {
+            self.errors.push(message.to_owned());
+            Some(LogEvent::Error(message.to_owned()))
+        } else if lower.contains("zowe") {
+            Some(LogEvent::Zowe(message.to_owned()))
+        } else {
+            None
+        }
+    }
+
+    /// The activation state at the end of a session.
+    fn activation(&self, found_log: bool) -> Activation {
+        if self.activation_failed {
+            Activation::Failed
+        } else if self.activation_started.is_some() {
+            Activation::Activated
+        } else if found_log {
+            Activation::NotActivated
+        } else {
+            Activation::Unknown
+        }
+    }
+}
+
+/// Splits a log line such as `2025-01-01 12:00:00.000 [error] message` into level and message.
+fn parse_entry(line: &str) -> Option<(&str, &str)> {
+    if !line.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let (_, rest) = line.split_once(" [")?;
+    let (level, message) = rest.split_once(']')?;
+    Some((level, message.trim()))
+}
+
+/// Reads the lines appended to a log file since the last call.
+struct LogTail {
+    path: PathBuf,
+    offset: u64,
+    partial: String,
+}
+
+impl LogTail {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            offset: 0,
+            partial: String::new(),
+        }
+    }
+
+    /// Returns the complete lines written since the last call. With `flush`, a trailing line
+    /// without a newline is returned as well.
+    fn read_lines(&mut self, flush: bool) -> Vec<String> {
+        let mut bytes = Vec::new();
+        if let Ok(mut file) = std::fs::File::open(&self.path) {
+            if file.seek(SeekFrom::Start(self.offset)).is_ok() {
+                if let Ok(read) = file.read_to_end(&mut bytes) {
+                    self.offset += read as u64;
+                }
+            }
+        }
+        self.partial.push_str(&String::from_utf8_lossy(&bytes));
+        let mut lines: Vec<String> = Vec::new();
+        while let Some(end) = self.partial.find('\n') {
+            let line: String = self.partial.drain(..=end).collect();
+            lines.push(line.trim_end().to_owned());
+        }
+        if flush && !self.partial.is_empty() {
+            lines.push(std::mem::take(&mut self.partial));
+        }
+        lines
+    }
+}
+
+/// Returns the VS Code log session folders of a sandbox.
+fn log_sessions(sandbox: &Sandbox) -> Vec<PathBuf> {
+    std::fs::read_dir(sandbox.user_data_dir().join("logs"))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the extension host logs of every window in a log session.
+fn exthost_logs(session: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(session) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with("window"))
+        .map(|e| e.path().join("exthost").join("exthost.log"))
+        .filter(|p| p.is_file())
+        .collect();
+    logs.sort();
+    logs
+}
+
+/// Formats a duration as e.g. `1m 05s` or `4.2s`.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..=59 => format!("{:.1}s", duration.as_secs_f32()),
+        60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Launches VS Code for a sandbox and stays attached until its window is closed, following the
+/// extension host logs of the new log session in the meantime.
+///
+/// # Arguments
+/// * `sandbox` - The sandbox to launch
+/// * `vsc_bin` - The Code CLI binary to launch VS Code with
+pub async fn run(sandbox: &Sandbox, vsc_bin: &Path) -> anyhow::Result<SessionSummary> {
+    let text = crate::output::text_enabled();
+    let previous = log_sessions(sandbox);
+    let started = Instant::now();
+    let mut child = sandbox.launch_attached(vsc_bin)?;
+    if text {
+        println!(
+            "   {}",
+            "Waiting for the VS Code window to be closed...".dimmed()
+        );
+    }
+
+    let mut monitor = LogMonitor::default();
+    let mut session: Option<PathBuf> = None;
+    let mut tails: BTreeMap<PathBuf, LogTail> = BTreeMap::new();
+    loop {
+        let exited = child.try_wait()?.is_some();
+        if session.is_none() {
+            session = log_sessions(sandbox)
+                .into_iter()
+                .filter(|s| !previous.contains(s))
+                .max();
+        }
+        if let Some(session) = &session {
+            for log in exthost_logs(session) {
+                tails
+                    .entry(log.clone())
+                    .or_insert_with(|| LogTail::new(log));
+            }
+        }
+        for tail in tails.values_mut() {
+            for line in tail.read_lines(exited) {
+                match monitor.observe(&line, started.elapsed()) {
+                    Some(LogEvent::Error(message)) if text => {
+                        println!("  ❌ {}", message.red())
+                    }
+                    Some(LogEvent::Zowe(message)) if text => {
+                        println!("  📝 {}", message.dimmed())
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if exited {
+            break;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    Ok(SessionSummary {
+        sandbox: sandbox.name.clone(),
+        log_session: session.and_then(|s| Some(s.file_name()?.to_string_lossy().into_owned())),
+        duration_ms: started.elapsed().as_millis() as u64,
+        activation: monitor.activation(!tails.is_empty()),
+        activation_ms: monitor.activation_started.map(|d| d.as_millis() as u64),
+        errors: monitor.errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTHOST_LOG: &str = "\
+2025-01-01 12:00:00.100 [info] Extension host with pid 4242 started
+2025-01-01 12:00:00.200 [info] ExtensionService#_doActivateExtension vscode.git, startup: true, activationEvent: '*'
+2025-01-01 12:00:01.300 [info] ExtensionService#_doActivateExtension zowe.vscode-extension-for-zowe, startup: false, activationEvent: 'onStartupFinished'
+2025-01-01 12:00:01.900 [error] Activating extension zowe.vscode-extension-for-zowe failed due to an error:
+2025-01-01 12:00:01.900 [error] Error: Cannot find module '@zowe/imperative'
+    at Module._resolveFilename (node:internal/modules/cjs/loader:1145:15)
+2025-01-01 12:00:02.000 [info] Eager extensions activated";
+
+    #[test]
+    fn tracks_activation_and_errors() {
+        let mut monitor = LogMonitor::default();
+        let events: Vec<_> = EXTHOST_LOG
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| monitor.observe(line, Duration::from_secs(i as u64)))
+            .collect();
+
+        assert_eq!(
+            events,
+            [
+                LogEvent::Zowe("ExtensionService#_doActivateExtension zowe.vscode-extension-for-zowe, startup: false, activationEvent: 'onStartupFinished'".to_owned()),
+                LogEvent::Error("Activating extension zowe.vscode-extension-for-zowe failed due to an error:".to_owned()),
+                LogEvent::Error("Error: Cannot find module '@zowe/imperative'".to_owned()),
+            ]
+        );
+        assert_eq!(monitor.activation_started, Some(Duration::from_secs(2)));
+        assert_eq!(monitor.errors.len(), 2);
+        assert_eq!(monitor.activation(true), Activation::Failed);
+
+        // Without the failure, an activation that started counts as activated
+        let mut monitor = LogMonitor::default();
+        for line in EXTHOST_LOG.lines().take(3) {
+            monitor.observe(line, Duration::ZERO);
+        }
+        assert_eq!(monitor.activation(true), Activation::Activated);
+        assert_eq!(
+            LogMonitor::default().activation(true),
+            Activation::NotActivated
+        );
+        assert_eq!(LogMonitor::default().activation(false), Activation::Unknown);
+    }
+
+    #[test]
+    fn tails_new_log_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exthost.log");
+        std::fs::write(&path, "first\nsec").unwrap();
+        let mut tail = LogTail::new(path.clone());
+        assert_eq!(tail.read_lines(false), ["first"]);
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        std::io::Write::write_all(&mut file, b"ond\nthird").unwrap();
+        assert_eq!(tail.read_lines(false), ["second"]);
+        assert!(tail.read_lines(false).is_empty());
+        assert_eq!(tail.read_lines(true), ["third"]);
+        assert_eq!(format_duration(Duration::from_millis(4200)), "4.2s");
+        assert_eq!(format_duration(Duration::from_secs(65)), "1m 05s");
+    }
+}
@@ -0,0 +1,363 @@
+//! Saving and restoring the state of a sandbox (`zedc sandbox snapshot|restore`).
+//!
+//! A snapshot is a zip file with the VS Code user data (settings, `globalStorage` with the Zowe
+//! Explorer favorites, history and persisted profiles), the workspace folder including `.zowe`, and
+//! a `snapshot.json` with the launch options and the extensions that were installed. Logs, the
+//! Electron caches and Zowe CLI plugins are left out, and so are the extensions unless they are
+//! requested, so that a snapshot stays small enough to share. Restoring a snapshot keeps the logs
+//! and Zowe CLI plugins of the target sandbox.
+
+use super::{InstalledExtension, LaunchOptions, Sandbox};
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// The name of the metadata file in a snapshot.
+const SNAPSHOT_FILE: &str = "snapshot.json";
+
+/// Folders in `user-data` that only hold logs, caches or lock files.
+const USER_DATA_EXCLUDES: [&str; 17] = [
+    "logs",
+    "CachedData",
+    "CachedExtensionVSIXs",
+    "CachedProfilesData",
+    "Cache",
+    "Code Cache",
+    "GPUCache",
+    "DawnCache",
+    "DawnGraphiteCache",
+    "DawnWebGPUCache",
+    "Crashpad",
+    "Service Worker",
+    "blob_storage",
+    "Shared Dictionary",
+    "code.lock",
+    "SingletonLock",
+    "SingletonCookie",
+];
+
+/// Folders in the sandbox `.zowe` folder that are left out of a snapshot and kept on restore: the
+/// Imperative logs and the Zowe CLI plugins installed with `--cli-plugin`.
+const ZOWE_EXCLUDES: [&str; 2] = ["logs", "plugins"];
+
+/// The top-level folders of a snapshot and the sandbox folders they are restored to.
+fn folders(sandbox: &Sandbox) -> [(&'static str, PathBuf); 3] {
+    [
+        ("user-data", sandbox.user_data_dir()),
+        ("workspace", sandbox.workspace_dir()),
+        ("extensions", sandbox.extensions_dir()),
+    ]
+}
+
+/// The metadata stored in a snapshot.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SnapshotInfo {
+    /// The sandbox the snapshot was taken from.
+    pub sandbox: String,
+    /// Unix timestamp of when the snapshot was taken.
+    pub created: u64,
+    pub zedc_version: String,
+    pub vscode_version: Option<String>,
+    /// The extensions installed in the sandbox when the snapshot was taken.
+    pub extensions: Vec<InstalledExtension>,
+    /// Whether the snapshot contains the extensions folder.
+    pub includes_extensions: bool,
+    pub launch: LaunchOptions,
+}
+
+/// Writes a snapshot of a sandbox to a zip file. Returns the metadata and the number of files.
+///
+/// # Arguments
+/// * `sandbox` - The sandbox to take the snapshot of
+/// * `path` - The zip file to create
+/// * `include_extensions` - Whether to include the installed extensions
+pub fn create(
+    sandbox: &Sandbox,
+    path: &Path,
+    include_extensions: bool,
+) -> anyhow::Result<(SnapshotInfo, usize)> {
+    let metadata = sandbox.metadata();
+    let info = SnapshotInfo {
+        sandbox: sandbox.name.clone(),
+        created: crate::util::unix_now(),
+        zedc_version: env!("CARGO_PKG_VERSION").to_owned(),
+        vscode_version: metadata.vscode_version,
+        extensions: sandbox.installed_extensions(),
+        includes_extensions: include_extensions,
+        launch: metadata.launch,
+    };
+
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file(SNAPSHOT_FILE, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&info)?)?;
+
+    let mut count = 0;
+    for (prefix, dir) in folders(sandbox) {
+        if prefix == "extensions" && !include_extensions {
+            continue;
+        }
+        for file in files(&dir, prefix == "user-data") {
+            let relative = file
+                .strip_prefix(&dir)
+                .unwrap_or(&file)
+                .to_string_lossy()
+                .replace('\\', "/");
+            if prefix == "workspace"
+                && ZOWE_EXCLUDES
+                    .iter()
+                    .any(|name| relative.starts_with(&format!(".zowe/{}/", name)))
+            {
+                continue;
+            }
+            let contents = std::fs::read(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            zip.start_file(format!("{}/{}", prefix, relative), options)?;
+            zip.write_all(&contents)?;
+            count += 1;
+        }
+    }
+    zip.finish()?;
+    Ok((info, count))
+}
+
+/// Returns the regular files below a directory, skipping symbolic links and sockets. With
+/// `skip_caches`, the top-level entries in [`USER_DATA_EXCLUDES`] are skipped as well.
+fn files(dir: &Path, skip_caches: bool) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return found;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name();
+        if skip_caches && USER_DATA_EXCLUDES.contains(&name.to_string_lossy().as_ref()) {
+            continue;
+        }
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => found.extend(files(&entry.path(), false)),
+            Ok(t) if t.is_file() => found.push(entry.path()),
+            _ => {}
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Reads the metadata of a snapshot without extracting it.
+pub fn read_info(path: &Path) -> anyhow::Result<SnapshotInfo> {
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("{} is not a valid snapshot", path.display()))?;
+    let mut entry = archive
+        .by_name(SNAPSHOT_FILE)
+        .with_context(|| format!("{} is not a zedc sandbox snapshot", path.display()))?;
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents)?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid {} in {}", SNAPSHOT_FILE, path.display()))
+}
+
+/// Replaces the user data and workspace of a sandbox with the contents of a snapshot, creating the
+/// sandbox if needed. The extensions are only replaced if the snapshot contains them, the logs and
+/// Zowe CLI plugins in `.zowe` are kept, and the launch options of the snapshot are recorded in the
+/// sandbox. The snapshot is checked before anything is removed.
+///
+/// # Arguments
+/// * `sandbox` - The sandbox to restore the snapshot into
+/// * `path` - The snapshot zip file
+pub fn restore(sandbox: &Sandbox, path: &Path) -> anyhow::Result<SnapshotInfo> {
+    let info = read_info(path)?;
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file)?;
+
+    let folders = folders(sandbox);
+    let mut targets = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)?;
+        let Some(name) = entry.enclosed_name() else {
+            bail!(
+                "{} contains an invalid path: {}",
+                path.display(),
+                entry.name()
+            );
+        };
+        if entry.is_dir() || name == Path::new(SNAPSHOT_FILE) {
+            continue;
+        }
+        let target = folders.iter().find_map(|(prefix, dir)| {
+            let relative = name.strip_prefix(prefix).ok()?;
+            Some(dir.join(relative))
+        });
+        if let Some(target) = target {
+            targets.push((i, target));
+        }
+    }
+
+    for (prefix, dir) in &folders {
+        match *prefix {
+            "extensions" if !info.includes_extensions => {}
+            "workspace" => clear_dir(dir, &[".zowe"])
+                .and_then(|_| clear_dir(&sandbox.zowe_dir(), &ZOWE_EXCLUDES))?,
+            _ => clear_dir(dir, &[])?,
+        }
+    }
+    sandbox.prepare()?;
+
+    for (i, target) in targets {
+        let mut entry = archive.by_index(i)?;
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut out = std::fs::File::create(&target)
+            .with_context(|| format!("Failed to write {}", target.display()))?;
+        std::io::copy(&mut entry, &mut out)?;
+    }
+
+    let mut metadata = sandbox.metadata();
+    metadata.launch = info.launch.clone();
+    metadata.last_launch = None;
+    sandbox.save_metadata(&metadata)?;
+    Ok(info)
+}
+
+/// Removes the contents of a directory, except for the top-level entries in `keep`.
+fn clear_dir(dir: &Path, keep: &[&str]) -> anyhow::Result<()> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        if keep.contains(&entry.file_name().to_string_lossy().as_ref()) {
+            continue;
+        }
+        let path = entry.path();
+        let removed = match entry.file_type() {
+            Ok(t) if t.is_dir() => std::fs::remove_dir_all(&path),
+            _ => std::fs::remove_file(&path),
+        };
+        removed.with_context(|| format!("Failed to remove {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: PathBuf, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn snapshots_and_restores_sandbox_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = Sandbox {
+            name: "bug".to_owned(),
+            root: dir.path().join("bug"),
+        };
+        source.prepare().unwrap();
+        let user_data = source.user_data_dir();
+        let state = "User/globalStorage/zowe.vscode-extension-for-zowe/favorites.json";
+        write(user_data.join(state), "[\"IBMUSER.TEST\"]");
+        write(user_data.join("User/settings.json"), "{}");
+        write(user_data.join("logs/20250101T100000/main.log"), "log");
+        write(user_data.join("CachedData/abc/chrome.js"), "cache");
+        write(source.zowe_dir().join("zowe.config.json"), "{}");
+        write(source.zowe_dir().join("logs/zowe.log"), "log");
+        write(
+            source
+                .extensions_dir()
+                .join("zowe.vscode-extension-for-zowe-3.2.0/package.json"),
+            "{}",
+        );
+        let mut metadata = source.metadata();
+        metadata.launch.locale = Some("de".to_owned());
+        source.save_metadata(&metadata).unwrap();
+
+        let zip_path = dir.path().join("bug.zip");
+        let (info, count) = create(&source, &zip_path, false).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(info.extensions.len(), 1);
+        assert!(!info.includes_extensions);
+
+        let target = Sandbox {
+            name: "repro".to_owned(),
+            root: dir.path().join("repro"),
+        };
+        write(target.workspace_dir().join("stale.txt"), "old");
+        write(target.zowe_dir().join("stale.json"), "old");
+        write(target.zowe_dir().join("logs/imperative.log"), "log");
+        write(
+            target
+                .zowe_dir()
+                .join("plugins/installed/lib/node_modules/@zowe/zos-ftp-for-zowe-cli/package.json"),
+            "{}",
+        );
+        write(
+            target
+                .extensions_dir()
+                .join("zowe.vscode-extension-for-zowe-3.1.0/package.json"),
+            "{}",
+        );
+        let restored = restore(&target, &zip_path).unwrap();
+        assert_eq!(restored.sandbox, "bug");
+        assert_eq!(
+            std::fs::read_to_string(target.user_data_dir().join(state)).unwrap(),
+            "[\"IBMUSER.TEST\"]"
+        );
+        assert!(target.zowe_dir().join("zowe.config.json").is_file());
+        assert!(!target.user_data_dir().join("CachedData").exists());
+        assert!(!target.workspace_dir().join("stale.txt").exists());
+        assert!(!target.zowe_dir().join("stale.json").exists());
+        // The logs and Zowe CLI plugins of the target sandbox are kept, and not replaced by the
+        // ones of the source
+        assert!(!target.zowe_dir().join("logs/zowe.log").exists());
+        assert!(target.zowe_dir().join("logs/imperative.log").is_file());
+        assert!(target
+            .zowe_dir()
+            .join("plugins/installed/lib/node_modules/@zowe/zos-ftp-for-zowe-cli/package.json")
+            .is_file());
+        // Extensions are kept when the snapshot does not include them
+        assert_eq!(target.installed_extensions()[0].version, "3.1.0");
+        assert_eq!(target.metadata().launch.locale.as_deref(), Some("de"));
+
+        // With the extensions included, they replace the installed ones
+        create(&source, &zip_path, true).unwrap();
+        restore(&target, &zip_path).unwrap();
+        assert_eq!(target.installed_extensions()[0].version, "3.2.0");
+    }
+
+    #[test]
+    fn refuses_invalid_snapshots_before_removing_anything() {
+        use std::io::Write;
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("evil.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&zip_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file(SNAPSHOT_FILE, options).unwrap();
+        zip.write_all(
+            br#"{ "sandbox": "bug", "created": 0, "zedc_version": "0.4.0", "extensions": [], "includes_extensions": false, "launch": {} }"#,
+        )
+        .unwrap();
+        zip.start_file("workspace/../../outside.txt", options)
+            .unwrap();
+        zip.write_all(b"x").unwrap();
+        zip.finish().unwrap();
+
+        let target = Sandbox {
+            name: "repro".to_owned(),
+            root: dir.path().join("repro"),
+        };
+        write(target.zowe_dir().join("zowe.config.json"), "{}");
+        assert!(restore(&target, &zip_path).is_err());
+        assert!(target.zowe_dir().join("zowe.config.json").is_file());
+        assert!(!dir.path().join("outside.txt").exists());
+    }
+}
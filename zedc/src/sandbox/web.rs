@@ -0,0 +1,139 @@
+//! Serving a sandbox as VS Code for the Web (`--web`).
+//!
+//! The `code-server` binary of a VS Code server build is started on localhost with the sandbox user
+//! data, extensions and workspace, and a connection token that is generated for each run, so that
+//! only the browser opened from the printed URL can connect.
+
+use super::{launch, Sandbox};
+use anyhow::Context;
+use owo_colors::OwoColorize;
+use std::{net::TcpListener, path::Path, process::Stdio};
+
+/// The address the server listens on.
+const HOST: &str = "127.0.0.1";
+
+/// Generates a random connection token (32 hex characters) from the random number generator of the
+/// OS.
+fn connection_token() -> anyhow::Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).context("Failed to generate a connection token")?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Returns a free port on localhost.
+fn free_port() -> anyhow::Result<u16> {
+    Ok(TcpListener::bind((HOST, 0))?.local_addr()?.port())
+}
+
+/// Returns the URL that opens the sandbox workspace in the browser and passes the token.
+fn url(port: u16, token: &str, workspace: &Path) -> String {
+    let folder = workspace.to_string_lossy().replace('\\', "/");
+    let folder = if folder.starts_with('/') {
+        folder
+    } else {
+        format!("/{}", folder)
+    };
+    format!(
+        "http://{}:{}/?tkn={}&folder={}",
+        HOST,
+        port,
+        token,
+        folder.replace(' ', "%20")
+    )
+}
+
+/// Serves a sandbox with the given `code-server` binary until the server is stopped (Ctrl+C).
+///
+/// The recorded launch options are applied like for the desktop sandbox, except that settings are
+/// merged into the machine `settings.json` of the server, since user settings are kept by the browser.
+///
+/// # Arguments
+/// * `sandbox` - The sandbox to serve
+/// * `server_bin` - The `code-server` binary of a VS Code server build
+pub async fn serve(sandbox: &Sandbox, server_bin: &Path) -> anyhow::Result<()> {
+    let text = crate::output::text_enabled();
+    sandbox.prepare()?;
+    let options = sandbox.metadata().launch;
+    if !options.settings.is_empty() {
+        launch::merge_settings(
+            &sandbox
+                .user_data_dir()
+                .join("Machine")
+                .join("settings.json"),
+            &options.settings,
+        )?;
+    }
+
+    let port = free_port()?;
+    let token = connection_token()?;
+    let mut child = sandbox
+        .code_command(server_bin)
+        .args(["--host", HOST, "--port"])
+        .arg(port.to_string())
+        .arg("--connection-token")
+        .arg(&token)
+        .arg("--accept-server-license-terms")
+        .arg("--default-folder")
+        .arg(sandbox.workspace_dir())
+        .envs(options.process_env(&sandbox.root))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "Failed to start VS Code server for sandbox '{}'",
+                sandbox.name
+            )
+        })?;
+
+    let mut metadata = sandbox.metadata();
+    metadata.last_launch = Some(crate::util::unix_now());
+    sandbox.save_metadata(&metadata)?;
+
+    let url = url(port, &token, &sandbox.workspace_dir());
+    if crate::output::json_enabled() {
+        crate::output::emit_json(&serde_json::json!({
+            "event": "serving",
+            "sandbox": sandbox.name,
+            "url": url,
+        }));
+    } else if text {
+        println!(
+            "🌐 Serving VS Code for the Web (sandbox: {})",
+            sandbox.name.bold()
+        );
+        println!("   {}", url.bold());
+        println!("   {}", "Press Ctrl+C to stop the server.".dimmed());
+    }
+
+    let status = tokio::task::spawn_blocking(move || child.wait()).await??;
+    if text && !status.success() {
+        println!("⚠️  VS Code server exited with {}", status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_url_with_token() {
+        let token = connection_token().unwrap();
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, connection_token().unwrap());
+        assert_eq!(
+            url(
+                8000,
+                "abc",
+                Path::new("/home/me/zedc_data/sandboxes/my sb/workspace")
+            ),
+            "http://127.0.0.1:8000/?tkn=abc&folder=/home/me/zedc_data/sandboxes/my%20sb/workspace"
+        );
+        assert_eq!(
+            url(8000, "abc", Path::new("C:\\zedc\\workspace")),
+            "http://127.0.0.1:8000/?tkn=abc&folder=/C:/zedc/workspace"
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! Zowe team configuration (`zowe.config.json`) templates for the sandbox `.zowe` directory.
+//!
+//! Templates are filled in with the host, port and user from `--zowe-host`, `--zowe-port` and
+//! `--zowe-user`, or the `ZOWE_OPT_HOST`, `ZOWE_OPT_PORT` and `ZOWE_OPT_USER` environment variables.
+//! Passwords and tokens are never written: the profiles mark them as secure, and the values are
+//! read from the environment (e.g. `ZOWE_OPT_PASSWORD`) that VS Code inherits from zedc.
+//!
+//! The `mock` template connects to `zedc mock zosmf` and `zedc mock ftp` on `localhost` without TLS.
+
+use crate::cmd::SandboxConfig;
+use anyhow::{bail, Context};
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+/// The names of the built-in templates.
+pub const TEMPLATES: [&str; 5] = ["zosmf", "ftp", "ssh", "base", "mock"];
+
+/// Properties that hold secrets and must never be stored in plain text.
+const SECRET_PROPERTIES: [&str; 3] = ["password", "tokenValue", "passphrase"];
+
+/// The host, port and user to fill into a template.
+#[derive(Default, Debug)]
+pub struct Connection {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub user: Option<String>,
+}
+
+impl Connection {
+    /// Reads the connection details from the command line, falling back to the environment.
+    pub fn from_config(config: &SandboxConfig) -> anyhow::Result<Self> {
+        let env = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let port = match (config.zowe_port, env("ZOWE_OPT_PORT")) {
+            (Some(port), _) => Some(port),
+            (None, Some(port)) => Some(
+                port.trim()
+                    .parse()
+                    .with_context(|| format!("Invalid ZOWE_OPT_PORT '{}'", port))?,
+            ),
+            (None, None) => None,
+        };
+        Ok(Self {
+            host: config.zowe_host.clone().or_else(|| env("ZOWE_OPT_HOST")),
+            port,
+            user: config.zowe_user.clone().or_else(|| env("ZOWE_OPT_USER")),
+        })
+    }
+}
+
+/// Returns the `type` and default port of the service profile in a template.
+fn service_profile(template: &str) -> Option<(&'static str, u16)> {
+    match template {
+        "zosmf" => Some(("zosmf", 443)),
+        "ftp" => Some(("zftp", 21)),
+        "ssh" => Some(("ssh", 22)),
+        "mock" => Some(("zosmf", crate::mock::zosmf::DEFAULT_PORT)),
+        _ => None,
+    }
+}
+
+/// Builds a team configuration from a built-in template.
+///
+/// # Arguments
+/// * `template` - One of [`TEMPLATES`]
+/// * `connection` - The host, port and user to fill in
+pub fn template(template: &str, connection: &Connection) -> anyhow::Result<Value> {
+    if !TEMPLATES.contains(&template) {
+        bail!(
+            "Unknown Zowe config template '{}'. Available templates: {}",
+            template,
+            TEMPLATES.join(", ")
+        );
+    }
+
+    let mock = template == "mock";
+    let mut base_properties = Map::new();
+    match &connection.host {
+        Some(host) => {
+            base_properties.insert("host".to_owned(), json!(host));
+        }
+        None if mock => {
+            base_properties.insert("host".to_owned(), json!("localhost"));
+        }
+        None => {}
+    }
+    base_properties.insert("rejectUnauthorized".to_owned(), json!(!mock));
+    let secure = match &connection.user {
+        Some(user) => {
+            base_properties.insert("user".to_owned(), json!(user));
+            json!(["password"])
+        }
+        None => json!(["user", "password"]),
+    };
+
+    let mut profiles = Map::new();
+    let mut defaults = Map::new();
+    if let Some((ty, default_port)) = service_profile(template) {
+        let mut properties = Map::new();
+        properties.insert(
+            "port".to_owned(),
+            json!(connection.port.unwrap_or(default_port)),
+        );
+        if mock {
+            properties.insert("protocol".to_owned(), json!("http"));
+        }
+        profiles.insert(
+            template.to_owned(),
+            json!({ "type": ty, "properties": properties, "secure": [] }),
+        );
+        defaults.insert(ty.to_owned(), json!(template));
+    }
+    if mock {
+        profiles.insert(
+            "mock_ftp".to_owned(),
+            json!({
+                "type": "zftp",
+                "properties": { "port": crate::mock::ftp::DEFAULT_PORT, "secureFtp": false },
+                "secure": []
+            }),
+        );
+        defaults.insert("zftp".to_owned(), json!("mock_ftp"));
+    }
+    profiles.insert(
+        "base".to_owned(),
+        json!({ "type": "base", "properties": base_properties, "secure": secure }),
+    );
+    defaults.insert("base".to_owned(), json!("base"));
+
+    Ok(json!({
+        "profiles": profiles,
+        "defaults": defaults,
+        "autoStore": false
+    }))
+}
+
+/// Resolves `--zowe-config` to a team configuration: a path to an existing file, or the name of a
+/// built-in template. Returns `None` if the option was not given.
+pub fn from_config(config: &SandboxConfig) -> anyhow::Result<Option<Value>> {
+    let Some(source) = &config.zowe_config else {
+        return Ok(None);
+    };
+    let path = Path::new(source);
+    let value = if path.is_file() {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        crate::util::parse_jsonc(&text)
+            .with_context(|| format!("Invalid Zowe config {}", path.display()))?
+    } else {
+        template(source, &Connection::from_config(config)?)?
+    };
+
+    let errors = validate(&value);
+    if !errors.is_empty() {
+        bail!(
+            "The Zowe config from '{}' is not valid:\n  - {}",
+            source,
+            errors.join("\n  - ")
+        );
+    }
+    Ok(Some(value))
+}
+
+/// Checks a team configuration against the structure of the Zowe team config schema, and rejects
+/// secrets stored in plain text. Returns a list of problems (empty if the config is valid).
+pub fn validate(config: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    let Some(root) = config.as_object() else {
+        return vec!["The config must be a JSON object".to_owned()];
+    };
+    for key in root.keys() {
+        if !matches!(
+            key.as_str(),
+            "$schema" | "profiles" | "defaults" | "autoStore" | "plugins"
+        ) {
+            errors.push(format!("Unknown top-level property '{}'", key));
+        }
+    }
+    if root.get("autoStore").is_some_and(|v| !v.is_boolean()) {
+        errors.push("'autoStore' must be a boolean".to_owned());
+    }
+
+    let mut names = Vec::new();
+    match root.get("profiles") {
+        Some(Value::Object(profiles)) => validate_profiles(profiles, "", &mut names, &mut errors),
+        Some(_) => errors.push("'profiles' must be an object".to_owned()),
+        None => errors.push("Missing 'profiles'".to_owned()),
+    }
+
+    match root.get("defaults") {
+        None => {}
+        Some(Value::Object(defaults)) => {
+            for (ty, name) in defaults {
+                match name.as_str() {
+                    Some(name) if names.iter().any(|n| n == name) => {}
+                    Some(name) => {
+                        errors.push(format!("Default {} profile '{}' does not exist", ty, name))
+                    }
+                    None => errors.push(format!("Default for '{}' must be a string", ty)),
+                }
+            }
+        }
+        Some(_) => errors.push("'defaults' must be an object".to_owned()),
+    }
+    errors
+}
+
+/// Validates a `profiles` object, collecting the full names of its (nested) profiles.
+fn validate_profiles(
+    profiles: &Map<String, Value>,
+    prefix: &str,
+    names: &mut Vec<String>,
+    errors: &mut Vec<String>,
+) {
+    for (name, profile) in profiles {
+        let full_name = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        names.push(full_name.clone());
+        let Some(profile) = profile.as_object() else {
+            errors.push(format!("Profile '{}' must be an object", full_name));
+            continue;
+        };
+
+        for key in profile.keys() {
+            if !matches!(key.as_str(), "type" | "properties" | "secure" | "profiles") {
+                errors.push(format!(
+                    "Profile '{}' has unknown property '{}'",
+                    full_name, key
+                ));
+            }
+        }
+        if profile.get("type").is_some_and(|t| !t.is_string()) {
+            errors.push(format!("Profile '{}': 'type' must be a string", full_name));
+        }
+
+        let secure: Vec<&str> = match profile.get("secure") {
+            None => Vec::new(),
+            Some(Value::Array(items)) if items.iter().all(Value::is_string) => {
+                items.iter().filter_map(Value::as_str).collect()
+            }
+            Some(_) => {
+                errors.push(format!(
+                    "Profile '{}': 'secure' must be a list of property names",
+                    full_name
+                ));
+                Vec::new()
+            }
+        };
+
+        match profile.get("properties") {
+            None => {}
+            Some(Value::Object(properties)) => {
+                for (key, value) in properties {
+                    if secure.contains(&key.as_str()) || SECRET_PROPERTIES.contains(&key.as_str()) {
+                        errors.push(format!(
+                            "Profile '{}' stores '{}' in plain text; mark it as secure and pass it through the environment instead",
+                            full_name, key
+                        ));
+                    }
+                    let valid = match key.as_str() {
+                        "host" | "user" | "protocol" | "basePath" => value.is_string(),
+                        "port" => value
+                            .as_u64()
+                            .is_some_and(|p| (1..=u16::MAX as u64).contains(&p)),
+                        "rejectUnauthorized" | "secureFtp" => value.is_boolean(),
+                        _ => true,
+                    };
+                    if !valid {
+                        errors.push(format!(
+                            "Profile '{}': invalid value for '{}': {}",
+                            full_name, key, value
+                        ));
+                    }
+                }
+            }
+            Some(_) => errors.push(format!(
+                "Profile '{}': 'properties' must be an object",
+                full_name
+            )),
+        }
+
+        match profile.get("profiles") {
+            None => {}
+            Some(Value::Object(nested)) => validate_profiles(nested, &full_name, names, errors),
+            Some(_) => errors.push(format!(
+                "Profile '{}': 'profiles' must be an object",
+                full_name
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_in_templates() {
+        let connection = Connection {
+            host: Some("lpar1.example.com".to_owned()),
+            port: None,
+            user: Some("ibmuser".to_owned()),
+        };
+        for name in TEMPLATES {
+            let config = template(name, &connection).unwrap();
+            assert_eq!(validate(&config), Vec::<String>::new(), "{}", name);
+            assert_eq!(
+                config["profiles"]["base"]["properties"]["host"],
+                "lpar1.example.com"
+            );
+            assert_eq!(config["profiles"]["base"]["secure"], json!(["password"]));
+        }
+
+        let config = template("ftp", &Connection::default()).unwrap();
+        assert_eq!(config["profiles"]["ftp"]["type"], "zftp");
+        assert_eq!(config["profiles"]["ftp"]["properties"]["port"], 21);
+        assert_eq!(config["defaults"]["zftp"], "ftp");
+        assert_eq!(
+            config["profiles"]["base"]["secure"],
+            json!(["user", "password"])
+        );
+        assert!(template("rse", &Connection::default()).is_err());
+
+        let config = template("mock", &Connection::default()).unwrap();
+        assert_eq!(config["profiles"]["mock"]["type"], "zosmf");
+        assert_eq!(config["profiles"]["mock"]["properties"]["port"], 10443);
+        assert_eq!(config["profiles"]["mock"]["properties"]["protocol"], "http");
+        assert_eq!(
+            config["profiles"]["base"]["properties"]["host"],
+            "localhost"
+        );
+        assert_eq!(
+            config["profiles"]["base"]["properties"]["rejectUnauthorized"],
+            false
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_configs() {
+        let config = json!({
+            "profiles": {
+                "lpar1": {
+                    "properties": { "host": "lpar1", "password": "hunter2" },
+                    "profiles": {
+                        "zosmf": { "type": "zosmf", "properties": { "port": 70000 } }
+                    }
+                }
+            },
+            "defaults": { "zosmf": "lpar1.zosmf", "tso": "lpar1.tso" },
+            "autoStore": "yes"
+        });
+        let errors = validate(&config);
+        assert_eq!(errors.len(), 4, "{:#?}", errors);
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("'password' in plain text")));
+        assert!(errors.iter().any(|e| e.contains("'port': 70000")));
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("'lpar1.tso' does not exist")));
+        assert!(errors.iter().any(|e| e.contains("autoStore")));
+    }
+}
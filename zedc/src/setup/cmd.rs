@@ -37,7 +37,7 @@ pub async fn handle_cmd(reference: Option<String>) -> anyhow::Result<()> {
                     bail!("There are changes in your working tree. Please commit or discard them before continuing.");
                 }
             }
-            Err(_) => todo!(),
+            Err(e) => bail!("Could not run Git to check for changes: {}", e),
         }
         match Command::new("git")
             .arg("checkout")
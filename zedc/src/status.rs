@@ -34,7 +34,7 @@ impl EnvironmentStatus {
             zowe_cli_version: None,
             vscode_version: None,
             git_status: None,
-            workspace_path: workspace_path,
+            workspace_path,
             ze_dir: Some(ze_dir.clone()),
             dependencies_installed: false,
             env_vars: Vec::new(),
@@ -61,7 +61,7 @@ impl EnvironmentStatus {
     fn check_dependencies(&mut self) -> Result<()> {
         match &self.ze_dir {
             Some(ze_dir) => {
-                self.dependencies_installed = pm::check_dependencies(&ze_dir);
+                self.dependencies_installed = pm::check_dependencies(ze_dir);
             }
             None => {
                 eprintln!("Could not find a repo folder containing package.json.");
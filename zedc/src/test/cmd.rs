@@ -1,7 +1,9 @@
 //! Command module for handling `test` commands.
 
+use crate::cmd::{SandboxConfig, TestConfig, VsCodeConfig};
 use crate::output::{self, exit};
-use crate::test::{coverage, ghr, local};
+use crate::test::{coverage, ghr, local, matrix, InstallReport};
+use anyhow::bail;
 use clap::Subcommand;
 use owo_colors::OwoColorize;
 
@@ -21,10 +23,24 @@ pub enum Commands {
     },
     #[command(
         name = "local",
-        about = "Provide multiple .vsix files containing extensions",
+        about = "Provide .vsix files, directories, globs or URLs containing extensions",
         alias = "l"
     )]
-    Local { files: Vec<String> },
+    Local {
+        files: Vec<String>,
+        #[arg(
+            long,
+            help = "Build the extension packages of the current tree and install them (skipped for packages whose sources are unchanged)"
+        )]
+        build: bool,
+        #[arg(
+            long = "package",
+            value_name = "NAME",
+            requires = "build",
+            help = "Only build the given package, by folder or package name (repeatable)"
+        )]
+        packages: Vec<String>,
+    },
     #[command(
         name = "coverage",
         about = "Run unit tests and compare patch coverage with main branch",
@@ -47,34 +63,61 @@ pub enum Commands {
 /// Handles the logic for the `zedc test [t]` command.
 ///
 /// # Arguments
-/// * `install_cli` - (optional) Installs the given version of Zowe CLI, if provided.
-/// * `vsc_version` - (optional) The version of VS Code to install (default: `latest`)
+/// * `config` - Options shared by all `test` subcommands (Zowe CLI, VS Code version and sandbox)
 /// * `cmd` - Any subcommands passed to the `test` command
-pub async fn handle_cmd(
-    install_cli: Option<String>,
-    vsc_version: Option<String>,
-    cmd: Commands,
-) -> anyhow::Result<i32> {
+pub async fn handle_cmd(config: TestConfig, cmd: Commands) -> anyhow::Result<i32> {
+    let TestConfig {
+        vscode,
+        matrix,
+        smoke,
+        sandbox,
+    } = config;
     let json = output::json_enabled();
     if !json {
         println!("{}\n", "zedc test".bold().blue());
     }
+    let use_matrix = matrix::requested(&vscode, matrix, smoke);
+    if use_matrix && sandbox.wait {
+        bail!("--wait cannot be used when testing more than one VS Code version.");
+    }
+    if use_matrix && sandbox.inspect.is_some() {
+        bail!("--inspect cannot be used when testing more than one VS Code version.");
+    }
+    if use_matrix && vscode.web {
+        bail!("--web cannot be used when testing more than one VS Code version.");
+    }
 
     // Handle any subcommands.
-    let (code, action_command) = match cmd {
+    let (code, action_result) = match cmd {
         Commands::GhRepo { references } => {
             let crab = octocrab::instance();
-            ghr::setup(references, vsc_version, &crab).await?;
-            (exit::SUCCESS, Some("test gh-repo"))
+            if use_matrix {
+                let files = ghr::resolve(references, &crab).await?;
+                run_matrix("test gh-repo", &vscode, matrix, smoke, files, &sandbox).await?
+            } else {
+                let report = ghr::setup(references, &vscode, &sandbox, &crab).await?;
+                report_result("test gh-repo", &report)?
+            }
         }
-        Commands::Local { files } => {
-            match local::setup(vsc_version, files).await {
-                Ok(_) => {}
-                Err(_e) => {
-                    return Ok(exit::SUCCESS);
-                }
-            };
-            (exit::SUCCESS, Some("test local"))
+        Commands::Local {
+            mut files,
+            build,
+            packages,
+        } => {
+            if build {
+                let mut built = local::build::build_packages(&packages)?;
+                built.append(&mut files);
+                files = built;
+            } else if files.is_empty() {
+                bail!("Provide the .vsix files to install, or use --build to build them.");
+            }
+            if use_matrix {
+                let files = local::resolve(files).await;
+                run_matrix("test local", &vscode, matrix, smoke, files, &sandbox).await?
+            } else {
+                let report = local::setup(&vscode, files, &sandbox).await?;
+                report_result("test local", &report)?
+            }
         }
         Commands::Coverage {
             verbose,
@@ -87,26 +130,71 @@ pub async fn handle_cmd(
     };
 
     if code != exit::SUCCESS {
-        return Ok(code);
-    }
-
-    // Install Zowe CLI if a version was provided.
-    if install_cli.is_some() {
-        let ver = install_cli.unwrap();
-        if output::text_enabled() {
-            println!(
-                "💿 {}",
-                format!("Installing Zowe CLI (version: {})...", ver).blue()
-            );
+        if json {
+            if let Some((command, result)) = action_result {
+                emit_result(command, false, result);
+            }
         }
-        super::install_cli(ver)?;
+        return Ok(code);
     }
 
     if json {
-        if let Some(command) = action_command {
-            output::emit_action_result(command, true);
+        if let Some((command, result)) = action_result {
+            emit_result(command, true, result);
         }
     }
 
     Ok(code)
 }
+
+/// Installs the given extensions into one sandbox per requested VS Code version.
+/// Returns the exit code and the per-version results.
+async fn run_matrix(
+    command: &'static str,
+    vscode: &VsCodeConfig,
+    use_engines: bool,
+    smoke: bool,
+    files: Vec<String>,
+    sandbox: &SandboxConfig,
+) -> anyhow::Result<(i32, Option<(&'static str, serde_json::Value)>)> {
+    if files.is_empty() {
+        bail!("No valid .vsix files provided.".red());
+    }
+    let entries = matrix::entries(vscode, use_engines, &files)?;
+    let results = matrix::run(entries, files, sandbox, smoke).await?;
+    if output::text_enabled() {
+        matrix::print_results(&results);
+    }
+    let code = if results.iter().all(|r| r.ok()) {
+        exit::SUCCESS
+    } else {
+        exit::FAILURE
+    };
+    Ok((
+        code,
+        Some((command, serde_json::json!({ "matrix": results }))),
+    ))
+}
+
+/// Returns the exit code and JSON result for a single sandbox install.
+fn report_result(
+    command: &'static str,
+    report: &InstallReport,
+) -> anyhow::Result<(i32, Option<(&'static str, serde_json::Value)>)> {
+    Ok((
+        report.exit_code(),
+        Some((command, serde_json::to_value(report)?)),
+    ))
+}
+
+/// Emits the JSON envelope for a `test` action, merging in the fields of `result`.
+fn emit_result(command: &str, ok: bool, result: serde_json::Value) {
+    let mut body = serde_json::json!({
+        "ok": ok,
+        "command": command,
+    });
+    if let (Some(body), serde_json::Value::Object(fields)) = (body.as_object_mut(), result) {
+        body.extend(fields);
+    }
+    output::emit_json(&body);
+}
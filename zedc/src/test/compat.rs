@@ -0,0 +1,248 @@
+//! Module for checking extensions against the VS Code version they are installed into.
+//!
+//! The `engines.vscode` requirement and `extensionDependencies` of each `.vsix` are read before
+//! installing, so that an extension VS Code would refuse to activate is reported instead of being
+//! installed silently.
+
+use crate::cmd::VsCodeConfig;
+use crate::doctor::{self, Version};
+use crate::sandbox::InstalledExtension;
+use crate::test::verify::{ExtensionInstall, InstallStatus};
+use crate::vsix::VsixManifest;
+use anyhow::bail;
+use std::path::Path;
+
+/// The `--vsc-version` value that picks a VS Code version compatible with every extension.
+pub const COMPATIBLE: &str = "compatible";
+
+/// Returns whether a VS Code version satisfies an `engines.vscode` requirement, or `None` if
+/// either of them could not be parsed.
+pub fn satisfies(vscode_version: &str, requirement: &str) -> Option<bool> {
+    let requirement = requirement.trim();
+    if requirement == "*" {
+        return Some(true);
+    }
+    let version = Version::parse(vscode_version)?;
+    let (op, required) = doctor::parse_requirement(requirement)?;
+    Some(doctor::satisfies(&version, op, &required))
+}
+
+/// Returns the highest `engines.vscode` floor among the given extensions.
+pub fn engines_floor(files: &[String]) -> anyhow::Result<Option<String>> {
+    let mut floor: Option<String> = None;
+    for file in files {
+        let manifest = crate::vsix::read_manifest(Path::new(file))?;
+        if let Some(v) = manifest.vscode_engine().and_then(crate::vsix::engine_floor) {
+            if floor
+                .as_deref()
+                .is_none_or(|f| crate::code::compare_versions(&v, f).is_gt())
+            {
+                floor = Some(v);
+            }
+        }
+    }
+    Ok(floor)
+}
+
+/// Returns the lowest VS Code version that satisfies the `engines.vscode` requirement of every
+/// extension, or `latest` if none of them have a requirement.
+pub fn compatible_version(files: &[String]) -> anyhow::Result<String> {
+    let Some(floor) = engines_floor(files)? else {
+        return Ok("latest".to_owned());
+    };
+    for file in files {
+        let manifest = crate::vsix::read_manifest(Path::new(file))?;
+        if let Some(requirement) = manifest.vscode_engine() {
+            if satisfies(&floor, requirement) == Some(false) {
+                bail!(
+                    "No single VS Code version satisfies every extension: {} requires VS Code {}, which excludes {}",
+                    manifest.id(),
+                    requirement,
+                    floor
+                );
+            }
+        }
+    }
+    Ok(floor)
+}
+
+/// Replaces `--vsc-version compatible` with the result of [`compatible_version`].
+///
+/// # Arguments
+/// * `vscode` - The VS Code options from the command line
+/// * `files` - The `.vsix` files that will be installed
+pub fn resolve_version(vscode: &VsCodeConfig, files: &[String]) -> anyhow::Result<VsCodeConfig> {
+    let mut config = vscode.clone();
+    if config.vsc_version.as_deref() == Some(COMPATIBLE) {
+        let version = compatible_version(files)?;
+        if crate::output::text_enabled() {
+            println!("🧩 Using VS Code {} to satisfy `engines.vscode`", version);
+        }
+        config.vsc_version = Some(version);
+    }
+    Ok(config)
+}
+
+/// Checks each extension against the VS Code version it will be installed into, before installing.
+///
+/// Extensions whose `engines.vscode` excludes `vscode_version`, or that depend on such an extension,
+/// are marked as [`InstallStatus::Incompatible`] so that they are not installed. Dependencies that
+/// are neither part of `manifests` nor already installed in the sandbox are reported as warnings,
+/// since the Code CLI will try to install them from the marketplace.
+///
+/// # Arguments
+/// * `results` - The result for each `.vsix` file, in the same order as `manifests`
+/// * `manifests` - The manifest of each `.vsix` file, if it could be read
+/// * `vscode_version` - The resolved VS Code version
+/// * `installed` - The extensions already installed in the sandbox
+pub fn check(
+    results: &mut [ExtensionInstall],
+    manifests: &[Option<VsixManifest>],
+    vscode_version: &str,
+    installed: &[InstalledExtension],
+) {
+    for (result, manifest) in results.iter_mut().zip(manifests) {
+        let Some(requirement) = manifest.as_ref().and_then(VsixManifest::vscode_engine) else {
+            continue;
+        };
+        match satisfies(vscode_version, requirement) {
+            Some(true) => {}
+            Some(false) => {
+                result.status = InstallStatus::Incompatible;
+                result.error = Some(format!(
+                    "Requires VS Code {}, but VS Code {} is being used (use `--vsc-version {}` to pick a compatible version)",
+                    requirement, vscode_version, COMPATIBLE
+                ));
+            }
+            None => result.warnings.push(format!(
+                "Could not compare `engines.vscode` ({}) with VS Code {}",
+                requirement, vscode_version
+            )),
+        }
+    }
+
+    // Dependents of incompatible extensions are refused as well. This is repeated until no more
+    // are found, so that extensions further down a dependency chain are covered.
+    loop {
+        let incompatible: Vec<String> = results
+            .iter()
+            .filter(|r| r.status == InstallStatus::Incompatible)
+            .filter_map(|r| r.id.clone())
+            .collect();
+        let mut changed = false;
+        for (result, manifest) in results.iter_mut().zip(manifests) {
+            if result.status == InstallStatus::Incompatible {
+                continue;
+            }
+            let Some(manifest) = manifest else {
+                continue;
+            };
+            if let Some(dep) = manifest
+                .extension_dependencies
+                .iter()
+                .find(|dep| incompatible.contains(&dep.to_lowercase()))
+            {
+                result.status = InstallStatus::Incompatible;
+                result.error = Some(format!(
+                    "Depends on {}, which is not compatible with VS Code {}",
+                    dep, vscode_version
+                ));
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let provided: Vec<String> = results.iter().filter_map(|r| r.id.clone()).collect();
+    for (result, manifest) in results.iter_mut().zip(manifests) {
+        let Some(manifest) = manifest else {
+            continue;
+        };
+        for dep in &manifest.extension_dependencies {
+            if !provided.contains(&dep.to_lowercase())
+                && !installed.iter().any(|e| e.id.eq_ignore_ascii_case(dep))
+            {
+                result.warnings.push(format!(
+                    "Depends on {}, which is not among the .vsix files; the Code CLI will install it from the marketplace",
+                    dep
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(name: &str, engine: &str, deps: &[&str]) -> VsixManifest {
+        VsixManifest {
+            publisher: "Zowe".to_owned(),
+            name: name.to_owned(),
+            version: "3.2.0".to_owned(),
+            engines: [("vscode".to_owned(), engine.to_owned())].into(),
+            extension_dependencies: deps.iter().map(|d| d.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn compares_engine_requirements() {
+        assert_eq!(satisfies("1.101.2", "^1.90.0"), Some(true));
+        assert_eq!(satisfies("1.89.1", "^1.90.0"), Some(false));
+        assert_eq!(satisfies("1.106.0-1a2b3c4", "^1.106.0"), Some(true));
+        assert_eq!(satisfies("1.90.0", "*"), Some(true));
+        assert_eq!(satisfies("1.90.0", "latest"), None);
+    }
+
+    #[test]
+    fn refuses_incompatible_extensions_and_their_dependents() {
+        let manifests = vec![
+            Some(manifest("vscode-extension-for-zowe", "^1.101.0", &[])),
+            Some(manifest(
+                "zowe-explorer-ftp-extension",
+                "^1.90.0",
+                &["Zowe.vscode-extension-for-zowe"],
+            )),
+            Some(manifest(
+                "zowex-for-zowe-explorer",
+                "^1.90.0",
+                &["zowe.zowe-explorer-api"],
+            )),
+        ];
+        let mut results: Vec<ExtensionInstall> = manifests
+            .iter()
+            .map(|m| ExtensionInstall::new("ext.vsix", m.as_ref()))
+            .collect();
+
+        check(&mut results, &manifests, "1.95.0", &[]);
+        assert_eq!(results[0].status, InstallStatus::Incompatible);
+        assert!(results[0].error.as_deref().unwrap().contains("^1.101.0"));
+        assert_eq!(results[1].status, InstallStatus::Incompatible);
+        assert_eq!(results[2].status, InstallStatus::Unverified);
+        assert_eq!(results[2].warnings.len(), 1);
+    }
+
+    #[test]
+    fn refuses_dependency_chains_of_incompatible_extensions() {
+        // The dependent of the dependent comes first, so a single pass would miss it
+        let manifests = vec![
+            Some(manifest("c", "^1.90.0", &["Zowe.b"])),
+            Some(manifest("b", "^1.90.0", &["Zowe.a"])),
+            Some(manifest("a", "^1.101.0", &[])),
+        ];
+        let mut results: Vec<ExtensionInstall> = manifests
+            .iter()
+            .map(|m| ExtensionInstall::new("ext.vsix", m.as_ref()))
+            .collect();
+
+        check(&mut results, &manifests, "1.95.0", &[]);
+        assert!(results
+            .iter()
+            .all(|r| r.status == InstallStatus::Incompatible));
+        assert!(results[0].error.as_deref().unwrap().contains("Zowe.b"));
+        assert!(results[1].error.as_deref().unwrap().contains("Zowe.a"));
+    }
+}
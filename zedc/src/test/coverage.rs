@@ -358,8 +358,8 @@ fn get_changed_files_and_lines(
     let mut current_file_content_lines: Option<Vec<String>> = None;
 
     for line in changed_files.lines() {
-        if line.starts_with("+++ b/") {
-            current_file = line[6..].to_string();
+        if let Some(file) = line.strip_prefix("+++ b/") {
+            current_file = file.to_string();
             current_file_content_lines = None;
 
             if current_file.starts_with("zedc/") // Exclude zedc changes
@@ -533,10 +533,8 @@ fn parse_hunk_header(
     // Parse the line numbers from the hunk header `@@ -old_start,old_count +new_start,new_count @@ ...`
     if let Some(hunk_details) = line.split("@@").nth(1) {
         // " -1,5 +1,7 "
-        if let Some(new_hunk_part_str) = hunk_details
-            .trim()
-            .split_whitespace()
-            .find(|s| s.starts_with('+'))
+        if let Some(new_hunk_part_str) =
+            hunk_details.split_whitespace().find(|s| s.starts_with('+'))
         {
             // "+1,7"
             let new_hunk_info = new_hunk_part_str.trim_start_matches('+'); // "1,7" or "1"
@@ -714,24 +712,51 @@ fn process_stdout<R: BufRead>(
     progress_bar: &ProgressBar,
     lines: &Arc<Mutex<Vec<String>>>,
 ) {
-    for line_result in reader.lines() {
-        if let Ok(line) = line_result {
-            if let Some(pass_idx) = line.rfind("PASS") {
-                let display_line = if let Some(path_part) = line
-                    .get(pass_idx + 4..)
-                    .and_then(|s| s.split_whitespace().next())
-                {
-                    format!("✓ PASS {}", path_part)
-                } else {
-                    format!(
-                        "✓ {}",
-                        line.trim_start_matches(|c: char| c != ':')
-                            .trim_start_matches(':')
-                            .trim()
-                    )
-                };
-                progress_bar.set_message(display_line);
-            } else if let Some(fail_idx) = line.rfind("FAIL") {
+    for line in reader.lines().map_while(Result::ok) {
+        if let Some(pass_idx) = line.rfind("PASS") {
+            let display_line = if let Some(path_part) = line
+                .get(pass_idx + 4..)
+                .and_then(|s| s.split_whitespace().next())
+            {
+                format!("✓ PASS {}", path_part)
+            } else {
+                format!(
+                    "✓ {}",
+                    line.trim_start_matches(|c: char| c != ':')
+                        .trim_start_matches(':')
+                        .trim()
+                )
+            };
+            progress_bar.set_message(display_line);
+        } else if let Some(fail_idx) = line.rfind("FAIL") {
+            let display_line = if let Some(path_part) = line
+                .get(fail_idx + 4..)
+                .and_then(|s| s.split_whitespace().next())
+            {
+                format!("✗ FAIL {}", path_part)
+            } else {
+                format!(
+                    "✗ {}",
+                    line.trim_start_matches(|c: char| c != ':')
+                        .trim_start_matches(':')
+                        .trim()
+                )
+            };
+            progress_bar.set_message(display_line);
+        }
+        lines.lock().unwrap().push(line);
+    }
+}
+
+/// Process stderr from test commands
+fn process_stderr<R: BufRead>(
+    reader: R,
+    progress_bar: &ProgressBar,
+    lines: &Arc<Mutex<Vec<String>>>,
+) {
+    for line in reader.lines().map_while(Result::ok) {
+        if line.contains("FAIL") {
+            if let Some(fail_idx) = line.rfind("FAIL") {
                 let display_line = if let Some(path_part) = line
                     .get(fail_idx + 4..)
                     .and_then(|s| s.split_whitespace().next())
@@ -747,39 +772,8 @@ fn process_stdout<R: BufRead>(
                 };
                 progress_bar.set_message(display_line);
             }
-            lines.lock().unwrap().push(line);
-        }
-    }
-}
-
-/// Process stderr from test commands
-fn process_stderr<R: BufRead>(
-    reader: R,
-    progress_bar: &ProgressBar,
-    lines: &Arc<Mutex<Vec<String>>>,
-) {
-    for line_result in reader.lines() {
-        if let Ok(line) = line_result {
-            if line.contains("FAIL") {
-                if let Some(fail_idx) = line.rfind("FAIL") {
-                    let display_line = if let Some(path_part) = line
-                        .get(fail_idx + 4..)
-                        .and_then(|s| s.split_whitespace().next())
-                    {
-                        format!("✗ FAIL {}", path_part)
-                    } else {
-                        format!(
-                            "✗ {}",
-                            line.trim_start_matches(|c: char| c != ':')
-                                .trim_start_matches(':')
-                                .trim()
-                        )
-                    };
-                    progress_bar.set_message(display_line);
-                }
-            }
-            lines.lock().unwrap().push(line);
         }
+        lines.lock().unwrap().push(line);
     }
 }
 
@@ -920,7 +914,7 @@ fn process_coverage_file(
 
 /// Get the relative path of a package from the repo root
 fn get_package_relative_path(
-    coverage_file_path: &PathBuf,
+    coverage_file_path: &Path,
     repo_root_pathbuf: &PathBuf,
     verbose: bool,
 ) -> Result<String> {
@@ -953,7 +947,7 @@ fn get_package_relative_path(
 fn check_file_coverage(
     file_from_diff: &str,
     lines_in_diff: &Vec<usize>,
-    repo_root_pathbuf: &PathBuf,
+    repo_root_pathbuf: &Path,
     package_coverage_data: &serde_json::Value,
     covered_lines_in_patch: &mut usize,
     uncovered_lines_details: &mut HashMap<String, Vec<usize>>,
@@ -1030,7 +1024,7 @@ fn check_file_coverage(
 fn find_coverage_data<'a>(
     package_coverage_data: &'a serde_json::Value,
     changed_file_rel_path: &str,
-    repo_root: &PathBuf,
+    repo_root: &Path,
     verbose: bool,
 ) -> (Option<&'a serde_json::Value>, String) {
     let coverage_map = package_coverage_data
@@ -1039,7 +1033,7 @@ fn find_coverage_data<'a>(
         .or_else(|| package_coverage_data.as_object());
 
     // Construct a normalized, absolute path for the file from the git diff.
-    let mut changed_file_abs_path = repo_root.clone();
+    let mut changed_file_abs_path = repo_root.to_path_buf();
     for component in changed_file_rel_path.split('/') {
         changed_file_abs_path.push(component);
     }
@@ -0,0 +1,274 @@
+//! Module for resolving the `extensionDependencies` of the extensions being installed.
+//!
+//! Dependencies that are not among the given `.vsix` files or already installed in the sandbox are
+//! taken from the local VSIX cache (`zedc_data/vsix-cache`), or downloaded from an Open VSX-compatible
+//! registry (`ZEDC_OPENVSX_URL`) into that cache. The files are then ordered so that every
+//! extension is installed after its dependencies.
+
+use crate::sandbox::InstalledExtension;
+use crate::vsix::registry::{self, Registry};
+use anyhow::bail;
+use owo_colors::OwoColorize;
+use std::path::{Path, PathBuf};
+
+/// An extension in the dependency graph.
+#[derive(Debug)]
+pub struct Node {
+    pub file: String,
+    /// The extension identifier in lowercase, if the manifest could be read.
+    pub id: Option<String>,
+    /// The `extensionDependencies` of the extension, in lowercase.
+    pub dependencies: Vec<String>,
+}
+
+impl Node {
+    /// Reads the identifier and dependencies of a `.vsix` file.
+    pub fn read(file: &str) -> Self {
+        let manifest = crate::vsix::read_manifest(Path::new(file)).ok();
+        Self {
+            file: file.to_owned(),
+            id: manifest.as_ref().map(|m| m.id()),
+            dependencies: manifest
+                .map(|m| {
+                    m.extension_dependencies
+                        .iter()
+                        .map(|d| d.to_lowercase())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Adds the missing dependencies of the given `.vsix` files and returns all files in install order.
+///
+/// Dependencies that cannot be found are reported in text mode and left to the Code CLI, which
+/// will try to install them from its marketplace.
+///
+/// # Arguments
+/// * `files` - The `.vsix` files to install
+/// * `installed` - The extensions already installed in the sandbox
+pub async fn resolve(
+    files: Vec<String>,
+    installed: &[InstalledExtension],
+) -> anyhow::Result<Vec<String>> {
+    let text = crate::output::text_enabled();
+    let mut nodes: Vec<Node> = files.iter().map(|f| Node::read(f)).collect();
+
+    let mut registry: Option<Registry> = None;
+    let mut unresolved: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < nodes.len() {
+        for dep in nodes[i].dependencies.clone() {
+            if nodes.iter().any(|n| n.id.as_deref() == Some(dep.as_str()))
+                || installed.iter().any(|e| e.id.eq_ignore_ascii_case(&dep))
+                || unresolved.contains(&dep)
+            {
+                continue;
+            }
+            let registry = registry.get_or_insert_with(Registry::from_env);
+            match find_dependency(&dep, registry).await {
+                Ok((file, source)) => {
+                    if text {
+                        println!(
+                            "  📦 {} {}",
+                            dep.bold(),
+                            format!(
+                                "(dependency of {}, from {})",
+                                nodes[i].id.as_deref().unwrap_or(&nodes[i].file),
+                                source
+                            )
+                            .dimmed()
+                        );
+                    }
+                    nodes.push(Node::read(&file.to_string_lossy()));
+                }
+                Err(e) => {
+                    if text {
+                        println!(
+                            "  ⚠️  {}",
+                            format!("Could not find dependency {}: {:#}", dep, e).yellow()
+                        );
+                    }
+                    unresolved.push(dep);
+                }
+            }
+        }
+        i += 1;
+    }
+
+    let order = install_order(&nodes)?;
+    Ok(order.into_iter().map(|i| nodes[i].file.clone()).collect())
+}
+
+/// Looks up a dependency in the local VSIX cache, then downloads it from the registry.
+/// Returns the `.vsix` path and a description of where it came from.
+async fn find_dependency(id: &str, registry: &Registry) -> anyhow::Result<(PathBuf, String)> {
+    let cache = registry::cache_dir()?;
+    if let Some((path, manifest)) = registry::find_cached(&cache, id) {
+        return Ok((path, format!("VSIX cache, {}", manifest.version)));
+    }
+    let path = registry.download(id, &cache).await?;
+    Ok((path, registry.base_url().to_owned()))
+}
+
+/// Returns the indices of `nodes` in install order: every extension comes after its dependencies,
+/// and otherwise the given order is kept.
+pub fn install_order(nodes: &[Node]) -> anyhow::Result<Vec<usize>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        New,
+        Visiting,
+        Done,
+    }
+
+    fn visit(
+        i: usize,
+        nodes: &[Node],
+        marks: &mut [Mark],
+        path: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+    ) -> anyhow::Result<()> {
+        match marks[i] {
+            Mark::Done => return Ok(()),
+            Mark::Visiting => {
+                let start = path.iter().position(|&p| p == i).unwrap_or(0);
+                let cycle: Vec<&str> = path[start..]
+                    .iter()
+                    .chain(std::iter::once(&i))
+                    .map(|&p| nodes[p].id.as_deref().unwrap_or(&nodes[p].file))
+                    .collect();
+                bail!("Circular extension dependency: {}", cycle.join(" -> "));
+            }
+            Mark::New => {}
+        }
+        marks[i] = Mark::Visiting;
+        path.push(i);
+        for dep in &nodes[i].dependencies {
+            if let Some(d) = nodes
+                .iter()
+                .position(|n| n.id.as_deref() == Some(dep.as_str()))
+            {
+                visit(d, nodes, marks, path, order)?;
+            }
+        }
+        path.pop();
+        marks[i] = Mark::Done;
+        order.push(i);
+        Ok(())
+    }
+
+    let mut marks = vec![Mark::New; nodes.len()];
+    let mut order = Vec::with_capacity(nodes.len());
+    for i in 0..nodes.len() {
+        visit(i, nodes, &mut marks, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vsix::tests::write_vsix;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    fn node(id: &str, deps: &[&str]) -> Node {
+        Node {
+            file: format!("{}.vsix", id),
+            id: Some(id.to_owned()),
+            dependencies: deps.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn orders_dependencies_first() {
+        let nodes = [
+            node(
+                "zowe.zowe-explorer-ftp-extension",
+                &["zowe.vscode-extension-for-zowe"],
+            ),
+            node("zowe.zowex-for-zowe-explorer", &[]),
+            node("zowe.vscode-extension-for-zowe", &[]),
+        ];
+        assert_eq!(install_order(&nodes).unwrap(), [2, 0, 1]);
+
+        let cycle = [node("a.a", &["b.b"]), node("b.b", &["a.a"])];
+        let err = install_order(&cycle).unwrap_err().to_string();
+        assert!(err.contains("a.a -> b.b -> a.a"), "{}", err);
+    }
+
+    #[test]
+    fn finds_newest_cached_vsix() {
+        let dir = tempfile::tempdir().unwrap();
+        for version in ["3.1.0", "3.10.0", "3.2.0"] {
+            write_vsix(
+                dir.path(),
+                &format!("ze-{}.vsix", version),
+                &format!(
+                    r#"{{"publisher":"Zowe","name":"vscode-extension-for-zowe","version":"{}"}}"#,
+                    version
+                ),
+                &[],
+            );
+        }
+        let (path, manifest) =
+            registry::find_cached(dir.path(), "zowe.vscode-extension-for-zowe").unwrap();
+        assert_eq!(manifest.version, "3.10.0");
+        assert!(path.ends_with("ze-3.10.0.vsix"));
+        assert!(registry::find_cached(dir.path(), "zowe.zowe-explorer-api").is_none());
+    }
+
+    #[tokio::test]
+    async fn downloads_from_registry() {
+        let src = tempfile::tempdir().unwrap();
+        let vsix = std::fs::read(write_vsix(
+            src.path(),
+            "ze.vsix",
+            r#"{"publisher":"Zowe","name":"vscode-extension-for-zowe","version":"3.2.0"}"#,
+            &[],
+        ))
+        .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let metadata = format!(
+            r#"{{"version":"3.2.0","files":{{"download":"{}/download/ze.vsix"}}}}"#,
+            base_url
+        );
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for body in [metadata.into_bytes(), vsix] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(&body).unwrap();
+                requests.push(request_line);
+            }
+            requests
+        });
+
+        let cache = tempfile::tempdir().unwrap();
+        let path = Registry::new(&base_url)
+            .download("Zowe.vscode-extension-for-zowe", cache.path())
+            .await
+            .unwrap();
+        assert!(path.ends_with("zowe.vscode-extension-for-zowe-3.2.0.vsix"));
+        assert_eq!(crate::vsix::read_manifest(&path).unwrap().version, "3.2.0");
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /api/Zowe/vscode-extension-for-zowe/latest "));
+        assert!(requests[1].starts_with("GET /download/ze.vsix "));
+    }
+}
@@ -1,162 +1,383 @@
 //! Module for test functions that interact with the filesystem.
 
-use crate::code::code_binary;
+use crate::cmd::SandboxConfig;
+use crate::code::compare_versions;
+use crate::code::VsCodeInstall;
+use crate::output::exit;
+use crate::sandbox::cli::{self, CliInstall};
+use crate::sandbox::debug;
+use crate::sandbox::session::{self, SessionSummary};
+use crate::sandbox::web;
+use crate::sandbox::{zowe, LaunchOptions, Sandbox};
+use crate::test::verify::{self, ExtensionInstall, InstallStatus};
+use crate::test::{compat, deps};
+use crate::vsix::{registry, VsixManifest};
 use anyhow::bail;
 use owo_colors::OwoColorize;
+use serde::Serialize;
 use std::{
+    cmp::Ordering,
     ffi::OsStr,
-    path::Path,
-    process::{Command, Stdio},
+    path::{Path, PathBuf},
+    process::Stdio,
 };
 
-/// (WIP) Installs a copy of Zowe CLI for use during testing.
+/// The copy of VS Code a sandbox was prepared with and the install result for each extension.
+#[derive(Serialize)]
+pub struct InstallReport {
+    pub vscode: VsCodeInstall,
+    pub extensions: Vec<ExtensionInstall>,
+    /// The session summary when zedc stayed attached to VS Code (`--wait`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<SessionSummary>,
+    /// The debugger attach configuration when the sandbox was launched with `--inspect`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attach_config: Option<serde_json::Value>,
+    /// Zowe CLI and the plugins installed into the sandbox (`--install-cli`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cli: Option<CliInstall>,
+}
+
+impl InstallReport {
+    /// Whether every requested extension was installed with the expected version.
+    pub fn ok(&self) -> bool {
+        self.extensions.iter().all(ExtensionInstall::ok)
+    }
+
+    /// The exit code for this report: a failed install takes precedence over a failed activation.
+    pub fn exit_code(&self) -> i32 {
+        if !self.ok() {
+            exit::FAILURE
+        } else if self.session.as_ref().is_some_and(|s| !s.ok()) {
+            exit::ACTIVATION_FAILED
+        } else {
+            exit::SUCCESS
+        }
+    }
+}
+
+/// Installs the given list of .vsix files into a sandbox using the given VS Code binary,
+/// then launches the sandbox if every extension was installed. With `--wait`, the session is
+/// followed until the VS Code window is closed (see [`session::run`]). With `--inspect`, an attach
+/// configuration for the extension host is printed first (see [`debug::attach_config`]). A VS Code
+/// server build (`--web`) is served in the browser instead (see [`web::serve`]). With `--install-cli`,
+/// Zowe CLI and its plugins are installed into the sandbox before the extensions (see [`cli::install`]).
 ///
 /// # Arguments
-/// * `version` - The version of Zowe CLI to install from `npm`
-pub fn install_cli(version: String) -> anyhow::Result<()> {
-    let text = crate::output::text_enabled();
-    let nm_path = Path::new("./node_modules");
-    if nm_path.exists() {
-        std::fs::remove_dir_all(nm_path)?;
-    }
-    std::fs::create_dir(nm_path)?;
-    let mut npm = crate::pm::npm();
-    npm.args(["install", "-g", "--no-save", "--prefix", "./node_modules"])
-        .arg(format!("@zowe/cli@{}", version));
-    if !text {
-        npm.stdout(Stdio::null()).stderr(Stdio::null());
-    }
-    let status = match npm.status() {
-        Ok(s) => s,
-        Err(e) => {
-            if text {
-                println!("❌ Could not install Zowe CLI, error: {}", e);
+/// * `vsc` - The copy of VS Code to install the extensions with
+/// * `files` - A `Vec` of file paths that correspond to extension files (`.vsix`)
+/// * `config` - The sandbox to install the extensions into
+pub async fn install_from_paths(
+    vsc: VsCodeInstall,
+    files: Vec<String>,
+    config: &SandboxConfig,
+) -> anyhow::Result<InstallReport> {
+    if vsc.web && (config.wait || config.inspect.is_some()) {
+        bail!("--wait and --inspect are not supported with --web.");
+    }
+    let sandbox = Sandbox::new(&config.name)?;
+    let repo = debug::repo_root()?;
+    if config.launch_json && repo.is_none() {
+        bail!("--launch-json requires running zedc inside a repository with a package.json.");
+    }
+    sandbox.set_launch_options(LaunchOptions::from_config(config)?)?;
+    if let Some(zowe_config) = zowe::from_config(config)? {
+        sandbox.write_zowe_config(&zowe_config)?;
+    }
+    let cli = match &config.install_cli {
+        Some(version) => Some(cli::install(&sandbox, version, &config.cli_plugins)?),
+        None => None,
+    };
+    let extensions = install_into_sandbox(&vsc, files, &sandbox).await?;
+    let mut report = InstallReport {
+        vscode: vsc,
+        extensions,
+        session: None,
+        attach_config: None,
+        cli,
+    };
+
+    if let Some(port) = config.inspect.filter(|_| report.ok()) {
+        let (attach, warnings) = debug::attach_config(&sandbox, port, repo.as_deref());
+        let written = match (&repo, config.launch_json) {
+            (Some(repo), true) => Some(debug::write_launch_json(repo, &attach)?),
+            _ => None,
+        };
+        if crate::output::text_enabled() {
+            println!(
+                "\n🐞 {}\n{}",
+                "Attach configuration for the extension host:".bold(),
+                serde_json::to_string_pretty(&attach)?
+            );
+            if let Some(path) = written {
+                println!("   {} {}", "Written to".dimmed(), path.display());
+            }
+            for warning in &warnings {
+                println!("   ⚠️  {}", warning.italic());
             }
-            bail!(e)
         }
-    };
-    if !status.success() {
-        bail!("Could not install Zowe CLI");
+        report.attach_config = Some(attach);
     }
 
-    if text {
-        println!("✔️  Installed Zowe CLI");
+    // Launch VS Code after installing the given extensions.
+    if report.ok() && report.vscode.web {
+        web::serve(&sandbox, Path::new(&report.vscode.bin)).await?;
+    } else if report.ok() && config.wait {
+        let summary = session::run(&sandbox, Path::new(&report.vscode.bin)).await?;
+        if crate::output::text_enabled() {
+            summary.print();
+        }
+        report.session = Some(summary);
+    } else if report.ok() {
+        sandbox.launch(Path::new(&report.vscode.bin))?;
+    } else if crate::output::text_enabled() {
+        println!(
+            "\n{}",
+            format!(
+                "Not launching sandbox '{}' because some extensions were not installed.",
+                sandbox.name
+            )
+            .red()
+        );
     }
-    Ok(())
+    Ok(report)
 }
 
-/// Installs the given list of .vsix files using the given VS Code binary.
+/// Installs the given list of .vsix files into a sandbox using the given VS Code binary,
+/// and records the VS Code version and extensions in the sandbox metadata.
+///
+/// Missing `extensionDependencies` are added first (see [`deps::resolve`]), and all files are
+/// installed after their dependencies. Each file is then checked against the VS Code version (see [`compat::check`]), and files that
+/// are not compatible are not installed. The others are installed with a separate Code CLI call so
+/// that failures can be attributed to them. Afterwards, the installed extensions are listed through
+/// the Code CLI and matched against the manifest of each `.vsix` file.
 ///
 /// # Arguments
-/// * `vsc_bin` - A path to the VS Code binary
+/// * `vsc` - The copy of VS Code to install the extensions with
 /// * `files` - A `Vec` of file paths that correspond to extension files (`.vsix`)
-pub async fn install_from_paths(vsc_bin: String, files: Vec<String>) -> anyhow::Result<()> {
+/// * `sandbox` - The sandbox to install the extensions into
+pub async fn install_into_sandbox(
+    vsc: &VsCodeInstall,
+    files: Vec<String>,
+    sandbox: &Sandbox,
+) -> anyhow::Result<Vec<ExtensionInstall>> {
     if files.is_empty() {
         bail!("No valid .vsix files provided.".red());
     }
+    sandbox.prepare()?;
+    let installed = sandbox.installed_extensions();
 
     // Install the given extensions using the VS Code CLI.
     // Must complete before launching so the data/ directory isn't locked by two processes.
-    let vsc_bin_path = Path::new(&vsc_bin);
+    let vsc_bin_path = Path::new(&vsc.bin);
     let text = crate::output::text_enabled();
     if text {
         println!("\n⌛ Installing extensions...");
     }
-    let mut cmd = Command::new(vsc_bin_path);
-    for file in files.iter() {
-        cmd.args(["--install-extension", file]);
-    }
+    let files = deps::resolve(files, &installed).await?;
+    let manifests: Vec<_> = files
+        .iter()
+        .map(|f| crate::vsix::read_manifest(Path::new(f)).ok())
+        .collect();
+    let mut results: Vec<_> = files
+        .iter()
+        .zip(&manifests)
+        .map(|(f, m)| ExtensionInstall::new(f, m.as_ref()))
+        .collect();
+    compat::check(&mut results, &manifests, &vsc.version, &installed);
 
-    let install_status = cmd.stdout(Stdio::null()).stderr(Stdio::null()).status()?;
-    if !install_status.success() {
-        bail!("VS Code CLI exited with a non-zero status while installing extensions");
+    for (file, result) in files.iter().zip(results.iter_mut()) {
+        if result.failed_early() {
+            continue;
+        }
+        let output = sandbox
+            .code_command(vsc_bin_path)
+            .args(["--install-extension", file])
+            .stdin(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            result.status = InstallStatus::Failed;
+            result.error = Some(verify::cli_error(&output));
+        }
     }
 
-    // Launch VS Code after installing the given extensions.
-    let vsc_dir = match std::env::consts::OS {
-        "macos" => vsc_bin_path.ancestors().nth(6).unwrap(),
-        _ => vsc_bin_path.parent().unwrap().parent().unwrap(),
-    };
-    let sandbox_dir = vsc_dir.parent().unwrap().join("sandbox");
-    let sandbox_str = sandbox_dir.to_str().unwrap();
-    let zowe_dir = sandbox_dir.join(".zowe");
-    tokio::fs::create_dir_all(&zowe_dir).await?;
-    let vsc = vsc_dir.join(code_binary());
-
-    if std::env::consts::OS == "macos" {
-        match Command::new("open")
-            .args([
-                vsc.to_str().unwrap(),
-                "--args",
-                "--new-window",
-                "--disable-updates",
-                sandbox_str,
-            ])
-            .env("ZOWE_CLI_HOME", zowe_dir.to_str().unwrap())
-            .stdout(Stdio::null())
-            .spawn()
-        {
-            Ok(_s) => {
-                if text {
-                    println!("🚀 Launched VS Code");
-                }
-                Ok(())
-            }
-            Err(_) => todo!(),
-        }
-    } else {
-        match Command::new(vsc)
-            .args(["--new-window", sandbox_str])
-            .env("ZOWE_CLI_HOME", zowe_dir.to_str().unwrap())
-            .stdout(Stdio::null())
-            .spawn()
-        {
-            Ok(_s) => {
-                if text {
-                    println!("🚀 Launched VS Code");
-                }
-                Ok(())
+    match verify::list_extensions(sandbox, vsc_bin_path) {
+        Ok(installed) => results.iter_mut().for_each(|r| r.verify(&installed)),
+        Err(e) => {
+            for r in results.iter_mut().filter(|r| !r.failed_early()) {
+                r.error = Some(format!("Could not list installed extensions: {:#}", e));
             }
-            Err(_) => todo!(),
         }
     }
+    if text {
+        results.iter().for_each(ExtensionInstall::print);
+    }
+
+    let mut metadata = sandbox.metadata();
+    metadata.vscode_version = Some(vsc.version.clone());
+    metadata.vscode_bin = Some(vsc.bin.clone());
+    metadata.vsix = files;
+    sandbox.save_metadata(&metadata)?;
+    Ok(results)
 }
 
-/// Resolves absolute file paths given a list of relative paths.
+/// Resolves the `.vsix` files to install from the given arguments, returning absolute paths.
+///
+/// Each argument can be a file, a directory (every `.vsix` file directly inside it), a glob such as
+/// `dist/*-3.6.*.vsix`, or an `http(s)://` URL that is downloaded into the VSIX cache. When several
+/// files of the same extension are found, only the highest version is kept. Invalid entries are
+/// reported and skipped.
 ///
 /// # Arguments
-/// * `files` - A `Vec` of relative file paths to resolve on the local filesystem.
-pub fn resolve_paths(files: Vec<String>) -> Vec<String> {
+/// * `files` - A `Vec` of relative file paths, directories, globs or URLs to resolve.
+pub async fn resolve_paths(files: Vec<String>) -> Vec<String> {
     let text = crate::output::text_enabled();
     if text {
         println!("\n🔍 Resolving files...");
     }
-    files
-        .iter()
-        .filter_map(|f| match std::fs::canonicalize(f) {
-            Ok(p) => match p.extension().unwrap_or(OsStr::new("")).to_str().unwrap() {
-                "vsix" => {
-                    if text {
-                        println!("  ✔️  {}", f.bold());
-                    }
-                    Some(p.to_str().unwrap().to_owned())
-                }
-                _ => {
-                    if text {
-                        println!(
-                            "  ❌ {}",
-                            format!("{}: invalid extension format", f).italic()
-                        );
-                    }
-                    None
-                }
-            },
+    let mut paths = Vec::new();
+    for f in &files {
+        let found = if f.starts_with("http://") || f.starts_with("https://") {
+            match registry::cache_dir() {
+                Ok(dir) => registry::download_url(f, &dir).await.map(|p| vec![p]),
+                Err(e) => Err(e),
+            }
+        } else {
+            expand_path(f)
+        };
+        match found {
+            Ok(found) => paths.extend(found),
             Err(e) => {
                 if text {
-                    println!("  ❌ {}", format!("{}: {}", f, e).italic());
+                    println!("  ❌ {}", format!("{}: {:#}", f, e).italic());
                 }
-                None
             }
+        }
+    }
+
+    let (kept, skipped) = keep_highest_versions(paths);
+    if text {
+        for path in &kept {
+            println!("  ✔️  {}", path.display().bold());
+        }
+        for (path, newer) in &skipped {
+            println!(
+                "  ⏭️  {}",
+                format!("{}: skipped in favor of {}", path.display(), newer).italic()
+            );
+        }
+    }
+    kept.iter()
+        .map(|p| p.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Expands a local path argument (file, directory or glob) to the absolute paths of `.vsix` files.
+fn expand_path(arg: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let path = Path::new(arg);
+    let candidates: Vec<PathBuf> = if path.is_dir() {
+        let mut found: Vec<PathBuf> = std::fs::read_dir(path)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && p.extension() == Some(OsStr::new("vsix")))
+            .collect();
+        found.sort();
+        if found.is_empty() {
+            bail!("no .vsix files in directory");
+        }
+        found
+    } else if arg.contains(['*', '?', '[']) {
+        let found: Vec<PathBuf> = glob::glob(arg)?.filter_map(|p| p.ok()).collect();
+        if found.is_empty() {
+            bail!("no files match the pattern");
+        }
+        found
+    } else {
+        vec![path.to_owned()]
+    };
+
+    candidates
+        .into_iter()
+        .map(|p| {
+            let p = std::fs::canonicalize(&p)?;
+            if p.extension() != Some(OsStr::new("vsix")) {
+                bail!("{}: invalid extension format", p.display());
+            }
+            Ok(p)
         })
-        .collect::<Vec<_>>()
+        .collect()
+}
+
+/// De-duplicates `.vsix` files by extension identifier, keeping the highest version of each in the
+/// order they were given. Returns the kept files and the skipped ones with the file kept instead.
+/// Files whose manifest cannot be read are kept, so that the install reports the error.
+fn keep_highest_versions(paths: Vec<PathBuf>) -> (Vec<PathBuf>, Vec<(PathBuf, String)>) {
+    let label = |m: &VsixManifest| format!("{}@{}", m.id(), m.version);
+    let mut kept: Vec<(PathBuf, Option<VsixManifest>)> = Vec::new();
+    let mut skipped = Vec::new();
+    for path in paths {
+        if kept.iter().any(|(p, _)| *p == path) {
+            continue;
+        }
+        let Ok(manifest) = crate::vsix::read_manifest(&path) else {
+            kept.push((path, None));
+            continue;
+        };
+        let existing = kept.iter_mut().find_map(|(p, k)| {
+            k.as_mut()
+                .filter(|k| k.id().eq_ignore_ascii_case(&manifest.id()))
+                .map(|k| (p, k))
+        });
+        match existing {
+            Some((kept_path, kept_manifest))
+                if compare_versions(&manifest.version, &kept_manifest.version)
+                    == Ordering::Greater =>
+            {
+                skipped.push((std::mem::replace(kept_path, path), label(&manifest)));
+                *kept_manifest = manifest;
+            }
+            Some((_, kept_manifest)) => skipped.push((path, label(kept_manifest))),
+            None => kept.push((path, Some(manifest))),
+        }
+    }
+    (kept.into_iter().map(|(p, _)| p).collect(), skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vsix::tests::write_vsix;
+
+    fn manifest(name: &str, version: &str) -> String {
+        format!(
+            r#"{{ "publisher": "Zowe", "name": "{}", "version": "{}" }}"#,
+            name, version
+        )
+    }
+
+    #[test]
+    fn expands_directories_and_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = write_vsix(dir.path(), "ze-3.5.0.vsix", &manifest("ze", "3.5.0"), &[]);
+        let new = write_vsix(dir.path(), "ze-3.6.1.vsix", &manifest("ze", "3.6.1"), &[]);
+        let ftp = write_vsix(dir.path(), "ftp-3.6.0.vsix", &manifest("ftp", "3.6.0"), &[]);
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+        let canonical = |p: &Path| std::fs::canonicalize(p).unwrap();
+
+        let all = expand_path(&dir.path().to_string_lossy()).unwrap();
+        assert_eq!(all, vec![canonical(&ftp), canonical(&old), canonical(&new)]);
+        let pattern = dir.path().join("*-3.6.*.vsix");
+        let matched = expand_path(&pattern.to_string_lossy()).unwrap();
+        assert_eq!(matched, vec![canonical(&ftp), canonical(&new)]);
+        assert!(expand_path(&dir.path().join("*-4.*.vsix").to_string_lossy()).is_err());
+        assert!(expand_path(&dir.path().join("notes.txt").to_string_lossy()).is_err());
+
+        // The highest version of each extension is kept, in the order the files were given
+        let (kept, skipped) = keep_highest_versions(vec![old.clone(), ftp.clone(), new.clone()]);
+        assert_eq!(kept, vec![new.clone(), ftp]);
+        assert_eq!(skipped, vec![(old.clone(), "zowe.ze@3.6.1".to_owned())]);
+        let (kept, skipped) = keep_highest_versions(vec![new.clone(), old.clone(), new.clone()]);
+        assert_eq!(kept, vec![new]);
+        assert_eq!(skipped, vec![(old, "zowe.ze@3.6.1".to_owned())]);
+    }
 }
@@ -1,5 +1,7 @@
 //! Module for handling the `test ghr` sub-command.
 
+use crate::cmd::{SandboxConfig, VsCodeConfig};
+use crate::test::InstallReport;
 use anyhow::bail;
 use octocrab::{params::actions::ArchiveFormat, Octocrab};
 use owo_colors::OwoColorize;
@@ -117,16 +119,31 @@ async fn fetch_artifacts(refs: Vec<String>, gh: &Octocrab) -> anyhow::Result<Vec
 }
 
 /// Downloads VS Code, resolves artifacts from the given GitHub refs, installs them in VS Code and opens it.
+/// Returns the copy of VS Code that was used and the install result for each extension.
 ///
 /// # Arguments
 /// * `refs` - A `Vec` of Git references containing artifacts to install
-/// * `vsc_version` - (optional) The VS Code version to download (default: `latest`)
+/// * `vscode` - The VS Code version or archive to use (default: `latest`)
+/// * `sandbox` - The sandbox to install the artifacts into
 /// * `gh` - An instance of Octocrab to use for GitHub API requests.
 pub async fn setup(
     refs: Vec<String>,
-    vsc_version: Option<String>,
+    vscode: &VsCodeConfig,
+    sandbox: &SandboxConfig,
     gh: &Octocrab,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<InstallReport> {
+    let paths = resolve(refs, gh).await?;
+    let vscode = super::resolve_version(vscode, &paths)?;
+    let vsc = crate::code::download_vscode(&vscode).await?;
+    super::fs::install_from_paths(vsc, paths, sandbox).await
+}
+
+/// Downloads the artifacts for the given GitHub refs and returns the paths to their `.vsix` files.
+///
+/// # Arguments
+/// * `refs` - A `Vec` of Git references containing artifacts to install
+/// * `gh` - An instance of Octocrab to use for GitHub API requests.
+pub async fn resolve(refs: Vec<String>, gh: &Octocrab) -> anyhow::Result<Vec<String>> {
     if refs.is_empty() {
         bail!("At least one reference is required to use this command.".red());
     }
@@ -136,9 +153,5 @@ pub async fn setup(
         bail!("A GitHub personal access token must be defined in the ZEDC_PAT environment variable to use this command.".red());
     }
 
-    let vsc_bin = crate::code::download_vscode(vsc_version).await?;
-    let paths = fetch_artifacts(refs, gh).await?;
-    super::fs::install_from_paths(vsc_bin, paths).await?;
-
-    Ok(())
+    fetch_artifacts(refs, gh).await
 }
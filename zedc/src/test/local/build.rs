@@ -0,0 +1,368 @@
+//! Building the extension packages of the current tree for `zedc test local --build`.
+//!
+//! Each selected workspace package is built with `<pm> package`, and the VSIX it produces is copied
+//! into `zedc_data/build-cache/<package>/<hash>.vsix`, where the hash covers the sources of the
+//! package and of the workspace packages it depends on. When the hash of the current tree matches a
+//! cached build, the package is not rebuilt.
+
+use anyhow::{bail, Context};
+use owo_colors::OwoColorize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Folders that hold dependencies or build output rather than sources.
+const IGNORED_DIRS: [&str; 7] = [
+    "node_modules",
+    "out",
+    "lib",
+    "dist",
+    "results",
+    "coverage",
+    "__snapshots__",
+];
+
+/// A package of the repository that can be packaged as an extension.
+#[derive(Debug)]
+struct Package {
+    /// The `name` in `package.json`.
+    name: String,
+    /// The folder of the package.
+    dir: PathBuf,
+    /// The names of all dependencies in `package.json`.
+    dependencies: Vec<String>,
+}
+
+/// Returns the packages of a repository: `packages/*` for a monorepo like Zowe Explorer, otherwise
+/// the repository root. Only packages with a `package` script and `engines.vscode` are returned.
+fn workspace_packages(repo: &Path) -> Vec<Package> {
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(repo.join("packages"))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.join("package.json").is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    if dirs.is_empty() {
+        dirs.push(repo.to_owned());
+    }
+    dirs.sort();
+    dirs.into_iter()
+        .filter_map(|dir| {
+            let manifest: Value =
+                serde_json::from_str(&std::fs::read_to_string(dir.join("package.json")).ok()?)
+                    .ok()?;
+            let dependencies = ["dependencies", "devDependencies"]
+                .iter()
+                .filter_map(|key| manifest[key].as_object())
+                .flat_map(|deps| deps.keys().cloned())
+                .collect();
+            if !manifest["scripts"]["package"].is_string()
+                || !manifest["engines"]["vscode"].is_string()
+            {
+                return None;
+            }
+            Some(Package {
+                name: manifest["name"].as_str()?.to_owned(),
+                dir,
+                dependencies,
+            })
+        })
+        .collect()
+}
+
+/// Returns the hash of the sources a package is built from: the files of the package, the files of
+/// the workspace packages it depends on and the lock file at the repository root.
+fn source_hash(repo: &Path, package: &Package) -> anyhow::Result<String> {
+    let mut dirs = vec![package.dir.clone()];
+    if let Ok(entries) = std::fs::read_dir(repo.join("packages")) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let manifest = std::fs::read_to_string(entry.path().join("package.json"))
+                .ok()
+                .and_then(|text| serde_json::from_str::<Value>(&text).ok());
+            let is_dependency = manifest
+                .as_ref()
+                .and_then(|m| m["name"].as_str())
+                .is_some_and(|name| package.dependencies.iter().any(|d| d == name));
+            if is_dependency {
+                dirs.push(entry.path());
+            }
+        }
+    }
+
+    let mut files: Vec<PathBuf> = dirs.iter().flat_map(|dir| source_files(dir)).collect();
+    files.extend(
+        ["pnpm-lock.yaml", "yarn.lock", "package-lock.json"]
+            .iter()
+            .map(|name| repo.join(name))
+            .filter(|path| path.is_file()),
+    );
+    files.sort();
+    files.dedup();
+
+    let mut hasher = Sha256::new();
+    for file in files {
+        let relative = file.strip_prefix(repo).unwrap_or(&file);
+        hasher.update(relative.to_string_lossy().replace('\\', "/").as_bytes());
+        hasher.update([0]);
+        hasher.update(
+            std::fs::read(&file).with_context(|| format!("Failed to read {}", file.display()))?,
+        );
+        hasher.update([0]);
+    }
+    let digest = hasher.finalize();
+    Ok(digest[..8].iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Returns the source files below a directory, skipping hidden folders, [`IGNORED_DIRS`] and
+/// packaged `.vsix` files.
+fn source_files(dir: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return found;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        match entry.file_type() {
+            Ok(t)
+                if t.is_dir()
+                    && !name.starts_with('.')
+                    && !IGNORED_DIRS.contains(&name.as_str()) =>
+            {
+                found.extend(source_files(&entry.path()))
+            }
+            Ok(t) if t.is_file() && !name.ends_with(".vsix") => found.push(entry.path()),
+            _ => {}
+        }
+    }
+    found
+}
+
+/// Returns the `.vsix` files for a package in the folders that `<pm> package` writes to: `dist/` at
+/// the repository root (Zowe Explorer's `mv-pack.js` post-step) and the package folder.
+fn packaged_vsix(repo: &Path, package: &Package) -> Vec<PathBuf> {
+    [repo.join("dist"), package.dir.clone()]
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "vsix"))
+        .filter(|p| crate::vsix::read_manifest(p).is_ok_and(|m| m.name == package.name))
+        .collect()
+}
+
+/// Returns the packages selected with `--package`, matched by folder or package name, or all
+/// packages when none were given.
+fn select(packages: Vec<Package>, names: &[String]) -> anyhow::Result<Vec<Package>> {
+    if names.is_empty() {
+        return Ok(packages);
+    }
+    let folder = |p: &Package| {
+        p.dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    for name in names {
+        if !packages
+            .iter()
+            .any(|p| p.name == *name || folder(p) == *name)
+        {
+            bail!(
+                "Unknown package '{}'. Available packages: {}",
+                name,
+                packages.iter().map(folder).collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+    Ok(packages
+        .into_iter()
+        .filter(|p| names.iter().any(|n| p.name == *n || folder(p) == *n))
+        .collect())
+}
+
+/// Builds the selected extension packages of the repository in the current directory and returns
+/// the paths of their VSIXes. Packages whose sources are unchanged since the last build are not
+/// rebuilt.
+///
+/// # Arguments
+/// * `names` - The packages to build, by folder (e.g. `zowe-explorer`) or package name (default: all)
+pub fn build_packages(names: &[String]) -> anyhow::Result<Vec<String>> {
+    let Some(repo) = crate::sandbox::debug::repo_root()? else {
+        bail!("Could not find a repo folder containing package.json.");
+    };
+    build(&repo, names, &crate::util::data_dir()?.join("build-cache"))
+}
+
+/// Builds the selected extension packages of a repository, caching the VSIXes in `cache`. A
+/// package that fails to build is returned as an error, so that `test local --build` exits
+/// non-zero instead of installing the VSIXes of an earlier build.
+fn build(repo: &Path, names: &[String], cache: &Path) -> anyhow::Result<Vec<String>> {
+    let text = crate::output::text_enabled();
+    let packages = workspace_packages(repo);
+    if packages.is_empty() {
+        bail!(
+            "No extension packages with a `package` script found in {}",
+            repo.display()
+        );
+    }
+    let packages = select(packages, names)?;
+    if !crate::pm::check_dependencies(repo) {
+        bail!(
+            "Dependencies are not installed in {}; run `{} install` first.",
+            repo.display(),
+            crate::pm::detect_pkg_mgr(repo)?
+        );
+    }
+    if text {
+        println!("\n{}", "Building VSIXes...".underline());
+    }
+
+    let mut vsix_paths = Vec::new();
+    for package in packages {
+        let dir = cache.join(&package.name);
+        let cached = dir.join(format!("{}.vsix", source_hash(repo, &package)?));
+        if cached.is_file() {
+            if text {
+                println!(
+                    "  ⏭️  {}",
+                    format!("{}: sources unchanged since the last build", package.name).italic()
+                );
+            }
+            vsix_paths.push(cached.to_string_lossy().into_owned());
+            continue;
+        }
+
+        // Remove stale VSIXes so that only the output of this build is picked up.
+        for stale in packaged_vsix(repo, &package) {
+            let _ = std::fs::remove_file(stale);
+        }
+        crate::pr::run_package(repo, &package.dir)
+            .with_context(|| format!("Failed to build {}", package.name))?;
+        let Some(built) = packaged_vsix(repo, &package).into_iter().next() else {
+            bail!("No .vsix file found for {} after the build", package.name);
+        };
+
+        // The build may regenerate files in the tree (e.g. l10n bundles), so the hash is taken again
+        // to match the next run.
+        let cached = dir.join(format!("{}.vsix", source_hash(repo, &package)?));
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+        }
+        std::fs::create_dir_all(&dir)?;
+        std::fs::copy(&built, &cached)
+            .with_context(|| format!("Failed to copy {}", built.display()))?;
+        if text {
+            println!(
+                "  📦 {}",
+                built
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .bold()
+            );
+        }
+        vsix_paths.push(cached.to_string_lossy().into_owned());
+    }
+    Ok(vsix_paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: PathBuf, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn hashes_package_and_workspace_dependency_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        write(repo.join("pnpm-lock.yaml"), "lockfileVersion: 9");
+        write(
+            repo.join("packages/zowe-explorer/package.json"),
+            r#"{ "name": "vscode-extension-for-zowe", "engines": { "vscode": "^1.79.0" },
+                 "scripts": { "package": "vsce package" },
+                 "dependencies": { "@zowe/zowe-explorer-api": "workspace:*" } }"#,
+        );
+        write(repo.join("packages/zowe-explorer/src/extension.ts"), "a");
+        write(
+            repo.join("packages/zowe-explorer-api/package.json"),
+            r#"{ "name": "@zowe/zowe-explorer-api", "scripts": { "package": "npm pack" } }"#,
+        );
+        write(repo.join("packages/zowe-explorer-api/src/index.ts"), "a");
+
+        let packages = workspace_packages(repo);
+        assert_eq!(packages.len(), 1);
+        assert!(select(workspace_packages(repo), &["zowe-explorer".to_owned()]).is_ok());
+        assert!(select(
+            workspace_packages(repo),
+            &["vscode-extension-for-zowe".to_owned()]
+        )
+        .is_ok());
+        assert!(select(workspace_packages(repo), &["ftp".to_owned()]).is_err());
+
+        let package = &packages[0];
+        let hash = source_hash(repo, package).unwrap();
+        // Build output, dependencies and packaged VSIXes do not change the hash
+        write(package.dir.join("out/src/extension.js"), "b");
+        write(package.dir.join("node_modules/x/index.js"), "b");
+        write(
+            package.dir.join("vscode-extension-for-zowe-3.2.0.vsix"),
+            "b",
+        );
+        assert_eq!(source_hash(repo, package).unwrap(), hash);
+        // Sources of the package and its workspace dependencies do
+        write(repo.join("packages/zowe-explorer-api/src/index.ts"), "b");
+        let changed = source_hash(repo, package).unwrap();
+        assert_ne!(changed, hash);
+        write(package.dir.join("src/extension.ts"), "b");
+        assert_ne!(source_hash(repo, package).unwrap(), changed);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn fails_when_a_package_does_not_build() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        write(repo.join("pnpm-lock.yaml"), "lockfileVersion: 9");
+        write(repo.join("node_modules/.modules.yaml"), "");
+        write(
+            repo.join("packages/broken-ext/package.json"),
+            r#"{ "name": "zedc-broken-ext", "engines": { "vscode": "^1.79.0" },
+                 "scripts": { "package": "tsc && vsce package" } }"#,
+        );
+        write(repo.join("packages/broken-ext/src/extension.ts"), "a");
+
+        // A package manager whose `package` script fails, found first on the PATH
+        let bin = dir.path().join("bin");
+        write(bin.join("pnpm"), "#!/bin/sh\nexit 3\n");
+        std::fs::set_permissions(bin.join("pnpm"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let mut paths = vec![bin];
+        paths.extend(std::env::split_paths(&path));
+        std::env::set_var("PATH", std::env::join_paths(paths).unwrap());
+        let cache = dir.path().join("build-cache");
+        let result = build(&repo, &[], &cache);
+        std::env::set_var("PATH", path);
+
+        let error = format!("{:#}", result.unwrap_err());
+        assert!(
+            error.starts_with("Failed to build zedc-broken-ext"),
+            "{}",
+            error
+        );
+        assert!(
+            error.contains("`pnpm package` exited with a non-zero status"),
+            "{}",
+            error
+        );
+        assert!(!cache.join("zedc-broken-ext").exists());
+    }
+}
@@ -1,14 +1,34 @@
 //! Module for handling the `test local` sub-command.
 
+pub mod build;
+
+use crate::cmd::{SandboxConfig, VsCodeConfig};
+use crate::test::InstallReport;
+
 /// Downloads VS Code, resolves artifacts from the given file paths, installs them in VS Code and opens it.
+/// Returns the copy of VS Code that was used and the install result for each extension.
+///
+/// # Arguments
+/// * `vscode` - The VS Code version or archive to use (default: `latest`, or `compatible` to satisfy
+///   the `engines.vscode` requirement of every extension)
+/// * `files` - A `Vec` of relative file paths, directories, globs or URLs pointing to extensions to install
+/// * `sandbox` - The sandbox to install the extensions into
+pub async fn setup(
+    vscode: &VsCodeConfig,
+    files: Vec<String>,
+    sandbox: &SandboxConfig,
+) -> anyhow::Result<InstallReport> {
+    let resolved_paths = resolve(files).await;
+    let vscode = super::resolve_version(vscode, &resolved_paths)?;
+    let vsc = crate::code::download_vscode(&vscode).await?;
+    super::fs::install_from_paths(vsc, resolved_paths, sandbox).await
+}
+
+/// Resolves the given files, directories, globs and URLs to absolute paths of `.vsix` files, keeping
+/// the highest version of each extension and skipping invalid entries.
 ///
 /// # Arguments
-/// * `vsc_version` - (optional) The VS Code version to download (default: `latest`)
-/// * `files` - A `Vec` of relative file paths pointing to extensions to install
-pub async fn setup(vsc_version: Option<String>, files: Vec<String>) -> anyhow::Result<()> {
-    let vsc_bin = crate::code::download_vscode(vsc_version).await?;
-    let resolved_paths = super::fs::resolve_paths(files);
-    super::fs::install_from_paths(vsc_bin, resolved_paths).await?;
-
-    Ok(())
+/// * `files` - A `Vec` of relative file paths, directories, globs or URLs pointing to extensions to install
+pub async fn resolve(files: Vec<String>) -> Vec<String> {
+    super::fs::resolve_paths(files).await
 }
@@ -0,0 +1,245 @@
+//! Module for testing the same extensions against several VS Code versions in one command.
+//!
+//! Each version gets its own sandbox (`<sandbox>-<version>`), so the results of one version never
+//! leak into another.
+
+use crate::cmd::{SandboxConfig, VsCodeConfig};
+use crate::code::{Channel, VsCodeInstall};
+use crate::sandbox::{cli, zowe, InstalledExtension, LaunchOptions, Sandbox};
+use crate::test::compat;
+use crate::test::verify::{self, ExtensionInstall};
+use anyhow::bail;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::path::Path;
+
+/// A VS Code version to test against, as requested on the command line.
+pub struct MatrixEntry {
+    /// The requested version (`1.90.0`, `latest`, `insider` or `compatible`), also used in the
+    /// sandbox name.
+    pub label: String,
+    pub vscode: VsCodeConfig,
+}
+
+/// The outcome of preparing the sandbox for one VS Code version.
+#[derive(Serialize)]
+pub struct MatrixResult {
+    pub requested: String,
+    pub sandbox: String,
+    pub vscode: Option<VsCodeInstall>,
+    /// The install result for each requested `.vsix` file.
+    pub vsix: Vec<ExtensionInstall>,
+    /// All extensions installed in the sandbox.
+    pub extensions: Vec<InstalledExtension>,
+    pub errors: Vec<String>,
+}
+
+impl MatrixResult {
+    pub fn ok(&self) -> bool {
+        self.errors.is_empty() && self.vsix.iter().all(ExtensionInstall::ok)
+    }
+}
+
+/// Returns whether the test should run as a matrix: `--matrix`, `--smoke` or a comma-separated
+/// `--vsc-version`.
+pub fn requested(vscode: &VsCodeConfig, matrix: bool, smoke: bool) -> bool {
+    matrix
+        || smoke
+        || vscode
+            .vsc_version
+            .as_deref()
+            .is_some_and(|v| v.contains(','))
+}
+
+/// Builds the list of VS Code versions to test against.
+///
+/// # Arguments
+/// * `vscode` - The VS Code options; `vsc_version` may be a comma-separated list
+/// * `matrix` - Whether to test against the `engines.vscode` floor of the extensions and `latest`
+/// * `files` - The `.vsix` files that will be installed
+pub fn entries(
+    vscode: &VsCodeConfig,
+    matrix: bool,
+    files: &[String],
+) -> anyhow::Result<Vec<MatrixEntry>> {
+    if vscode.vscode_archive.is_some() {
+        bail!("--vscode-archive cannot be combined with multiple VS Code versions");
+    }
+
+    let mut labels = Vec::new();
+    if matrix {
+        match compat::engines_floor(files)? {
+            Some(floor) => labels.push(floor),
+            None => bail!(
+                "--matrix requires at least one extension with an `engines.vscode` requirement"
+            ),
+        }
+        labels.push("latest".to_owned());
+    } else {
+        let versions = vscode.vsc_version.as_deref().unwrap_or("latest");
+        labels.extend(
+            versions
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_owned),
+        );
+    }
+    let mut seen = std::collections::HashSet::new();
+    labels.retain(|label| seen.insert(label.clone()));
+
+    let mut entries = Vec::new();
+    for label in labels {
+        let mut config = vscode.clone();
+        if label == "insider" {
+            config.channel = Channel::Insider;
+            config.vsc_version = Some("latest".to_owned());
+        } else if label == compat::COMPATIBLE {
+            config.vsc_version = Some(compat::compatible_version(files)?);
+        } else {
+            config.vsc_version = Some(label.clone());
+        }
+        entries.push(MatrixEntry {
+            label,
+            vscode: config,
+        });
+    }
+    Ok(entries)
+}
+
+/// Prepares one sandbox per VS Code version and installs the same extensions into each.
+///
+/// With `smoke`, each sandbox is checked headlessly by listing its extensions through the Code CLI;
+/// otherwise, every sandbox that was prepared successfully is launched.
+///
+/// # Arguments
+/// * `entries` - The VS Code versions to test against
+/// * `files` - The `.vsix` files to install
+/// * `config` - The sandbox used as a prefix for the per-version sandbox names
+/// * `smoke` - Whether to run a headless smoke check instead of launching VS Code
+pub async fn run(
+    entries: Vec<MatrixEntry>,
+    files: Vec<String>,
+    config: &SandboxConfig,
+    smoke: bool,
+) -> anyhow::Result<Vec<MatrixResult>> {
+    let text = crate::output::text_enabled();
+    let options = LaunchOptions::from_config(config)?;
+    let zowe_config = zowe::from_config(config)?;
+    let mut results = Vec::new();
+    for entry in entries {
+        let sandbox = Sandbox::new(&format!("{}-{}", config.name, entry.label))?;
+        sandbox.set_launch_options(options.clone())?;
+        if let Some(zowe_config) = &zowe_config {
+            sandbox.write_zowe_config(zowe_config)?;
+        }
+        if text {
+            println!(
+                "\n{}",
+                format!("VS Code {} (sandbox: {})", entry.label, sandbox.name).underline()
+            );
+        }
+        let mut result = MatrixResult {
+            requested: entry.label,
+            sandbox: sandbox.name.clone(),
+            vscode: None,
+            vsix: Vec::new(),
+            extensions: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        let vsc = match crate::code::download_vscode(&entry.vscode).await {
+            Ok(vsc) => vsc,
+            Err(e) => {
+                result.errors.push(format!("{:#}", e));
+                results.push(result);
+                continue;
+            }
+        };
+        if let Some(version) = &config.install_cli {
+            if let Err(e) = cli::install(&sandbox, version, &config.cli_plugins) {
+                result.errors.push(format!("{:#}", e));
+            }
+        }
+        match super::fs::install_into_sandbox(&vsc, files.clone(), &sandbox).await {
+            Ok(vsix) => result.vsix = vsix,
+            Err(e) => result.errors.push(format!("{:#}", e)),
+        }
+
+        if smoke {
+            match verify::list_extensions(&sandbox, Path::new(&vsc.bin)) {
+                Ok(extensions) => result.extensions = extensions,
+                Err(e) => result.errors.push(format!("Smoke check failed: {:#}", e)),
+            }
+        } else {
+            result.extensions = sandbox.installed_extensions();
+            if result.ok() {
+                if let Err(e) = sandbox.launch(Path::new(&vsc.bin)) {
+                    result.errors.push(format!("{:#}", e));
+                }
+            }
+        }
+        result.vscode = Some(vsc);
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// Prints a summary of the matrix results in text mode.
+pub fn print_results(results: &[MatrixResult]) {
+    println!("\n{}", "Results".bold());
+    for r in results {
+        let version = r.vscode.as_ref().map(|v| v.version.as_str()).unwrap_or("-");
+        if r.ok() {
+            println!("  ✔️  {:<10} {:<16} {}", r.requested, version, r.sandbox);
+        } else {
+            println!(
+                "  ❌ {:<10} {:<16} {}",
+                r.requested,
+                version,
+                r.sandbox.red()
+            );
+        }
+        for ext in &r.extensions {
+            println!("       {}@{}", ext.id, ext.version.dimmed());
+        }
+        for vsix in r.vsix.iter().filter(|v| !v.ok()) {
+            println!(
+                "       {}: {}",
+                vsix.file,
+                vsix.error.as_deref().unwrap_or("not installed").italic()
+            );
+        }
+        for e in &r.errors {
+            println!("       {}", e.italic());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_version_lists() {
+        let vscode = VsCodeConfig {
+            vsc_version: Some("1.90.0, latest,insider".to_owned()),
+            ..Default::default()
+        };
+        assert!(requested(&vscode, false, false));
+        let entries = entries(&vscode, false, &[]).unwrap();
+        let labels: Vec<_> = entries.iter().map(|e| e.label.as_str()).collect();
+        assert_eq!(labels, ["1.90.0", "latest", "insider"]);
+        assert_eq!(entries[2].vscode.channel, Channel::Insider);
+        assert_eq!(entries[2].vscode.vsc_version.as_deref(), Some("latest"));
+
+        // Repeated versions are only tested once, in the order they were first given
+        let vscode = VsCodeConfig {
+            vsc_version: Some("latest,1.90.0,latest,1.90.0".to_owned()),
+            ..Default::default()
+        };
+        let repeated = super::entries(&vscode, false, &[]).unwrap();
+        let labels: Vec<_> = repeated.iter().map(|e| e.label.as_str()).collect();
+        assert_eq!(labels, ["latest", "1.90.0"]);
+    }
+}
@@ -1,10 +1,15 @@
 //! "Root" module for handling `test` commands.
 
 mod cmd;
+mod compat;
+mod deps;
 mod fs;
+mod matrix;
+mod verify;
 
 pub mod coverage;
 pub mod ghr;
 pub mod local;
 pub use cmd::{handle_cmd, Commands};
-pub use fs::{install_cli, install_from_paths};
+pub use compat::resolve_version;
+pub use fs::{install_from_paths, InstallReport};
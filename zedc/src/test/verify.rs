@@ -0,0 +1,222 @@
+//! Module for checking which extensions actually got installed into a sandbox.
+
+use crate::sandbox::{InstalledExtension, Sandbox};
+use crate::vsix::VsixManifest;
+use anyhow::bail;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::{path::Path, process::Stdio};
+
+/// The outcome of installing a single `.vsix` file.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallStatus {
+    /// The extension is installed with the version from the `.vsix` file.
+    Installed,
+    /// The extension is installed, but with a different version than the `.vsix` file.
+    VersionMismatch,
+    /// The Code CLI reported success, but the extension is not installed.
+    Missing,
+    /// The Code CLI failed to install the `.vsix` file.
+    Failed,
+    /// The `engines.vscode` requirement of the extension excludes the VS Code version, so the
+    /// `.vsix` file was not installed.
+    Incompatible,
+    /// The installed extensions could not be listed, so the install could not be checked.
+    Unverified,
+}
+
+/// The install result for one requested `.vsix` file.
+#[derive(Serialize, Clone, Debug)]
+pub struct ExtensionInstall {
+    pub file: String,
+    /// The extension identifier from the `.vsix` manifest (`<publisher>.<name>`).
+    pub id: Option<String>,
+    /// The version from the `.vsix` manifest.
+    pub expected_version: Option<String>,
+    /// The version reported by `code --list-extensions --show-versions`.
+    pub installed_version: Option<String>,
+    pub status: InstallStatus,
+    pub error: Option<String>,
+    /// Problems that did not prevent the install, such as dependencies missing from the sandbox.
+    pub warnings: Vec<String>,
+}
+
+impl ExtensionInstall {
+    /// Creates a result for a `.vsix` file, before its install has been checked.
+    pub fn new(file: &str, manifest: Option<&VsixManifest>) -> Self {
+        Self {
+            file: file.to_owned(),
+            id: manifest.map(VsixManifest::id),
+            expected_version: manifest.map(|m| m.version.clone()),
+            installed_version: None,
+            status: InstallStatus::Unverified,
+            error: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn ok(&self) -> bool {
+        self.status == InstallStatus::Installed
+    }
+
+    /// Compares this result with the extensions reported by the Code CLI and updates its status.
+    /// Results that already failed to install or were not installed are left unchanged.
+    pub fn verify(&mut self, installed: &[InstalledExtension]) {
+        if self.failed_early() {
+            return;
+        }
+        let Some(id) = &self.id else {
+            self.status = InstallStatus::Unverified;
+            self.error = Some("Could not read the extension manifest from the .vsix file".into());
+            return;
+        };
+        match installed.iter().find(|e| e.id.eq_ignore_ascii_case(id)) {
+            None => {
+                self.status = InstallStatus::Missing;
+                self.error = Some(format!("{} is not listed by the Code CLI", id));
+            }
+            Some(ext) => {
+                self.installed_version = Some(ext.version.clone());
+                if self.expected_version.as_deref() == Some(ext.version.as_str()) {
+                    self.status = InstallStatus::Installed;
+                } else {
+                    self.status = InstallStatus::VersionMismatch;
+                    self.error = Some(format!(
+                        "Expected version {}, but {} is installed",
+                        self.expected_version.as_deref().unwrap_or("unknown"),
+                        ext.version
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Whether the `.vsix` file already failed before it could be checked against the sandbox.
+    pub fn failed_early(&self) -> bool {
+        matches!(
+            self.status,
+            InstallStatus::Failed | InstallStatus::Incompatible
+        )
+    }
+
+    /// Prints the result as a single line in text mode, followed by any warnings.
+    pub fn print(&self) {
+        let name = match (&self.id, &self.installed_version) {
+            (Some(id), Some(version)) => format!("{}@{}", id, version),
+            (Some(id), None) => id.clone(),
+            _ => self.file.clone(),
+        };
+        if self.ok() {
+            println!("  ✔️  {}", name.bold());
+        } else {
+            println!(
+                "  ❌ {}: {}",
+                name.bold(),
+                self.error.as_deref().unwrap_or("not installed").italic()
+            );
+        }
+        for warning in &self.warnings {
+            println!("     ⚠️  {}", warning.yellow());
+        }
+    }
+}
+
+/// Lists the extensions installed in a sandbox using the Code CLI, without opening a window.
+pub fn list_extensions(
+    sandbox: &Sandbox,
+    vsc_bin: &Path,
+) -> anyhow::Result<Vec<InstalledExtension>> {
+    let output = sandbox
+        .code_command(vsc_bin)
+        .args(["--list-extensions", "--show-versions"])
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "Code CLI exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(parse_extension_list(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Parses the `<id>@<version>` lines printed by `code --list-extensions --show-versions`.
+fn parse_extension_list(output: &str) -> Vec<InstalledExtension> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (id, version) = line.trim().rsplit_once('@')?;
+            Some(InstalledExtension {
+                id: id.to_owned(),
+                version: version.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Returns the most useful error text from the output of a failed Code CLI command.
+pub fn cli_error(output: &std::process::Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let text = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    if text.is_empty() {
+        format!("Code CLI exited with {}", output.status)
+    } else {
+        text.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(version: &str) -> VsixManifest {
+        VsixManifest {
+            publisher: "Zowe".to_owned(),
+            name: "vscode-extension-for-zowe".to_owned(),
+            version: version.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_listed_extensions() {
+        let extensions = parse_extension_list(
+            "zowe.vscode-extension-for-zowe@3.2.0\nzowe.zowe-explorer-ftp-extension@3.2.0\n",
+        );
+        assert_eq!(extensions.len(), 2);
+        assert_eq!(extensions[0].id, "zowe.vscode-extension-for-zowe");
+        assert_eq!(extensions[1].version, "3.2.0");
+    }
+
+    #[test]
+    fn matches_installed_versions() {
+        let installed = parse_extension_list("zowe.vscode-extension-for-zowe@3.2.0\n");
+
+        let mut result = ExtensionInstall::new("ze.vsix", Some(&manifest("3.2.0")));
+        result.verify(&installed);
+        assert_eq!(result.status, InstallStatus::Installed);
+        assert_eq!(result.installed_version.as_deref(), Some("3.2.0"));
+
+        let mut result = ExtensionInstall::new("ze.vsix", Some(&manifest("3.3.0")));
+        result.verify(&installed);
+        assert_eq!(result.status, InstallStatus::VersionMismatch);
+
+        let mut result = ExtensionInstall::new("ze.vsix", Some(&manifest("3.2.0")));
+        result.verify(&[]);
+        assert_eq!(result.status, InstallStatus::Missing);
+
+        let mut result = ExtensionInstall::new("ze.vsix", Some(&manifest("3.2.0")));
+        result.status = InstallStatus::Failed;
+        result.verify(&installed);
+        assert_eq!(result.status, InstallStatus::Failed);
+    }
+}
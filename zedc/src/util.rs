@@ -1,7 +1,7 @@
 //! Utility module containing general helper functions.
 
 use glob::glob;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Removes new line characters from the given String.
 pub fn trim_newline(line: &mut String) {
@@ -43,3 +43,137 @@ pub fn find_dir_match(patterns: &[&str]) -> anyhow::Result<Option<PathBuf>> {
 
     Ok(None)
 }
+
+/// Returns the path of the `zedc_data` directory next to the `zedc` binary.
+///
+/// All cached VS Code versions, sandboxes and downloaded VSIX files live under this directory.
+pub fn data_dir() -> anyhow::Result<PathBuf> {
+    let current_exe = std::env::current_exe()?;
+    match current_exe.parent() {
+        Some(dir) => Ok(dir.join("zedc_data")),
+        None => anyhow::bail!("Could not resolve zedc executable directory"),
+    }
+}
+
+/// Returns the total size in bytes of all files below the given path.
+/// Symbolic links are not followed; unreadable entries are skipped.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| dir_size(&e.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Formats a byte count for display, e.g. `6.9 MB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Returns the current time as seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Formats a Unix timestamp relative to now, e.g. `3 hours ago`.
+pub fn format_age(timestamp: u64) -> String {
+    let elapsed = unix_now().saturating_sub(timestamp);
+    let (value, unit) = match elapsed {
+        0..=59 => return "just now".to_owned(),
+        60..=3599 => (elapsed / 60, "minute"),
+        3600..=86399 => (elapsed / 3600, "hour"),
+        _ => (elapsed / 86400, "day"),
+    };
+    format!(
+        "{} {}{} ago",
+        value,
+        unit,
+        if value == 1 { "" } else { "s" }
+    )
+}
+
+/// Parses a JSON file that may contain comments and trailing commas, like VS Code's `settings.json`.
+pub fn parse_jsonc(text: &str) -> serde_json::Result<serde_json::Value> {
+    // First drop comments, then drop commas that are followed by a closing bracket or brace.
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            stripped.push(c);
+            match c {
+                '\\' => stripped.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        stripped.push(c);
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            _ => {
+                in_string = c == '"';
+                stripped.push(c);
+            }
+        }
+    }
+
+    let mut out = String::with_capacity(stripped.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in stripped.char_indices() {
+        if in_string {
+            in_string = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ','
+            && matches!(
+                stripped[i + 1..].trim_start().chars().next(),
+                Some('}' | ']')
+            )
+        {
+            continue;
+        }
+        out.push(c);
+    }
+    serde_json::from_str(&out)
+}
@@ -0,0 +1,320 @@
+//! Checks `.vsix` files against the size budgets configured for the repository.
+//!
+//! Budgets are read from the `vsixBudgets` object in `zedc.config.json` at the repository root,
+//! keyed by package name (`vscode-extension-for-zowe`) or extension ID
+//! (`zowe.vscode-extension-for-zowe`). Limits are either a number of bytes or a string such as
+//! `"8 MB"`, and apply to the size of the `.vsix` file on disk.
+
+use super::inspect::{folder_sizes, largest_files, FolderSize};
+use super::{Vsix, VsixEntry, VsixManifest};
+use crate::util::format_bytes;
+use anyhow::{bail, Context};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// The name of the repository config file that holds the budgets.
+pub const CONFIG_FILE: &str = "zedc.config.json";
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Limit {
+    Bytes(u64),
+    Text(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RepoConfig {
+    #[serde(default)]
+    vsix_budgets: BTreeMap<String, Limit>,
+}
+
+/// The size budgets configured for a repository.
+pub struct Budgets {
+    /// The size limit in bytes for each package name or extension ID, in lowercase.
+    pub limits: BTreeMap<String, u64>,
+}
+
+impl Budgets {
+    /// Reads the budgets from a `zedc.config.json` file.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the config file
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: RepoConfig = serde_json::from_str(&text)
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+
+        let mut limits = BTreeMap::new();
+        for (package, limit) in config.vsix_budgets {
+            let bytes = match limit {
+                Limit::Bytes(bytes) => bytes,
+                Limit::Text(text) => parse_size(&text).with_context(|| {
+                    format!("Invalid size budget for {} in {}", package, path.display())
+                })?,
+            };
+            limits.insert(package.to_lowercase(), bytes);
+        }
+        if limits.is_empty() {
+            bail!("No `vsixBudgets` are configured in {}", path.display());
+        }
+        Ok(Self { limits })
+    }
+
+    /// Returns the budget for an extension, matched by extension ID first and package name second.
+    pub fn limit_for(&self, manifest: &VsixManifest) -> Option<u64> {
+        self.limits
+            .get(&manifest.id())
+            .or_else(|| self.limits.get(&manifest.name.to_lowercase()))
+            .copied()
+    }
+}
+
+/// Parses a size such as `8 MB`, `512KB` or `1048576`, using 1024-based units like the rest of zedc.
+pub fn parse_size(text: &str) -> anyhow::Result<u64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .with_context(|| format!("`{}` is not a size", text))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "KB" | "K" => 1024,
+        "MB" | "M" => 1024 * 1024,
+        "GB" | "G" => 1024 * 1024 * 1024,
+        other => bail!("Unknown size unit `{}` in `{}`", other, text),
+    };
+    Ok((number * multiplier as f64).round() as u64)
+}
+
+/// The budget check for one `.vsix` file.
+#[derive(Serialize)]
+pub struct BudgetCheck {
+    pub file: String,
+    pub id: String,
+    pub version: String,
+    /// The size of the `.vsix` file on disk.
+    pub size: u64,
+    /// The configured budget, or `None` if the package has no budget.
+    pub budget: Option<u64>,
+    /// How many bytes the file is over its budget.
+    pub overage: u64,
+    /// The compressed size per top-level folder, largest first. Only listed when over budget.
+    pub folders: Vec<FolderSize>,
+    /// The files taking up the most space in the archive. Only listed when over budget.
+    pub largest_files: Vec<VsixEntry>,
+}
+
+impl BudgetCheck {
+    /// Checks a `.vsix` file against its budget, listing the `top` contributors when it is over.
+    pub fn new(vsix: &Vsix, budgets: &Budgets, top: usize) -> Self {
+        let budget = budgets.limit_for(&vsix.manifest);
+        let overage = budget.map_or(0, |b| vsix.file_size.saturating_sub(b));
+
+        let (mut folders, largest_files) = if overage > 0 {
+            // The budget applies to the file on disk, so rank entries by their compressed size.
+            let packed: Vec<VsixEntry> = vsix
+                .entries
+                .iter()
+                .map(|e| VsixEntry {
+                    size: e.compressed_size,
+                    ..e.clone()
+                })
+                .collect();
+            (folder_sizes(&packed), largest_files(&packed, top))
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        folders.truncate(top);
+
+        Self {
+            file: vsix.path.to_string_lossy().into_owned(),
+            id: vsix.manifest.id(),
+            version: vsix.manifest.version.clone(),
+            size: vsix.file_size,
+            budget,
+            overage,
+            folders,
+            largest_files,
+        }
+    }
+
+    pub fn exceeded(&self) -> bool {
+        self.overage > 0
+    }
+
+    /// Prints the result in human-readable form.
+    pub fn print(&self) {
+        let name = format!("{}@{}", self.id, self.version);
+        let Some(budget) = self.budget else {
+            println!(
+                "  ➖ {} {}",
+                name.bold(),
+                format!("({}, no budget configured)", format_bytes(self.size)).dimmed()
+            );
+            return;
+        };
+        if !self.exceeded() {
+            println!(
+                "  ✔️  {} {} / {}",
+                name.bold(),
+                format_bytes(self.size),
+                format_bytes(budget)
+            );
+            return;
+        }
+
+        println!(
+            "  ❌ {} {} / {} ({} over budget)",
+            name.bold(),
+            format_bytes(self.size).red(),
+            format_bytes(budget),
+            format_bytes(self.overage).red()
+        );
+        println!("     {}", "Largest folders (compressed)".underline());
+        for folder in &self.folders {
+            println!(
+                "     {:>10}  {:<24} {} file{}",
+                format_bytes(folder.size),
+                folder.folder,
+                folder.files,
+                if folder.files == 1 { "" } else { "s" }
+            );
+        }
+        println!("     {}", "Largest files (compressed)".underline());
+        for file in &self.largest_files {
+            println!("     {:>10}  {}", format_bytes(file.size), file.path);
+        }
+    }
+}
+
+/// The result of `zedc vsix budget`.
+#[derive(Serialize)]
+pub struct BudgetReport {
+    pub ok: bool,
+    /// The config file the budgets were read from.
+    pub config: String,
+    pub packages: Vec<BudgetCheck>,
+}
+
+/// Finds the `zedc.config.json` of the current repository by searching upward.
+pub fn find_config() -> anyhow::Result<PathBuf> {
+    match crate::util::find_dir_match(&[CONFIG_FILE])? {
+        Some(dir) => Ok(dir.join(CONFIG_FILE)),
+        None => bail!(
+            "Could not find {} in the current directory or any of its parents",
+            CONFIG_FILE
+        ),
+    }
+}
+
+/// Returns the `.vsix` files in the `dist` folder next to the config file.
+pub fn dist_files(config: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let dist = config.parent().unwrap_or(Path::new(".")).join("dist");
+    let mut files: Vec<PathBuf> = glob::glob(&dist.join("*.vsix").to_string_lossy())?
+        .filter_map(Result::ok)
+        .collect();
+    if files.is_empty() {
+        bail!(
+            "No .vsix files found in {}; package the extensions first or pass the files to check",
+            dist.display()
+        );
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Checks each `.vsix` file against the budgets in a config file.
+///
+/// # Arguments
+/// * `config` - The path to `zedc.config.json`
+/// * `files` - The `.vsix` files to check
+/// * `top` - The number of folders and files to list for packages that are over budget
+pub fn check(config: &Path, files: &[PathBuf], top: usize) -> anyhow::Result<BudgetReport> {
+    let budgets = Budgets::load(config)?;
+    let packages = files
+        .iter()
+        .map(|file| Ok(BudgetCheck::new(&Vsix::open(file)?, &budgets, top)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(BudgetReport {
+        ok: !packages.iter().any(BudgetCheck::exceeded),
+        config: config.to_string_lossy().into_owned(),
+        packages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vsix::tests::write_vsix;
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("512 KB").unwrap(), 512 * 1024);
+        assert_eq!(parse_size("7.5MB").unwrap(), 7_864_320);
+        assert_eq!(parse_size("1 gb").unwrap(), 1024 * 1024 * 1024);
+        assert!(parse_size("7 parsecs").is_err());
+        assert!(parse_size("MB").is_err());
+    }
+
+    #[test]
+    fn reports_packages_over_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join(CONFIG_FILE);
+        std::fs::write(
+            &config,
+            r#"{"vsixBudgets":{"vscode-extension-for-zowe":100,"zowe.zowe-explorer-ftp-extension":"1 MB"}}"#,
+        )
+        .unwrap();
+        let large: Vec<u8> = (0..4096u32).map(|i| (i * 7919 % 251) as u8).collect();
+        let files = [
+            write_vsix(
+                dir.path(),
+                "ze.vsix",
+                r#"{"publisher":"Zowe","name":"vscode-extension-for-zowe","version":"3.2.0"}"#,
+                &[
+                    ("extension/out/extension.js", &large),
+                    ("extension/README.md", b"# ZE"),
+                ],
+            ),
+            write_vsix(
+                dir.path(),
+                "ftp.vsix",
+                r#"{"publisher":"Zowe","name":"zowe-explorer-ftp-extension","version":"3.2.0"}"#,
+                &[],
+            ),
+            write_vsix(
+                dir.path(),
+                "api.vsix",
+                r#"{"publisher":"Zowe","name":"zowex-for-zowe-explorer","version":"3.2.0"}"#,
+                &[],
+            ),
+        ];
+
+        let report = check(&config, &files, 1).unwrap();
+        assert!(!report.ok);
+
+        let ze = &report.packages[0];
+        assert!(ze.exceeded());
+        assert_eq!(ze.overage, ze.size - 100);
+        assert_eq!(ze.folders.len(), 1);
+        assert_eq!(ze.folders[0].folder, "out");
+        assert_eq!(ze.largest_files[0].path, "extension/out/extension.js");
+
+        let ftp = &report.packages[1];
+        assert_eq!(ftp.budget, Some(1024 * 1024));
+        assert!(!ftp.exceeded());
+        assert!(ftp.folders.is_empty());
+
+        assert_eq!(report.packages[2].budget, None);
+    }
+}
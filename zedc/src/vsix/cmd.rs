@@ -0,0 +1,82 @@
+//! Command module for handling `vsix` commands.
+
+use super::budget;
+use super::diff::VsixDiff;
+use super::inspect::Inspection;
+use super::Vsix;
+use crate::output::{self, exit};
+use std::path::PathBuf;
+
+#[derive(clap::Subcommand)]
+pub enum Commands {
+    /// Show the manifest, dependencies, activation events and size breakdown of a .vsix file
+    Inspect {
+        /// Path to the .vsix file
+        file: PathBuf,
+        /// Number of largest files to list
+        #[arg(long, value_name = "N", default_value_t = 10)]
+        top: usize,
+    },
+    /// Compare the files, sizes and package.json of two builds of an extension
+    Diff {
+        /// Path to the old .vsix file
+        old: PathBuf,
+        /// Path to the new .vsix file
+        new: PathBuf,
+    },
+    /// Check .vsix files against the size budgets in zedc.config.json
+    Budget {
+        /// The .vsix files to check (defaults to the .vsix files in `dist/`)
+        files: Vec<PathBuf>,
+        /// Number of folders and files to list for packages over budget
+        #[arg(long, value_name = "N", default_value_t = 5)]
+        top: usize,
+    },
+}
+
+/// Handles the logic for the `zedc vsix` command.
+///
+/// # Arguments
+/// * `cmd` - The `vsix` subcommand to run
+pub async fn handle_cmd(cmd: Commands) -> anyhow::Result<i32> {
+    match cmd {
+        Commands::Inspect { file, top } => {
+            let inspection = Inspection::new(&Vsix::open(&file)?, top);
+            if output::json_enabled() {
+                output::emit_json(&inspection);
+            } else {
+                inspection.print();
+            }
+        }
+        Commands::Diff { old, new } => {
+            let diff = VsixDiff::new(&Vsix::open(&old)?, &Vsix::open(&new)?);
+            if output::json_enabled() {
+                output::emit_json(&diff);
+            } else {
+                diff.print();
+            }
+        }
+        Commands::Budget { files, top } => {
+            let config = budget::find_config()?;
+            let files = if files.is_empty() {
+                budget::dist_files(&config)?
+            } else {
+                files
+            };
+            let report = budget::check(&config, &files, top)?;
+            if output::json_enabled() {
+                output::emit_json(&report);
+            } else {
+                println!("📦 Checking VSIX sizes against {}", report.config);
+                for package in &report.packages {
+                    package.print();
+                }
+            }
+            if !report.ok {
+                return Ok(exit::BUDGET_EXCEEDED);
+            }
+        }
+    }
+
+    Ok(exit::SUCCESS)
+}
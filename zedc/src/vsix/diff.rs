@@ -0,0 +1,442 @@
+//! Compares two builds of an extension for `zedc vsix diff`.
+
+use super::inspect::folder_sizes;
+use super::{Vsix, VsixEntry};
+use crate::util::format_bytes;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A file that was added, removed or changed between two builds.
+#[derive(Serialize)]
+pub struct FileChange {
+    pub path: String,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+    pub delta: i64,
+}
+
+/// The change in size of one top-level folder of the extension.
+#[derive(Serialize)]
+pub struct FolderChange {
+    pub folder: String,
+    pub old_size: u64,
+    pub new_size: u64,
+    pub delta: i64,
+}
+
+/// A scalar `package.json` field that differs between two builds.
+#[derive(Serialize)]
+pub struct ValueChange {
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Entries of a `package.json` list or map that were added, removed or changed.
+#[derive(Serialize, Default)]
+pub struct SetChange {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl SetChange {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// The differences between the `package.json` of two builds.
+#[derive(Serialize)]
+pub struct ManifestDiff {
+    pub version: Option<ValueChange>,
+    pub vscode_engine: Option<ValueChange>,
+    pub commands: SetChange,
+    pub settings: SetChange,
+    pub activation_events: SetChange,
+    pub extension_dependencies: SetChange,
+}
+
+/// The result of `zedc vsix diff`.
+#[derive(Serialize)]
+pub struct VsixDiff {
+    pub old: String,
+    pub new: String,
+    pub old_file_size: u64,
+    pub new_file_size: u64,
+    pub file_size_delta: i64,
+    pub uncompressed_size_delta: i64,
+    pub added: Vec<FileChange>,
+    pub removed: Vec<FileChange>,
+    pub changed: Vec<FileChange>,
+    /// Folders whose size changed, largest change first.
+    pub folders: Vec<FolderChange>,
+    pub manifest: ManifestDiff,
+    /// Changes that are likely to be packaging mistakes.
+    pub warnings: Vec<String>,
+}
+
+fn delta(old: u64, new: u64) -> i64 {
+    new as i64 - old as i64
+}
+
+/// Formats a size difference with an explicit sign (e.g. `+1.2 MB`).
+fn format_delta(delta: i64) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    format!("{}{}", sign, format_bytes(delta.unsigned_abs()))
+}
+
+impl VsixDiff {
+    /// Compares an old and a new build of an extension.
+    pub fn new(old: &Vsix, new: &Vsix) -> Self {
+        let old_files: BTreeMap<&str, &VsixEntry> =
+            old.entries.iter().map(|e| (e.path.as_str(), e)).collect();
+        let new_files: BTreeMap<&str, &VsixEntry> =
+            new.entries.iter().map(|e| (e.path.as_str(), e)).collect();
+
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+        for (path, entry) in &new_files {
+            match old_files.get(path) {
+                None => added.push(FileChange {
+                    path: path.to_string(),
+                    old_size: None,
+                    new_size: Some(entry.size),
+                    delta: entry.size as i64,
+                }),
+                Some(previous) if previous.crc32 != entry.crc32 || previous.size != entry.size => {
+                    changed.push(FileChange {
+                        path: path.to_string(),
+                        old_size: Some(previous.size),
+                        new_size: Some(entry.size),
+                        delta: delta(previous.size, entry.size),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        let removed: Vec<FileChange> = old_files
+            .iter()
+            .filter(|(path, _)| !new_files.contains_key(*path))
+            .map(|(path, entry)| FileChange {
+                path: path.to_string(),
+                old_size: Some(entry.size),
+                new_size: None,
+                delta: -(entry.size as i64),
+            })
+            .collect();
+
+        let old_folders: BTreeMap<String, u64> = folder_sizes(&old.entries)
+            .into_iter()
+            .map(|f| (f.folder, f.size))
+            .collect();
+        let new_folders: BTreeMap<String, u64> = folder_sizes(&new.entries)
+            .into_iter()
+            .map(|f| (f.folder, f.size))
+            .collect();
+        let mut folders: Vec<FolderChange> = old_folders
+            .keys()
+            .chain(new_folders.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|folder| {
+                let old_size = old_folders.get(folder).copied().unwrap_or(0);
+                let new_size = new_folders.get(folder).copied().unwrap_or(0);
+                FolderChange {
+                    folder: folder.clone(),
+                    old_size,
+                    new_size,
+                    delta: delta(old_size, new_size),
+                }
+            })
+            .filter(|f| f.delta != 0)
+            .collect();
+        folders.sort_by_key(|f| std::cmp::Reverse(f.delta.unsigned_abs()));
+
+        let warnings = warnings(&added, &removed);
+        Self {
+            old: old.path.to_string_lossy().into_owned(),
+            new: new.path.to_string_lossy().into_owned(),
+            old_file_size: old.file_size,
+            new_file_size: new.file_size,
+            file_size_delta: delta(old.file_size, new.file_size),
+            uncompressed_size_delta: delta(old.uncompressed_size(), new.uncompressed_size()),
+            added,
+            removed,
+            changed,
+            folders,
+            manifest: diff_manifest(&old.package_json, &new.package_json),
+            warnings,
+        }
+    }
+
+    /// Prints the differences in human-readable form.
+    pub fn print(&self) {
+        println!(
+            "{} {} → {} ({})",
+            "Size:".bold(),
+            format_bytes(self.old_file_size),
+            format_bytes(self.new_file_size),
+            format_delta(self.file_size_delta)
+        );
+        println!(
+            "{} {} added, {} removed, {} changed",
+            "Files:".bold(),
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        );
+
+        if !self.folders.is_empty() {
+            println!("\n{}", "Size change by folder".underline());
+            for f in &self.folders {
+                println!(
+                    "  {:>12}  {:<24} {} → {}",
+                    format_delta(f.delta),
+                    f.folder,
+                    format_bytes(f.old_size),
+                    format_bytes(f.new_size)
+                );
+            }
+        }
+
+        for (title, files, marker) in [
+            ("Added files", &self.added, "+".green().to_string()),
+            ("Removed files", &self.removed, "-".red().to_string()),
+            ("Changed files", &self.changed, "~".yellow().to_string()),
+        ] {
+            if files.is_empty() {
+                continue;
+            }
+            println!("\n{}", title.underline());
+            for f in files {
+                println!("  {} {:>12}  {}", marker, format_delta(f.delta), f.path);
+            }
+        }
+
+        let m = &self.manifest;
+        println!("\n{}", "package.json".underline());
+        for (name, change) in [
+            ("version", &m.version),
+            ("engines.vscode", &m.vscode_engine),
+        ] {
+            if let Some(c) = change {
+                println!(
+                    "  {}: {} → {}",
+                    name,
+                    c.old.as_deref().unwrap_or("(none)"),
+                    c.new.as_deref().unwrap_or("(none)")
+                );
+            }
+        }
+        for (name, change) in [
+            ("commands", &m.commands),
+            ("settings", &m.settings),
+            ("activationEvents", &m.activation_events),
+            ("extensionDependencies", &m.extension_dependencies),
+        ] {
+            if change.is_empty() {
+                continue;
+            }
+            println!("  {}:", name);
+            for a in &change.added {
+                println!("    {} {}", "+".green(), a);
+            }
+            for r in &change.removed {
+                println!("    {} {}", "-".red(), r);
+            }
+            for c in &change.changed {
+                println!("    {} {}", "~".yellow(), c);
+            }
+        }
+
+        for w in &self.warnings {
+            println!("\n⚠️  {}", w.yellow());
+        }
+    }
+}
+
+/// Flags changes that usually indicate a packaging mistake, such as bundled `node_modules`.
+fn warnings(added: &[FileChange], removed: &[FileChange]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let node_modules: Vec<&FileChange> = added
+        .iter()
+        .filter(|f| f.path.contains("node_modules/"))
+        .collect();
+    if !node_modules.is_empty() {
+        warnings.push(format!(
+            "{} files were added under node_modules ({}); check .vscodeignore and the bundler config",
+            node_modules.len(),
+            format_bytes(node_modules.iter().map(|f| f.delta.unsigned_abs()).sum())
+        ));
+    }
+    let resources = removed
+        .iter()
+        .filter(|f| f.path.starts_with("extension/resources/"))
+        .count();
+    if resources > 0 {
+        warnings.push(format!(
+            "{} files were removed from resources/; make sure they are no longer referenced",
+            resources
+        ));
+    }
+    warnings
+}
+
+/// Returns a string field from a JSON value at the given pointer.
+fn string_at(json: &Value, pointer: &str) -> Option<String> {
+    json.pointer(pointer)?.as_str().map(str::to_owned)
+}
+
+/// Returns a change if the string at `pointer` differs between the two manifests.
+fn value_change(old: &Value, new: &Value, pointer: &str) -> Option<ValueChange> {
+    let (old, new) = (string_at(old, pointer), string_at(new, pointer));
+    (old != new).then_some(ValueChange { old, new })
+}
+
+/// Collects the commands contributed by an extension, keyed by command ID.
+fn commands(json: &Value) -> BTreeMap<String, Value> {
+    json.pointer("/contributes/commands")
+        .and_then(Value::as_array)
+        .map(|commands| {
+            commands
+                .iter()
+                .filter_map(|c| Some((c.get("command")?.as_str()?.to_owned(), c.clone())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Collects the settings contributed by an extension, keyed by setting name.
+/// `contributes.configuration` can be a single object or an array of sections.
+fn settings(json: &Value) -> BTreeMap<String, Value> {
+    let sections = match json.pointer("/contributes/configuration") {
+        Some(Value::Array(sections)) => sections.iter().collect(),
+        Some(section) => vec![section],
+        None => Vec::new(),
+    };
+    sections
+        .into_iter()
+        .filter_map(|s| s.get("properties")?.as_object())
+        .flat_map(|props| props.iter().map(|(k, v)| (k.clone(), v.clone())))
+        .collect()
+}
+
+/// Collects a list of strings (e.g. `activationEvents`) as a map without values.
+fn string_list(json: &Value, pointer: &str) -> BTreeMap<String, Value> {
+    json.pointer(pointer)
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|i| Some((i.as_str()?.to_owned(), Value::Null)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Compares two keyed collections from `package.json`.
+fn set_change(old: BTreeMap<String, Value>, new: BTreeMap<String, Value>) -> SetChange {
+    let mut change = SetChange::default();
+    for (key, value) in &new {
+        match old.get(key) {
+            None => change.added.push(key.clone()),
+            Some(previous) if previous != value => change.changed.push(key.clone()),
+            Some(_) => {}
+        }
+    }
+    change.removed = old
+        .into_keys()
+        .filter(|key| !new.contains_key(key))
+        .collect();
+    change
+}
+
+/// Compares the fields of two `package.json` files that matter to reviewers.
+fn diff_manifest(old: &Value, new: &Value) -> ManifestDiff {
+    ManifestDiff {
+        version: value_change(old, new, "/version"),
+        vscode_engine: value_change(old, new, "/engines/vscode"),
+        commands: set_change(commands(old), commands(new)),
+        settings: set_change(settings(old), settings(new)),
+        activation_events: set_change(
+            string_list(old, "/activationEvents"),
+            string_list(new, "/activationEvents"),
+        ),
+        extension_dependencies: set_change(
+            string_list(old, "/extensionDependencies"),
+            string_list(new, "/extensionDependencies"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vsix::tests::write_vsix;
+
+    #[test]
+    fn diffs_manifests() {
+        let old = serde_json::json!({
+            "version": "3.1.0",
+            "activationEvents": ["onStartupFinished"],
+            "contributes": {
+                "commands": [{ "command": "zowe.refresh", "title": "Refresh" }],
+                "configuration": [{ "properties": { "zowe.a": { "default": 1 }, "zowe.b": {} } }]
+            }
+        });
+        let new = serde_json::json!({
+            "version": "3.2.0",
+            "activationEvents": ["onStartupFinished", "onView:zowe"],
+            "contributes": {
+                "commands": [{ "command": "zowe.refresh", "title": "Refresh All" }],
+                "configuration": { "properties": { "zowe.a": { "default": 2 }, "zowe.c": {} } }
+            }
+        });
+        let diff = diff_manifest(&old, &new);
+        let version = diff.version.unwrap();
+        assert_eq!(version.old.as_deref(), Some("3.1.0"));
+        assert_eq!(version.new.as_deref(), Some("3.2.0"));
+        assert!(diff.vscode_engine.is_none());
+        assert_eq!(diff.commands.changed, ["zowe.refresh"]);
+        assert_eq!(diff.settings.added, ["zowe.c"]);
+        assert_eq!(diff.settings.removed, ["zowe.b"]);
+        assert_eq!(diff.settings.changed, ["zowe.a"]);
+        assert_eq!(diff.activation_events.added, ["onView:zowe"]);
+    }
+
+    #[test]
+    fn diffs_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = r#"{"publisher":"Zowe","name":"ze","version":"3.2.0"}"#;
+        let old = write_vsix(
+            dir.path(),
+            "old.vsix",
+            manifest,
+            &[
+                ("extension/out/extension.js", b"old"),
+                ("extension/resources/icon.png", b"png"),
+            ],
+        );
+        let new = write_vsix(
+            dir.path(),
+            "new.vsix",
+            manifest,
+            &[
+                ("extension/out/extension.js", b"new!"),
+                (
+                    "extension/node_modules/dep/index.js",
+                    b"module.exports = {};",
+                ),
+            ],
+        );
+        let diff = VsixDiff::new(&Vsix::open(&old).unwrap(), &Vsix::open(&new).unwrap());
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.removed[0].path, "extension/resources/icon.png");
+        assert_eq!(diff.changed[0].path, "extension/out/extension.js");
+        assert_eq!(diff.changed[0].delta, 1);
+        assert_eq!(diff.folders[0].folder, "node_modules");
+        assert_eq!(diff.warnings.len(), 2);
+    }
+}
@@ -0,0 +1,190 @@
+//! Summarizes the contents of a `.vsix` file for `zedc vsix inspect`.
+
+use super::{top_level_folder, Vsix, VsixEntry};
+use crate::util::format_bytes;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The identity declared in `extension.vsixmanifest`.
+#[derive(Serialize)]
+pub struct Identity {
+    pub id: Option<String>,
+    pub publisher: Option<String>,
+    pub version: Option<String>,
+    pub target_platform: Option<String>,
+}
+
+/// The total size of the files in one top-level folder of the extension.
+#[derive(Serialize)]
+pub struct FolderSize {
+    pub folder: String,
+    pub files: usize,
+    pub size: u64,
+}
+
+/// The result of `zedc vsix inspect`.
+#[derive(Serialize)]
+pub struct Inspection {
+    pub file: String,
+    pub id: String,
+    pub version: String,
+    pub display_name: Option<String>,
+    pub engines: BTreeMap<String, String>,
+    pub extension_dependencies: Vec<String>,
+    pub activation_events: Vec<String>,
+    pub identity: Identity,
+    pub file_size: u64,
+    pub uncompressed_size: u64,
+    pub file_count: usize,
+    /// Folder sizes, largest first.
+    pub folders: Vec<FolderSize>,
+    /// The largest files in the archive, largest first.
+    pub largest_files: Vec<VsixEntry>,
+}
+
+/// Returns the uncompressed size and file count per top-level folder, largest first.
+pub fn folder_sizes(entries: &[VsixEntry]) -> Vec<FolderSize> {
+    let mut folders: BTreeMap<&str, (usize, u64)> = BTreeMap::new();
+    for entry in entries {
+        let folder = folders.entry(top_level_folder(&entry.path)).or_default();
+        folder.0 += 1;
+        folder.1 += entry.size;
+    }
+    let mut folders: Vec<FolderSize> = folders
+        .into_iter()
+        .map(|(folder, (files, size))| FolderSize {
+            folder: folder.to_owned(),
+            files,
+            size,
+        })
+        .collect();
+    folders.sort_by_key(|f| std::cmp::Reverse(f.size));
+    folders
+}
+
+/// Returns the `top` largest files in the archive, largest first.
+pub fn largest_files(entries: &[VsixEntry], top: usize) -> Vec<VsixEntry> {
+    let mut files = entries.to_vec();
+    files.sort_by_key(|e| std::cmp::Reverse(e.size));
+    files.truncate(top);
+    files
+}
+
+impl Inspection {
+    /// Summarizes a `.vsix` file, listing the `top` largest files.
+    pub fn new(vsix: &Vsix, top: usize) -> Self {
+        let manifest = &vsix.manifest;
+        Self {
+            file: vsix.path.to_string_lossy().into_owned(),
+            id: manifest.id(),
+            version: manifest.version.clone(),
+            display_name: manifest.display_name.clone(),
+            engines: manifest.engines.clone(),
+            extension_dependencies: manifest.extension_dependencies.clone(),
+            activation_events: manifest.activation_events.clone(),
+            identity: Identity {
+                id: vsix.identity_attribute("Id"),
+                publisher: vsix.identity_attribute("Publisher"),
+                version: vsix.identity_attribute("Version"),
+                target_platform: vsix.identity_attribute("TargetPlatform"),
+            },
+            file_size: vsix.file_size,
+            uncompressed_size: vsix.uncompressed_size(),
+            file_count: vsix.entries.len(),
+            folders: folder_sizes(&vsix.entries),
+            largest_files: largest_files(&vsix.entries, top),
+        }
+    }
+
+    /// Prints the summary in human-readable form.
+    pub fn print(&self) {
+        println!("{}", format!("{}@{}", self.id, self.version).bold().blue());
+        if let Some(name) = &self.display_name {
+            println!("  {} {}", "Name:".dimmed(), name);
+        }
+        println!(
+            "  {} {}",
+            "VS Code engine:".dimmed(),
+            self.engines
+                .get("vscode")
+                .map(String::as_str)
+                .unwrap_or("none")
+        );
+        if let Some(platform) = &self.identity.target_platform {
+            println!("  {} {}", "Target platform:".dimmed(), platform);
+        }
+        println!(
+            "  {} {} ({} uncompressed, {} files)",
+            "Size:".dimmed(),
+            format_bytes(self.file_size),
+            format_bytes(self.uncompressed_size),
+            self.file_count
+        );
+
+        println!("\n{}", "Extension dependencies".underline());
+        if self.extension_dependencies.is_empty() {
+            println!("  (none)");
+        }
+        for dep in &self.extension_dependencies {
+            println!("  {}", dep);
+        }
+
+        println!("\n{}", "Activation events".underline());
+        if self.activation_events.is_empty() {
+            println!("  (none)");
+        }
+        for event in &self.activation_events {
+            println!("  {}", event);
+        }
+
+        println!("\n{}", "Size by folder".underline());
+        for folder in &self.folders {
+            println!(
+                "  {:>10}  {:<24} {} file{}",
+                format_bytes(folder.size),
+                folder.folder,
+                folder.files,
+                if folder.files == 1 { "" } else { "s" }
+            );
+        }
+
+        println!("\n{}", "Largest files".underline());
+        for file in &self.largest_files {
+            println!("  {:>10}  {}", format_bytes(file.size), file.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, size: u64) -> VsixEntry {
+        VsixEntry {
+            path: path.to_owned(),
+            size,
+            compressed_size: size,
+            crc32: 0,
+        }
+    }
+
+    #[test]
+    fn groups_sizes_by_folder() {
+        let entries = [
+            entry("extension/out/extension.js", 300),
+            entry("extension/out/extension.js.map", 200),
+            entry("extension/resources/icon.png", 100),
+            entry("extension/package.json", 50),
+        ];
+        let folders = folder_sizes(&entries);
+        assert_eq!(folders[0].folder, "out");
+        assert_eq!(folders[0].files, 2);
+        assert_eq!(folders[0].size, 500);
+        assert_eq!(folders.len(), 3);
+
+        let largest = largest_files(&entries, 2);
+        assert_eq!(largest.len(), 2);
+        assert_eq!(largest[0].path, "extension/out/extension.js");
+    }
+}
@@ -0,0 +1,267 @@
+//! "Root" module for reading VS Code extension packages (`.vsix` files).
+//!
+//! A VSIX is a ZIP archive with the extension's `package.json` stored under `extension/`, next to
+//! an `extension.vsixmanifest` describing the package for the marketplace.
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+mod budget;
+mod cmd;
+mod diff;
+mod inspect;
+pub mod registry;
+pub use cmd::{handle_cmd, Commands};
+
+/// Prefix of the extension's files inside a `.vsix` archive.
+const EXTENSION_DIR: &str = "extension/";
+
+/// The fields of an extension's `package.json` that zedc relies on.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct VsixManifest {
+    pub publisher: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub engines: BTreeMap<String, String>,
+    #[serde(default)]
+    pub extension_dependencies: Vec<String>,
+    #[serde(default)]
+    pub activation_events: Vec<String>,
+}
+
+impl VsixManifest {
+    /// Returns the extension identifier (`<publisher>.<name>`), lowercased like VS Code does.
+    pub fn id(&self) -> String {
+        format!("{}.{}", self.publisher, self.name).to_lowercase()
+    }
+
+    /// Returns the `engines.vscode` requirement of the extension, if any (e.g. `^1.90.0`).
+    pub fn vscode_engine(&self) -> Option<&str> {
+        self.engines.get("vscode").map(String::as_str)
+    }
+}
+
+/// A file stored in a `.vsix` archive.
+#[derive(Serialize, Clone, Debug)]
+pub struct VsixEntry {
+    /// The path of the file inside the archive (e.g. `extension/out/src/extension.js`).
+    pub path: String,
+    /// The uncompressed size of the file in bytes.
+    pub size: u64,
+    /// The compressed size of the file in bytes.
+    pub compressed_size: u64,
+    /// The CRC-32 checksum of the file contents, used to detect changes between builds.
+    #[serde(skip)]
+    pub crc32: u32,
+}
+
+/// The contents of a `.vsix` file: its manifests and file list.
+pub struct Vsix {
+    pub path: PathBuf,
+    /// The size of the `.vsix` file on disk.
+    pub file_size: u64,
+    pub manifest: VsixManifest,
+    /// The full `extension/package.json`, for fields not covered by [`VsixManifest`].
+    pub package_json: Value,
+    /// The contents of `extension.vsixmanifest`, if present.
+    pub vsixmanifest: Option<String>,
+    /// All files in the archive (directories are skipped).
+    pub entries: Vec<VsixEntry>,
+}
+
+impl Vsix {
+    /// Reads the manifests and file list of a `.vsix` file.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the `.vsix` file
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let file_size = file.metadata()?.len();
+        let mut archive = zip::ZipArchive::new(file)
+            .with_context(|| format!("{} is not a valid VSIX archive", path.display()))?;
+
+        let mut entries = Vec::new();
+        for i in 0..archive.len() {
+            let entry = archive.by_index(i)?;
+            if entry.is_dir() {
+                continue;
+            }
+            entries.push(VsixEntry {
+                path: entry.name().to_owned(),
+                size: entry.size(),
+                compressed_size: entry.compressed_size(),
+                crc32: entry.crc32(),
+            });
+        }
+
+        let package_json =
+            read_entry(&mut archive, "extension/package.json").with_context(|| {
+                format!("{} does not contain extension/package.json", path.display())
+            })?;
+        let package_json: Value = serde_json::from_str(&package_json)
+            .with_context(|| format!("Invalid extension manifest in {}", path.display()))?;
+        let manifest = serde_json::from_value(package_json.clone())
+            .with_context(|| format!("Invalid extension manifest in {}", path.display()))?;
+        let vsixmanifest = read_entry(&mut archive, "extension.vsixmanifest").ok();
+
+        Ok(Self {
+            path: path.to_owned(),
+            file_size,
+            manifest,
+            package_json,
+            vsixmanifest,
+            entries,
+        })
+    }
+
+    /// Returns the total uncompressed size of all files in the archive.
+    pub fn uncompressed_size(&self) -> u64 {
+        self.entries.iter().map(|e| e.size).sum()
+    }
+
+    /// Returns the value of an attribute of the `<Identity>` element in `extension.vsixmanifest`.
+    pub fn identity_attribute(&self, name: &str) -> Option<String> {
+        let xml = self.vsixmanifest.as_deref()?;
+        let start = xml.find("<Identity")?;
+        let tag = &xml[start..start + xml[start..].find('>')?];
+        let needle = format!(" {}=\"", name);
+        let value_start = tag.find(&needle)? + needle.len();
+        let value_end = value_start + tag[value_start..].find('"')?;
+        Some(tag[value_start..value_end].to_owned())
+    }
+}
+
+/// Returns the folder of the extension that a file belongs to (e.g. `out` for
+/// `extension/out/src/extension.js`). Files at the root of the extension are grouped under `.`,
+/// and archive metadata outside of `extension/` under `(vsix)`.
+pub fn top_level_folder(path: &str) -> &str {
+    match path.strip_prefix(EXTENSION_DIR) {
+        Some(rest) => match rest.split_once('/') {
+            Some((folder, _)) => folder,
+            None => ".",
+        },
+        None => "(vsix)",
+    }
+}
+
+/// Reads a file from the archive as a string.
+fn read_entry(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> anyhow::Result<String> {
+    let mut contents = String::new();
+    archive.by_name(name)?.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+/// Reads the manifest (`extension/package.json`) from a `.vsix` file.
+///
+/// # Arguments
+/// * `path` - The path to the `.vsix` file
+pub fn read_manifest(path: &Path) -> anyhow::Result<VsixManifest> {
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("{} is not a valid VSIX archive", path.display()))?;
+    let contents = read_entry(&mut archive, "extension/package.json")
+        .with_context(|| format!("{} does not contain extension/package.json", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid extension manifest in {}", path.display()))
+}
+
+/// Returns the lowest VS Code version allowed by an `engines.vscode` requirement (e.g. `^1.90.0` -> `1.90.0`).
+pub fn engine_floor(requirement: &str) -> Option<String> {
+    let version = requirement
+        .trim()
+        .trim_start_matches(['^', '~', '>', '=', 'v', ' ']);
+    let parts: Vec<&str> = version.split('.').collect();
+    if parts.len() != 3 || parts.iter().any(|p| p.parse::<u32>().is_err()) {
+        return None;
+    }
+    Some(version.to_owned())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Writes a `.vsix` containing the given `package.json` and files to a directory.
+    pub(crate) fn write_vsix(
+        dir: &Path,
+        file_name: &str,
+        package_json: &str,
+        files: &[(&str, &[u8])],
+    ) -> PathBuf {
+        let path = dir.join(file_name);
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("extension/package.json", options).unwrap();
+        zip.write_all(package_json.as_bytes()).unwrap();
+        for (name, contents) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn reads_manifest_from_vsix() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_vsix(
+            dir.path(),
+            "ze.vsix",
+            r#"{"publisher":"Zowe","name":"vscode-extension-for-zowe","version":"3.2.0","engines":{"vscode":"^1.90.0"},"extensionDependencies":["zowe.zowe-explorer-api"]}"#,
+            &[],
+        );
+        let manifest = read_manifest(&path).unwrap();
+        assert_eq!(manifest.id(), "zowe.vscode-extension-for-zowe");
+        assert_eq!(manifest.version, "3.2.0");
+        assert_eq!(manifest.vscode_engine(), Some("^1.90.0"));
+        assert_eq!(manifest.extension_dependencies, ["zowe.zowe-explorer-api"]);
+    }
+
+    #[test]
+    fn reads_files_and_identity() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_vsix(
+            dir.path(),
+            "ze.vsix",
+            r#"{"publisher":"Zowe","name":"ze","version":"3.2.0"}"#,
+            &[
+                ("extension/out/extension.js", b"console.log(1);"),
+                ("extension/README.md", b"# ZE"),
+                (
+                    "extension.vsixmanifest",
+                    br#"<PackageManifest><Metadata><Identity Language="en-US" Id="ze" Version="3.2.0" Publisher="Zowe" /></Metadata></PackageManifest>"#,
+                ),
+            ],
+        );
+        let vsix = Vsix::open(&path).unwrap();
+        assert_eq!(vsix.entries.len(), 4);
+        assert_eq!(
+            vsix.identity_attribute("Publisher").as_deref(),
+            Some("Zowe")
+        );
+        assert_eq!(vsix.identity_attribute("TargetPlatform"), None);
+        assert_eq!(top_level_folder("extension/out/extension.js"), "out");
+        assert_eq!(top_level_folder("extension/README.md"), ".");
+        assert_eq!(top_level_folder("[Content_Types].xml"), "(vsix)");
+    }
+
+    #[test]
+    fn parses_engine_floor() {
+        assert_eq!(engine_floor("^1.90.0").as_deref(), Some("1.90.0"));
+        assert_eq!(engine_floor(">=1.79.2").as_deref(), Some("1.79.2"));
+        assert_eq!(engine_floor("*"), None);
+    }
+}
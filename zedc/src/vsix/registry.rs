@@ -0,0 +1,147 @@
+//! Sources for `.vsix` files that were not passed on the command line: the local VSIX cache in
+//! `zedc_data/vsix-cache` and an Open VSX-compatible registry.
+
+use super::VsixManifest;
+use anyhow::{bail, Context};
+use reqwest::Client;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that points zedc at an Open VSX-compatible registry (e.g. a local stand-in).
+pub const OPENVSX_URL_ENV: &str = "ZEDC_OPENVSX_URL";
+/// The public Open VSX registry, used when [`OPENVSX_URL_ENV`] is not set.
+pub const OPENVSX_URL: &str = "https://open-vsx.org";
+
+/// Returns the directory of cached `.vsix` files (`zedc_data/vsix-cache`).
+pub fn cache_dir() -> anyhow::Result<PathBuf> {
+    Ok(crate::util::data_dir()?.join("vsix-cache"))
+}
+
+/// Returns the newest `.vsix` file for an extension in a cache directory.
+///
+/// Files are matched by the identifier in their manifest, so any `.vsix` file copied into the
+/// directory is picked up regardless of its name.
+///
+/// # Arguments
+/// * `dir` - The cache directory
+/// * `id` - The extension identifier (`<publisher>.<name>`)
+pub fn find_cached(dir: &Path, id: &str) -> Option<(PathBuf, VsixManifest)> {
+    let entries = std::fs::read_dir(dir).ok()?;
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "vsix"))
+        .filter_map(|p| super::read_manifest(&p).ok().map(|m| (p, m)))
+        .filter(|(_, m)| m.id().eq_ignore_ascii_case(id))
+        .max_by(|(_, a), (_, b)| crate::code::compare_versions(&a.version, &b.version))
+}
+
+#[derive(Deserialize)]
+struct RegistryFiles {
+    download: String,
+}
+
+#[derive(Deserialize)]
+struct RegistryExtension {
+    version: String,
+    files: RegistryFiles,
+}
+
+/// Client for an Open VSX-compatible extension registry.
+pub struct Registry {
+    base_url: String,
+    client: Client,
+}
+
+impl Registry {
+    /// Creates a client for the registry at the given base URL (e.g. `https://open-vsx.org`).
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            client: Client::new(),
+        }
+    }
+
+    /// Creates a client for the registry in [`OPENVSX_URL_ENV`], or the public Open VSX registry.
+    pub fn from_env() -> Self {
+        match std::env::var(OPENVSX_URL_ENV) {
+            Ok(url) if !url.trim().is_empty() => Self::new(url.trim()),
+            _ => Self::new(OPENVSX_URL),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Downloads the latest version of an extension into a directory, returning the `.vsix` path.
+    ///
+    /// # Arguments
+    /// * `id` - The extension identifier (`<publisher>.<name>`)
+    /// * `dir` - The directory to save the `.vsix` file in, usually [`cache_dir`]
+    pub async fn download(&self, id: &str, dir: &Path) -> anyhow::Result<PathBuf> {
+        let Some((namespace, name)) = id.split_once('.') else {
+            bail!("Invalid extension identifier: {}", id);
+        };
+        let url = format!("{}/api/{}/{}/latest", self.base_url, namespace, name);
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to query {}", url))?;
+        if !resp.status().is_success() {
+            bail!("{} returned HTTP {}", url, resp.status());
+        }
+        let body = resp.text().await?;
+        let extension: RegistryExtension = serde_json::from_str(&body)
+            .with_context(|| format!("Invalid extension metadata from {}", url))?;
+
+        let bytes = fetch(&self.client, &extension.files.download).await?;
+
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}-{}.vsix", id.to_lowercase(), extension.version));
+        std::fs::write(&path, &bytes)?;
+        Ok(path)
+    }
+}
+
+/// Downloads a file, failing on HTTP error statuses.
+async fn fetch(client: &Client, url: &str) -> anyhow::Result<Vec<u8>> {
+    let resp = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to download {}", url))?;
+    if !resp.status().is_success() {
+        bail!("{} returned HTTP {}", url, resp.status());
+    }
+    Ok(resp.bytes().await?.to_vec())
+}
+
+/// Downloads a `.vsix` file from a URL into a directory, returning its path. The file is named
+/// after the extension identifier and version in its manifest, like registry downloads.
+///
+/// # Arguments
+/// * `url` - The `http(s)://` URL of the `.vsix` file
+/// * `dir` - The directory to save the `.vsix` file in, usually [`cache_dir`]
+pub async fn download_url(url: &str, dir: &Path) -> anyhow::Result<PathBuf> {
+    let bytes = fetch(&Client::new(), url).await?;
+    std::fs::create_dir_all(dir)?;
+    let partial = dir.join(".download.vsix");
+    std::fs::write(&partial, &bytes)?;
+    let manifest = match super::read_manifest(&partial) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial);
+            return Err(e.context(format!("{} is not a valid .vsix file", url)));
+        }
+    };
+    let path = dir.join(format!(
+        "{}-{}.vsix",
+        manifest.id().to_lowercase(),
+        manifest.version
+    ));
+    std::fs::rename(&partial, &path)?;
+    Ok(path)
+}